@@ -1,13 +1,13 @@
 use crate::error::ContractError;
 use crate::error::ContractError::{
-    AlreadyVested, Cw20Error, IncorrectFundsSupplied, NoFundsSupplied, Unauthorized,
+    Cw20Error, IncorrectFundsSupplied, NoFundsSupplied, Unauthorized,
 };
 use ::cw20_base::ContractError as Cw20ContractError;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response, StdError,
-    StdResult, Uint128,
+    to_json_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Storage, Uint128, Uint256,
 };
 use cw2::set_contract_version;
 use cw20::BalanceResponse;
@@ -18,7 +18,9 @@ use crate::msg::{
     ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, TotalSupplyResponse, UpdateConfigMsg,
     VestedAmountResponse, WithdrawableAmountResponse,
 };
-use crate::state::{Allocation, Config, Schedule, ALLOCATIONS, CONFIG};
+use crate::state::{
+    load_allocations, may_load_allocations, save_allocations, Allocation, Config, Schedule, CONFIG,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:credits";
@@ -29,11 +31,19 @@ pub const TOKEN_SYMBOL: &str = "cNTRN";
 pub const TOKEN_DECIMALS: u8 = 6;
 pub const DEPOSITED_SYMBOL: &str = "untrn";
 
-// Cliff duration in seconds for vesting.
+// Cliff duration in seconds for vesting if neither the vesting nor the config sets one.
 // Before the schedule.start_time + schedule.cliff vesting does not start.
 // 0 cliff means no cliff
 pub const VESTING_CLIFF: u64 = 0;
 
+// Max default cliff in seconds (4 years), so that a mistyped default
+// can't lock every new allocation for good
+pub const MAX_DEFAULT_CLIFF: u64 = 4 * 365 * 24 * 60 * 60;
+
+// Max number of allocations an address can receive with transfers,
+// so that iterating over them can't run out of gas
+pub const MAX_ALLOCATIONS: usize = 32;
+
 /// Instantiates the contract.
 /// Configures cw20 token info.
 /// Can specify addresses for dao, airdrop and lockdrop contracts.
@@ -54,6 +64,8 @@ pub fn instantiate(
         airdrop_address: None,
         lockdrop_address: None,
         when_withdrawable: None,
+        default_cliff: None,
+        user_transfers_enabled: false,
     };
 
     // store token info
@@ -88,14 +100,43 @@ pub fn execute(
             amount,
             start_time,
             duration,
-        } => execute_add_vesting(deps, env, info, address, amount, start_time, duration),
+            cliff,
+        } => execute_add_vesting(
+            deps, env, info, address, amount, start_time, duration, cliff,
+        ),
         ExecuteMsg::Transfer { recipient, amount } => {
             execute_transfer(deps, env, info, recipient, amount)
         }
-        ExecuteMsg::Withdraw {} => execute_withdraw(deps, env, info),
+        ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => execute_transfer_from(deps, env, info, owner, recipient, amount),
+        ExecuteMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => ::cw20_base::allowances::execute_increase_allowance(
+            deps, env, info, spender, amount, expires,
+        )
+        .map_err(Cw20Error),
+        ExecuteMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => ::cw20_base::allowances::execute_decrease_allowance(
+            deps, env, info, spender, amount, expires,
+        )
+        .map_err(Cw20Error),
+        ExecuteMsg::Withdraw { amount, recipient } => {
+            execute_withdraw(deps, env, info, amount, recipient)
+        }
         ExecuteMsg::Burn { amount } => execute_burn(deps, env, info, amount),
         ExecuteMsg::BurnFrom { owner, amount } => execute_burn_from(deps, env, info, owner, amount),
         ExecuteMsg::Mint {} => execute_mint(deps, env, info),
+        ExecuteMsg::RevokeAllocation { address } => {
+            execute_revoke_allocation(deps, env, info, address)
+        }
     }
 }
 
@@ -128,12 +169,28 @@ pub fn execute_update_config(
         config.when_withdrawable = Some(when_withdrawable);
     }
 
+    // a zero default cliff clears it, since allocations have no cliff by default
+    if let Some(default_cliff) = msg.default_cliff {
+        if default_cliff > MAX_DEFAULT_CLIFF {
+            return Err(ContractError::DefaultCliffTooLong {
+                cliff: default_cliff,
+                max: MAX_DEFAULT_CLIFF,
+            });
+        }
+        config.default_cliff = (default_cliff != VESTING_CLIFF).then_some(default_cliff);
+    }
+
+    if let Some(user_transfers_enabled) = msg.user_transfers_enabled {
+        config.user_transfers_enabled = user_transfers_enabled;
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::default())
 }
 
 /// Adds vesting settings for the specified `address` and `amount` for `duration`.
+/// Every call adds a separate allocation with its own schedule to the address.
 /// `amount` expected to be equal to amount on a user balance.
 /// Returns a default object of type [`Response`].
 ///
@@ -149,6 +206,10 @@ pub fn execute_update_config(
 /// * **start_time** is an object of type [`u64`]. Vesting starts after `start_time`. Specified in UNIX time in seconds.
 ///
 /// * **duration** is an object of type [`u64`]. Duration of vesting. Specified in seconds.
+///
+/// * **cliff** is an optional object of type [`u64`]. Cliff of vesting. Specified in seconds.
+/// Falls back to the default cliff from the config, or no cliff if neither is set.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_add_vesting(
     deps: DepsMut,
     _env: Env,
@@ -157,6 +218,7 @@ pub fn execute_add_vesting(
     amount: Uint128,
     start_time: u64,
     duration: u64,
+    cliff: Option<u64>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let airdrop_address = config
@@ -166,37 +228,49 @@ pub fn execute_add_vesting(
         return Err(Unauthorized);
     }
 
-    let user_balance = cw20_base::contract::query_balance(deps.as_ref(), address.clone())?;
-    if user_balance.balance.lt(&amount) {
-        return Err(NoFundsSupplied());
+    let cliff = cliff.or(config.default_cliff).unwrap_or(VESTING_CLIFF);
+    if cliff > duration {
+        return Err(ContractError::CliffExceedsDuration { cliff, duration });
     }
 
     let vested_to = deps.api.addr_validate(&address)?;
+    let mut allocations = may_load_allocations(deps.storage, &vested_to)?.unwrap_or_default();
 
-    ALLOCATIONS.update(
-        deps.storage,
-        &vested_to,
-        |o: Option<Allocation>| -> Result<Allocation, ContractError> {
-            match o {
-                Some(_) => Err(AlreadyVested { address }),
-                None => Ok(Allocation {
-                    allocated_amount: amount,
-                    withdrawn_amount: Uint128::zero(),
-                    schedule: Schedule {
-                        start_time,
-                        cliff: VESTING_CLIFF,
-                        duration,
-                    },
-                }),
-            }
+    // the balance has to back the locked part of the existing allocations as well
+    let locked_amount = allocations
+        .iter()
+        .try_fold(amount, |total, allocation| {
+            total.checked_add(
+                allocation
+                    .allocated_amount
+                    .saturating_sub(allocation.withdrawn_amount),
+            )
+        })
+        .map_err(StdError::from)?;
+    let user_balance = cw20_base::contract::query_balance(deps.as_ref(), address)?;
+    if user_balance.balance.lt(&locked_amount) {
+        return Err(NoFundsSupplied());
+    }
+
+    allocations.push(Allocation {
+        allocated_amount: amount,
+        withdrawn_amount: Uint128::zero(),
+        schedule: Schedule {
+            start_time,
+            cliff,
+            duration,
         },
-    )?;
+        revoked_at: None,
+    });
+    save_allocations(deps.storage, &vested_to, allocations)?;
 
     Ok(Response::default())
 }
 
 /// Transfers specified `amount` from sender to the specified `recipient`.
-/// Standard cw20 transfer. Allowed to execute only for an airdrop contract.
+/// Standard cw20 transfer for an airdrop contract.
+/// If user transfers are enabled, other holders can transfer as well,
+/// and a proportional slice of their allocations moves to the `recipient` (see [`move_allocations`]).
 /// Returns a default object of type [`Response`].
 ///
 /// ## Params
@@ -210,7 +284,7 @@ pub fn execute_add_vesting(
 ///
 /// * **amount** is an object of type [`Uint128`]. Amount to be transferred.
 pub fn execute_transfer(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     recipient: String,
@@ -220,19 +294,97 @@ pub fn execute_transfer(
     let airdrop_address = config
         .airdrop_address
         .ok_or(ContractError::AirdropNotConfigured)?;
-    if info.sender != airdrop_address {
+    if info.sender == airdrop_address {
+        return ::cw20_base::contract::execute_transfer(deps, env, info, recipient, amount)
+            .map_err(Cw20Error);
+    }
+    if !config.user_transfers_enabled {
+        return Err(Unauthorized);
+    }
+
+    let owner = info.sender.clone();
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let owner_balance = BALANCES.may_load(deps.storage, &owner)?.unwrap_or_default();
+    let now = env.block.time.seconds();
+
+    let res = ::cw20_base::contract::execute_transfer(deps.branch(), env, info, recipient, amount)
+        .map_err(Cw20Error)?;
+    move_allocations(
+        deps.storage,
+        &owner,
+        &recipient_addr,
+        amount,
+        owner_balance,
+        now,
+    )?;
+
+    Ok(res)
+}
+
+/// Transfers specified `amount` from `owner` to the specified `recipient` using an allowance of the sender.
+/// Standard cw20 transfer from. Allowed to execute only if user transfers are enabled.
+/// A proportional slice of the owner's allocations moves to the `recipient` (see [`move_allocations`]).
+/// Returns a default object of type [`Response`].
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **info** is an object of type [`MessageInfo`]
+///
+/// * **owner** is an object of type [`String`]. Address to transfer from.
+///
+/// * **recipient** is an object of type [`String`]. Address to transfer to.
+///
+/// * **amount** is an object of type [`Uint128`]. Amount to be transferred.
+pub fn execute_transfer_from(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.user_transfers_enabled {
         return Err(Unauthorized);
     }
 
-    ::cw20_base::contract::execute_transfer(deps, env, info, recipient, amount).map_err(Cw20Error)
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let owner_balance = BALANCES
+        .may_load(deps.storage, &owner_addr)?
+        .unwrap_or_default();
+    let now = env.block.time.seconds();
+
+    let res = ::cw20_base::allowances::execute_transfer_from(
+        deps.branch(),
+        env,
+        info,
+        owner,
+        recipient,
+        amount,
+    )
+    .map_err(Cw20Error)?;
+    move_allocations(
+        deps.storage,
+        &owner_addr,
+        &recipient_addr,
+        amount,
+        owner_balance,
+        now,
+    )?;
+
+    Ok(res)
 }
 
 /// Calculates amount that is already unlocked from vesting for sender,
-/// burns this amount of cNTRN tokens and sends 1:1 of untrn tokens proportion to the sender.
+/// burns this amount of cNTRN tokens and sends 1:1 of untrn tokens proportion to the `recipient`.
 ///
 /// Available to execute only after `config.when_withdrawable` time.
 ///
-/// Returns error if nothing left to withdraw.
+/// Returns error if nothing left to withdraw or `amount` exceeds the withdrawable amount.
 ///
 /// Returns a default object of type [`Response`].
 ///
@@ -242,10 +394,16 @@ pub fn execute_transfer(
 /// * **env** is an object of type [`Env`]
 ///
 /// * **info** is an object of type [`MessageInfo`]
+///
+/// * **amount** is an optional object of type [`Uint128`]. Amount to withdraw, all the withdrawable amount if not set.
+///
+/// * **recipient** is an optional object of type [`String`]. Address to send untrn tokens to, the sender if not set.
 pub fn execute_withdraw(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    amount: Option<Uint128>,
+    recipient: Option<String>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let when_withdrawable = config
@@ -256,35 +414,54 @@ pub fn execute_withdraw(
     }
 
     let owner = info.sender.clone();
-    let mut allocation: Allocation = ALLOCATIONS.load(deps.storage, &owner)?;
-    let max_withdrawable_amount = compute_withdrawable_amount(
-        allocation.allocated_amount,
-        allocation.withdrawn_amount,
-        &allocation.schedule,
-        env.block.time.seconds(),
-    )?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => owner.clone(),
+    };
+    let mut allocations = load_allocations(deps.storage, &owner)?;
+    let max_withdrawable_amount =
+        compute_total_withdrawable_amount(&allocations, env.block.time.seconds())?;
 
     if max_withdrawable_amount.is_zero() {
         return Err(ContractError::NoFundsToClaim);
     }
 
-    // Guard against the case where actual balance is smaller than max withdrawable amount.
-    // That can happen if user already withdrawn some funds as rewards for lockdrop participation through burn_from (skipping vesting).
-    // Example: user had 100 cNTRN on balance, and burned 100 cNTRN through burn_from.
-    // Suppose vesting period fully ended. In that case `compute_withdrawable_amount()` will return 100 untrn,
-    // although he has 0 on balance.
     let actual_balance = BALANCES.load(deps.storage, &owner)?;
-    let to_withdraw = max_withdrawable_amount.min(actual_balance);
+    let withdrawable_amount = cap_withdrawable_amount(max_withdrawable_amount, actual_balance);
 
     // Check that not zero
-    if to_withdraw.is_zero() {
+    if withdrawable_amount.is_zero() {
         return Err(ContractError::NoFundsToClaim);
     }
 
-    allocation.withdrawn_amount += to_withdraw;
-    ALLOCATIONS.save(deps.storage, &owner, &allocation)?;
+    let to_withdraw = match amount {
+        Some(amount) if amount.is_zero() => return Err(ContractError::NoFundsToClaim),
+        Some(amount) if amount > withdrawable_amount => {
+            return Err(ContractError::WithdrawAmountExceedsWithdrawable {
+                amount,
+                withdrawable: withdrawable_amount,
+            })
+        }
+        Some(amount) => amount,
+        None => withdrawable_amount,
+    };
 
-    burn_and_send(deps, env, info, to_withdraw)
+    // the withdrawn amount is taken from the allocations in the order they were added
+    let mut left_to_withdraw = to_withdraw;
+    for allocation in allocations.iter_mut() {
+        let withdrawable_amount = compute_withdrawable_amount(
+            allocation.allocated_amount,
+            allocation.withdrawn_amount,
+            &allocation.schedule,
+            allocation.vesting_time(env.block.time.seconds()),
+        )?;
+        let withdrawn_amount = withdrawable_amount.min(left_to_withdraw);
+        allocation.withdrawn_amount += withdrawn_amount;
+        left_to_withdraw -= withdrawn_amount;
+    }
+    save_allocations(deps.storage, &owner, allocations)?;
+
+    burn_and_send(deps, env, info, to_withdraw, recipient.to_string())
 }
 
 /// Withdraws specified `amount` of tokens -
@@ -317,7 +494,8 @@ pub fn execute_burn(
         return Err(Unauthorized);
     }
 
-    burn_and_send(deps, env, info, amount)
+    let to_address = info.sender.to_string();
+    burn_and_send(deps, env, info, amount, to_address)
 }
 
 /// Withdraws specified `amount` of tokens from specified `owner` -
@@ -358,7 +536,8 @@ pub fn execute_burn_from(
     // burn funds of `owner`, but skip the vesting stage
     info.sender = deps.api.addr_validate(&owner)?;
 
-    burn_and_send(deps, env, info, amount)
+    let to_address = info.sender.to_string();
+    burn_and_send(deps, env, info, amount, to_address)
 }
 
 /// Mints cNTRN tokens in 1:1 proportion to sent untrn ones
@@ -388,6 +567,98 @@ pub fn execute_mint(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
         .map_err(Cw20Error)
 }
 
+/// Revokes all the allocations of specified `address` that are not revoked yet:
+/// stops their vesting at the current time, burns the still unvested cNTRN tokens of the `address`
+/// and sends amount in 1:1 proportion of untrn tokens to the DAO.
+/// Whatever had vested before the revocation and was not withdrawn yet stays available to `withdraw`.
+///
+/// Only available for the DAO.
+///
+/// Returns error if all the allocations of the `address` are already revoked.
+///
+/// Returns a default object of type [`Response`] with the revocation recorded in attributes.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **address** is an object of type [`String`]. Address to revoke allocations of.
+pub fn execute_revoke_allocation(
+    deps: DepsMut,
+    env: Env,
+    mut info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.dao_address {
+        return Err(Unauthorized);
+    }
+
+    let holder = deps.api.addr_validate(&address)?;
+    let now = env.block.time.seconds();
+    let mut allocations = load_allocations(deps.storage, &holder)?;
+    let mut unvested_amount = Uint128::zero();
+    let mut revoked_count = 0u64;
+    for allocation in allocations
+        .iter_mut()
+        .filter(|allocation| allocation.revoked_at.is_none())
+    {
+        let vested_amount = compute_withdrawable_amount(
+            allocation.allocated_amount,
+            Uint128::zero(),
+            &allocation.schedule,
+            now,
+        )?;
+        unvested_amount += allocation.allocated_amount - vested_amount;
+        allocation.revoked_at = Some(now);
+        revoked_count += 1;
+    }
+    if revoked_count == 0 {
+        return Err(ContractError::AllocationAlreadyRevoked);
+    }
+    let mut unwithdrawn_vested_amount = Uint128::zero();
+    for allocation in &allocations {
+        let vested_amount = compute_withdrawable_amount(
+            allocation.allocated_amount,
+            Uint128::zero(),
+            &allocation.schedule,
+            allocation.vesting_time(now),
+        )?;
+        unwithdrawn_vested_amount += vested_amount.saturating_sub(allocation.withdrawn_amount);
+    }
+    save_allocations(deps.storage, &holder, allocations)?;
+
+    // rewards burned through burn_from could have left less than the unvested amount on the balance,
+    // and the vested but not yet withdrawn part of the balance stays with the holder
+    let balance = BALANCES
+        .may_load(deps.storage, &holder)?
+        .unwrap_or_default();
+    let revoked_amount = unvested_amount.min(balance.saturating_sub(unwithdrawn_vested_amount));
+
+    let res = if revoked_amount.is_zero() {
+        Response::new()
+    } else {
+        // burn funds of the holder and send them to the DAO
+        info.sender = holder.clone();
+        burn_and_send(
+            deps,
+            env,
+            info,
+            revoked_amount,
+            config.dao_address.to_string(),
+        )?
+    };
+
+    Ok(res
+        .add_attribute("action", "revoke_allocation")
+        .add_attribute("address", holder)
+        .add_attribute("revoked_allocations", revoked_count.to_string())
+        .add_attribute("revoked_amount", revoked_amount))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -398,6 +669,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_json_binary(&query_vested_amount(deps, env, address)?)
         }
         QueryMsg::Allocation { address } => to_json_binary(&query_allocation(deps, address)?),
+        QueryMsg::Allocations { address } => to_json_binary(&query_allocations(deps, address)?),
         QueryMsg::Balance { address } => {
             to_json_binary(&::cw20_base::contract::query_balance(deps, address)?)
         }
@@ -490,6 +762,7 @@ fn query_balance_at_height(
 }
 
 /// Returns amount for specified `address` that is available to `withdraw` from balance.
+/// The amount is capped by the balance the same way as on `withdraw` (see [`cap_withdrawable_amount`]).
 /// Returns an object of type [`StdResult<WithdrawableAmountResponse>`].
 /// Returns an error if no vesting was set up or no balance for such user exists.
 ///
@@ -505,22 +778,16 @@ fn query_withdrawable_amount(
     address: String,
 ) -> StdResult<WithdrawableAmountResponse> {
     let owner = deps.api.addr_validate(&address)?;
-    let allocation: Allocation = ALLOCATIONS.load(deps.storage, &owner)?;
-    let max_withdrawable_amount = compute_withdrawable_amount(
-        allocation.allocated_amount,
-        allocation.withdrawn_amount,
-        &allocation.schedule,
-        env.block.time.seconds(),
-    )?;
-    // // because we have lockdrop rewards that skip vesting, we can get withdrawable amount greater than the current balance
-    // // so we need to withdraw not more than the current balance
+    let allocations = load_allocations(deps.storage, &owner)?;
+    let max_withdrawable_amount =
+        compute_total_withdrawable_amount(&allocations, env.block.time.seconds())?;
     let actual_balance =
         BALANCES
             .may_load(deps.storage, &owner)?
             .ok_or_else(|| StdError::GenericErr {
                 msg: "No balance".to_string(),
             })?;
-    let amount = max_withdrawable_amount.min(actual_balance);
+    let amount = cap_withdrawable_amount(max_withdrawable_amount, actual_balance);
 
     Ok(WithdrawableAmountResponse { amount })
 }
@@ -542,23 +809,17 @@ pub fn query_vested_amount(
     address: String,
 ) -> StdResult<VestedAmountResponse> {
     let owner = deps.api.addr_validate(&address)?;
-    let allocation: Allocation = ALLOCATIONS.load(deps.storage, &owner)?;
-    let max_withdrawable_amount = compute_withdrawable_amount(
-        allocation.allocated_amount,
-        allocation.withdrawn_amount,
-        &allocation.schedule,
-        env.block.time.seconds(),
-    )?;
-    // because we have lockdrop rewards that skip vesting, we can get withdrawable amount greater than the current balance
-    // so we need to withdraw not more than the current balance
+    let allocations = load_allocations(deps.storage, &owner)?;
+    let max_withdrawable_amount =
+        compute_total_withdrawable_amount(&allocations, env.block.time.seconds())?;
     let actual_balance = BALANCES.load(deps.storage, &owner)?;
-    let withdrawable_amount = max_withdrawable_amount.min(actual_balance);
+    let withdrawable_amount = cap_withdrawable_amount(max_withdrawable_amount, actual_balance);
     let amount = actual_balance - withdrawable_amount;
 
     Ok(VestedAmountResponse { amount })
 }
 
-/// Returns current vesting allocation for specified `address`.
+/// Returns the first vesting allocation for specified `address`.
 /// Note that `allocation.withdrawn_amount` does not take burned rewards from `BurnFrom` into account.
 /// That means that `allocation.allocated_amount - allocation.withdrawn_amount` is not always equal to `withdrawable amount`
 /// Returns an object of type [`StdResult<Allocation>`].
@@ -570,7 +831,23 @@ pub fn query_vested_amount(
 /// * **address** is an object of type [`String`]. Address of the user we want to query withdrawable amount.
 fn query_allocation(deps: Deps, address: String) -> StdResult<Allocation> {
     let owner = deps.api.addr_validate(&address)?;
-    ALLOCATIONS.load(deps.storage, &owner)
+    load_allocations(deps.storage, &owner)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| StdError::not_found("credits::state::Allocation"))
+}
+
+/// Returns all vesting allocations for specified `address` in the order they were added.
+/// Returns an object of type [`StdResult<Vec<Allocation>>`].
+/// Returns an error if no vesting was set up for such user.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **address** is an object of type [`String`]. Address of the user we want to query allocations.
+fn query_allocations(deps: Deps, address: String) -> StdResult<Vec<Allocation>> {
+    let owner = deps.api.addr_validate(&address)?;
+    load_allocations(deps.storage, &owner)
 }
 
 fn try_find_untrns(funds: Vec<Coin>) -> Result<Uint128, ContractError> {
@@ -582,14 +859,14 @@ fn try_find_untrns(funds: Vec<Coin>) -> Result<Uint128, ContractError> {
     Ok(token.amount)
 }
 
-// burns cNTRN tokens and send untrn tokens to the sender
+// burns cNTRN tokens of the sender and send untrn tokens to `to_address`
 fn burn_and_send(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     amount: Uint128,
+    to_address: String,
 ) -> Result<Response, ContractError> {
-    let to_address = info.sender.to_string();
     let burn_response = ::cw20_base::contract::execute_burn(deps, env, info, amount)?;
     let send = BankMsg::Send {
         to_address,
@@ -599,6 +876,181 @@ fn burn_and_send(
     Ok(burn_response.add_message(send))
 }
 
+/// Moves a slice of every allocation of `owner` to `recipient` in proportion of `amount` to `owner_balance`,
+/// so that the vesting of the transferred tokens continues on the recipient with the same schedules.
+/// Both allocated and withdrawn amounts are split. A slice is merged into the recipient's allocation
+/// with the same schedule and revocation time, or added after the recipient's allocations otherwise.
+///
+/// The not yet withdrawn amounts of the slices are rounded so that they add up to the proportional share
+/// of the owner's not yet withdrawn amounts rounded up, which is exactly `amount` if the whole balance
+/// is allocated. So the owner never keeps allocations that are not backed by the balance.
+///
+/// The withdrawn amount of a slice is kept within what is vested on both sides at `current_time`,
+/// so that rounding never leaves an allocation with more withdrawn than vested.
+///
+/// ## Params
+/// * **storage** is an object of type [`&mut dyn Storage`].
+///
+/// * **owner** is an object of type [`&Addr`]. Address the tokens are transferred from.
+///
+/// * **recipient** is an object of type [`&Addr`]. Address the tokens are transferred to.
+///
+/// * **amount** is an object of type [`Uint128`]. Transferred amount.
+///
+/// * **owner_balance** is an object of type [`Uint128`]. Balance of the owner before the transfer.
+///
+/// * **current_time** is an object of type [`u64`]. Current UNIX time in seconds.
+fn move_allocations(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+    owner_balance: Uint128,
+    current_time: u64,
+) -> Result<(), ContractError> {
+    if owner == recipient || owner_balance.is_zero() {
+        return Ok(());
+    }
+    let mut owner_allocations = match may_load_allocations(storage, owner)? {
+        Some(allocations) => allocations,
+        None => return Ok(()),
+    };
+
+    let locked_amounts = owner_allocations
+        .iter()
+        .map(|allocation| {
+            allocation
+                .allocated_amount
+                .checked_sub(allocation.withdrawn_amount)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(StdError::from)?;
+    let total_locked = locked_amounts
+        .iter()
+        .try_fold(Uint128::zero(), |total, locked| total.checked_add(*locked))
+        .map_err(StdError::from)?;
+    if total_locked.is_zero() {
+        return Ok(());
+    }
+    let moved_total = multiply_ratio_ceil(amount, total_locked, owner_balance)?
+        .min(amount)
+        .min(total_locked);
+
+    let mut moved_locked_amounts = locked_amounts
+        .iter()
+        .map(|locked| locked.multiply_ratio(moved_total, total_locked))
+        .collect::<Vec<_>>();
+    // the remainder of the rounded down slices goes to the first allocations that can take it
+    let mut remainder = moved_total - moved_locked_amounts.iter().sum::<Uint128>();
+    for (moved, locked) in moved_locked_amounts.iter_mut().zip(&locked_amounts) {
+        if remainder.is_zero() {
+            break;
+        }
+        if *moved < *locked {
+            *moved += Uint128::one();
+            remainder -= Uint128::one();
+        }
+    }
+
+    let mut moved_allocations = vec![];
+    for ((allocation, locked), moved_locked) in owner_allocations
+        .iter_mut()
+        .zip(locked_amounts)
+        .zip(moved_locked_amounts)
+    {
+        if moved_locked.is_zero() {
+            continue;
+        }
+        let vesting_time = allocation.vesting_time(current_time);
+        let vested = |allocated_amount: Uint128| {
+            compute_withdrawable_amount(
+                allocated_amount,
+                Uint128::zero(),
+                &allocation.schedule,
+                vesting_time,
+            )
+        };
+        // the rounded down withdrawn amount never exceeds what the slice has vested,
+        // but may leave the owner with one token more withdrawn than vested
+        let mut moved_withdrawn = allocation
+            .withdrawn_amount
+            .multiply_ratio(moved_locked, locked);
+        let left_allocated = allocation.allocated_amount - moved_locked - moved_withdrawn;
+        if allocation.withdrawn_amount - moved_withdrawn > vested(left_allocated)? {
+            moved_withdrawn += Uint128::one();
+        }
+        let moved_allocated = moved_locked + moved_withdrawn;
+
+        allocation.allocated_amount -= moved_allocated;
+        allocation.withdrawn_amount -= moved_withdrawn;
+        // fails if no split of the withdrawn amount fits into what is vested on both sides
+        compute_withdrawable_amount(
+            allocation.allocated_amount,
+            allocation.withdrawn_amount,
+            &allocation.schedule,
+            vesting_time,
+        )?;
+        compute_withdrawable_amount(
+            moved_allocated,
+            moved_withdrawn,
+            &allocation.schedule,
+            vesting_time,
+        )?;
+        moved_allocations.push(Allocation {
+            allocated_amount: moved_allocated,
+            withdrawn_amount: moved_withdrawn,
+            schedule: allocation.schedule.clone(),
+            revoked_at: allocation.revoked_at,
+        });
+    }
+    owner_allocations.retain(|allocation| !allocation.allocated_amount.is_zero());
+    save_allocations(storage, owner, owner_allocations)?;
+
+    if !moved_allocations.is_empty() {
+        let mut recipient_allocations =
+            may_load_allocations(storage, recipient)?.unwrap_or_default();
+        for moved in moved_allocations {
+            match recipient_allocations.iter_mut().find(|allocation| {
+                allocation.schedule == moved.schedule && allocation.revoked_at == moved.revoked_at
+            }) {
+                Some(allocation) => {
+                    allocation.allocated_amount = allocation
+                        .allocated_amount
+                        .checked_add(moved.allocated_amount)
+                        .map_err(StdError::from)?;
+                    allocation.withdrawn_amount = allocation
+                        .withdrawn_amount
+                        .checked_add(moved.withdrawn_amount)
+                        .map_err(StdError::from)?;
+                }
+                None if recipient_allocations.len() >= MAX_ALLOCATIONS => {
+                    return Err(ContractError::TooManyAllocations {
+                        max: MAX_ALLOCATIONS,
+                    });
+                }
+                None => recipient_allocations.push(moved),
+            }
+        }
+        save_allocations(storage, recipient, recipient_allocations)?;
+    }
+
+    Ok(())
+}
+
+/// Returns `value * numerator / denominator` rounded up.
+fn multiply_ratio_ceil(
+    value: Uint128,
+    numerator: Uint128,
+    denominator: Uint128,
+) -> StdResult<Uint128> {
+    let floor = value.multiply_ratio(numerator, denominator);
+    if value.full_mul(numerator) == Uint256::from(floor).checked_mul(denominator.into())? {
+        Ok(floor)
+    } else {
+        Ok(floor.checked_add(Uint128::one())?)
+    }
+}
+
 /// Compute the max withdrawable amount based on the current timestamp and the vesting schedule
 ///
 /// The withdrawable amount is vesting amount minus the amount already withdrawn.
@@ -638,3 +1090,46 @@ pub fn compute_withdrawable_amount(
         .checked_sub(withdrawn_amount)
         .map_err(|overflow_err| overflow_err.into())
 }
+
+/// Caps the max withdrawable amount of a user by their balance, which is the amount actually paid on `withdraw`.
+///
+/// Guards against the case where actual balance is smaller than max withdrawable amount.
+/// That can happen if user already withdrawn some funds as rewards for lockdrop participation through burn_from (skipping vesting).
+/// Example: user had 100 cNTRN on balance, and burned 100 cNTRN through burn_from.
+/// Suppose vesting period fully ended. In that case `compute_withdrawable_amount()` will return 100 untrn,
+/// although he has 0 on balance.
+///
+/// ## Params
+/// * **max_withdrawable_amount** is an object of type [`Uint128`]. Max withdrawable amount of the allocations.
+///
+/// * **balance** is an object of type [`Uint128`]. Current cNTRN balance of the user.
+pub fn cap_withdrawable_amount(max_withdrawable_amount: Uint128, balance: Uint128) -> Uint128 {
+    max_withdrawable_amount.min(balance)
+}
+
+/// Compute the max withdrawable amount of all the allocations of a user
+/// based on the current timestamp and their vesting schedules.
+///
+/// Note that is does not take withdrawn rewards into account,
+/// so returned amount can be greater than the current user balance.
+///
+/// ## Params
+/// * **allocations** is an object of type [`&[Allocation]`]. Allocations of the user.
+///
+/// * **current_time** is an object of type [`u64`]. Current UNIX time in seconds.
+pub fn compute_total_withdrawable_amount(
+    allocations: &[Allocation],
+    current_time: u64,
+) -> StdResult<Uint128> {
+    allocations
+        .iter()
+        .try_fold(Uint128::zero(), |total_amount, allocation| {
+            let withdrawable_amount = compute_withdrawable_amount(
+                allocation.allocated_amount,
+                allocation.withdrawn_amount,
+                &allocation.schedule,
+                allocation.vesting_time(current_time),
+            )?;
+            Ok(total_amount.checked_add(withdrawable_amount)?)
+        })
+}
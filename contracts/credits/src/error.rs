@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use cw20_base::ContractError as Cw20ContractError;
 use thiserror::Error;
 
@@ -31,8 +31,23 @@ pub enum ContractError {
     #[error("When withdrawable is not set")]
     WhenWithdrawableIsNotConfigured,
 
-    #[error("Address {address} is already vested")]
-    AlreadyVested { address: String },
+    #[error("Amount {amount} exceeds withdrawable amount {withdrawable}")]
+    WithdrawAmountExceedsWithdrawable {
+        amount: Uint128,
+        withdrawable: Uint128,
+    },
+
+    #[error("Allocation is already revoked")]
+    AllocationAlreadyRevoked,
+
+    #[error("Cliff {cliff} exceeds vesting duration {duration}")]
+    CliffExceedsDuration { cliff: u64, duration: u64 },
+
+    #[error("Default cliff {cliff} exceeds the max default cliff {max}")]
+    DefaultCliffTooLong { cliff: u64, max: u64 },
+
+    #[error("Address can't have more than {max} allocations")]
+    TooManyAllocations { max: usize },
 
     #[error("transparent")]
     Cw20Error(#[from] Cw20ContractError),
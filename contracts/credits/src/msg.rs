@@ -2,8 +2,8 @@ use crate::state::{Allocation, Config};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Uint128;
 use cw20::{
-    AllAccountsResponse, AllAllowancesResponse, AllowanceResponse, BalanceResponse, MinterResponse,
-    TokenInfoResponse,
+    AllAccountsResponse, AllAllowancesResponse, AllowanceResponse, BalanceResponse, Expiration,
+    MinterResponse, TokenInfoResponse,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -23,6 +23,10 @@ pub struct UpdateConfigMsg {
     pub lockdrop_address: Option<String>,
     /// When can start withdrawing untrn tokens
     pub when_withdrawable: Option<u64>,
+    /// Cliff in seconds applied to new allocations that don't specify their own. Zero clears it
+    pub default_cliff: Option<u64>,
+    /// Whether holders can transfer cNTRN to other addresses along with their allocations
+    pub user_transfers_enabled: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -32,21 +36,52 @@ pub enum ExecuteMsg {
     /// [Permissioned - DAO]
     UpdateConfig { config: UpdateConfigMsg },
     /// AddVesting is a message that allows address to claim particular amount of untrn tokens at particular time.
-    /// Can only store one vesting amount per address.
+    /// Every call adds a separate allocation with its own schedule to the address.
+    /// If `cliff` is not set, the default cliff from the config is used.
     /// [Permissioned - Airdrop address]
     AddVesting {
         address: String,
         amount: Uint128,
         start_time: u64,
         duration: u64,
+        cliff: Option<u64>,
     },
     /// Transfer is a base message to move tokens to another account without triggering actions.
-    /// [Permissioned - Airdrop address]
+    /// If user transfers are enabled, holders can transfer too, and a proportional slice
+    /// of their allocations moves to the recipient along with the tokens.
+    /// [Permissioned - Airdrop address, or anyone if user transfers are enabled]
     Transfer { recipient: String, amount: Uint128 },
-    /// Withdraw is a message that burns all vested cNTRN tokens
+    /// TransferFrom moves tokens from `owner` to `recipient` using an allowance of the sender.
+    /// A proportional slice of the owner's allocations moves to the recipient along with the tokens.
+    /// [Permissionless, if user transfers are enabled]
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// IncreaseAllowance allows `spender` to transfer `amount` more tokens of the sender.
+    /// [Permissionless]
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// DecreaseAllowance lowers the amount of the sender's tokens `spender` can transfer.
+    /// [Permissionless]
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Withdraw is a message that burns vested cNTRN tokens
     /// on the sender and sends untrn tokens in 1:1 proportion.
+    /// Withdraws all the withdrawable amount if `amount` is not set,
+    /// sends untrn tokens to the sender if `recipient` is not set.
     /// [Permissionless]
-    Withdraw {},
+    Withdraw {
+        amount: Option<Uint128>,
+        recipient: Option<String>,
+    },
     /// Burns is a message that burns certain amount of cNTRN tokens and sends untrn tokens in 1:1 proportion.
     /// [Permissioned - Airdrop address]
     Burn { amount: Uint128 },
@@ -57,20 +92,27 @@ pub enum ExecuteMsg {
     /// Locks untrn tokens and mints cNTRN tokens in 1:1 proportion to the airdrop balance.
     /// [Permissioned - DAO] (DAO address set in initialize func as cw20 minter)
     Mint {},
+    /// RevokeAllocation stops vesting of all the allocations of the address, burns its still unvested cNTRN tokens
+    /// and sends untrn tokens in 1:1 proportion to the DAO. What had vested before can still be withdrawn.
+    /// [Permissioned - DAO]
+    RevokeAllocation { address: String },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    /// Returns the current vestings of the given address.
+    /// Returns the amount the given address can withdraw at the moment, capped by its balance.
     #[returns(WithdrawableAmountResponse)]
     WithdrawableAmount { address: String },
     /// Returns the amount that is left vested of the given address.
     #[returns(VestedAmountResponse)]
     VestedAmount { address: String },
-    /// Returns the current allocation of the given address.
+    /// Returns the first allocation of the given address.
     #[returns(Allocation)]
     Allocation { address: String },
+    /// Returns all the allocations of the given address in the order they were added.
+    #[returns(Vec<Allocation>)]
+    Allocations { address: String },
     /// Returns the current balance of the given address, 0 if unset.
     #[returns(BalanceResponse)]
     Balance { address: String },
@@ -125,7 +167,8 @@ pub struct TotalSupplyResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct WithdrawableAmountResponse {
-    /// Amount that the user can withdraw at this block height.
+    /// Amount that the user can withdraw at this block height, capped by the user balance.
+    /// That is the most `withdraw` pays out.
     pub amount: Uint128,
 }
 
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
 use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -14,6 +14,11 @@ pub struct Config {
     pub lockdrop_address: Option<Addr>,
     /// When can start withdrawing untrn tokens
     pub when_withdrawable: Option<u64>,
+    /// Cliff in seconds applied to new allocations that don't specify their own
+    pub default_cliff: Option<u64>,
+    /// Whether holders can transfer cNTRN to other addresses along with their allocations
+    #[serde(default)]
+    pub user_transfers_enabled: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -25,6 +30,18 @@ pub struct Allocation {
     pub withdrawn_amount: Uint128,
     /// Vesting schedule settings for this allocation
     pub schedule: Schedule,
+    /// Timestamp in UNIX seconds when the DAO revoked the allocation, nothing vests after it
+    #[serde(default)]
+    pub revoked_at: Option<u64>,
+}
+
+impl Allocation {
+    /// Returns the time the vesting of the allocation is computed at,
+    /// which is `current_time` unless the allocation was revoked before.
+    pub fn vesting_time(&self, current_time: u64) -> u64 {
+        self.revoked_at
+            .map_or(current_time, |revoked_at| revoked_at.min(current_time))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -41,6 +58,42 @@ pub struct Schedule {
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
-/// Assume that we cannot set vesting multiple times for same address
-/// Vested allocations of cNTRN
-pub const ALLOCATIONS: Map<&Addr, Allocation> = Map::new("allocations");
+/// Vested allocations of cNTRN, in the order they were added to an address
+pub const ALLOCATIONS: Map<&Addr, Vec<Allocation>> = Map::new("allocation_lists");
+
+/// Vested allocations of cNTRN stored when an address could only have one of them.
+/// An allocation is moved to `ALLOCATIONS` on the next write to its address.
+pub const LEGACY_ALLOCATIONS: Map<&Addr, Allocation> = Map::new("allocations");
+
+/// Returns the allocations of the specified `address` in the order they were added.
+/// Returns an error if no vesting was set up for the address.
+pub fn load_allocations(storage: &dyn Storage, address: &Addr) -> StdResult<Vec<Allocation>> {
+    match ALLOCATIONS.may_load(storage, address)? {
+        Some(allocations) => Ok(allocations),
+        None => Ok(vec![LEGACY_ALLOCATIONS.load(storage, address)?]),
+    }
+}
+
+/// Returns the allocations of the specified `address` in the order they were added,
+/// or `None` if no vesting was set up for the address.
+pub fn may_load_allocations(
+    storage: &dyn Storage,
+    address: &Addr,
+) -> StdResult<Option<Vec<Allocation>>> {
+    match ALLOCATIONS.may_load(storage, address)? {
+        Some(allocations) => Ok(Some(allocations)),
+        None => Ok(LEGACY_ALLOCATIONS
+            .may_load(storage, address)?
+            .map(|allocation| vec![allocation])),
+    }
+}
+
+/// Saves the allocations of the specified `address`, moving a legacy allocation along.
+pub fn save_allocations(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    allocations: Vec<Allocation>,
+) -> StdResult<()> {
+    LEGACY_ALLOCATIONS.remove(storage, address);
+    ALLOCATIONS.save(storage, address, &allocations)
+}
@@ -16,7 +16,7 @@ use crate::contract::{
 };
 use crate::error::ContractError;
 use crate::msg::{InstantiateMsg, UpdateConfigMsg};
-use crate::state::ALLOCATIONS;
+use crate::state::load_allocations;
 use cosmwasm_std::testing::{
     mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
 };
@@ -84,6 +84,8 @@ fn _do_simple_update_config(deps: DepsMut) -> (MessageInfo, Env) {
         airdrop_address: Some("airdrop_address".to_string()),
         lockdrop_address: Some("lockdrop_address".to_string()),
         when_withdrawable: Some(Timestamp::from_seconds(0).seconds()),
+        default_cliff: None,
+        user_transfers_enabled: None,
     };
     let info = mock_info("dao_address", &[]);
     let env = mock_env();
@@ -101,6 +103,19 @@ fn _do_update_config(deps: DepsMut, msg: UpdateConfigMsg) -> (MessageInfo, Env)
     (info, env)
 }
 
+fn _enable_user_transfers(deps: DepsMut) {
+    _do_update_config(
+        deps,
+        UpdateConfigMsg {
+            airdrop_address: None,
+            lockdrop_address: None,
+            when_withdrawable: None,
+            default_cliff: None,
+            user_transfers_enabled: Some(true),
+        },
+    );
+}
+
 fn _do_add_vesting(
     deps: DepsMut,
     env: Env,
@@ -119,6 +134,7 @@ fn _do_add_vesting(
         amount,
         start_time,
         duration,
+        None,
     );
     assert!(res.is_ok());
 }
@@ -154,9 +170,9 @@ fn _assert_withdrawn(
         }
         .into()
     );
-    let allocation = ALLOCATIONS
-        .load(deps.storage, &Addr::unchecked("somebody"))
-        .unwrap();
+    let allocation = load_allocations(deps.storage, &Addr::unchecked("somebody"))
+        .unwrap()
+        .remove(0);
     assert_eq!(allocation.allocated_amount, Uint128::new(allocated_amount));
     assert_eq!(
         allocation.withdrawn_amount,
@@ -196,6 +212,8 @@ mod instantiate {
                 airdrop_address: Some("airdrop_address".to_string()),
                 lockdrop_address: Some("lockdrop_address".to_string()),
                 when_withdrawable: Some(Timestamp::from_seconds(0).seconds()),
+                default_cliff: None,
+                user_transfers_enabled: None,
             },
         );
         let config = query_config(deps.as_ref()).unwrap();
@@ -233,12 +251,18 @@ mod instantiate {
 }
 
 mod add_vesting {
-    use crate::contract::{execute_add_vesting, execute_mint, execute_transfer, VESTING_CLIFF};
-    use crate::error::ContractError;
-    use crate::error::ContractError::Unauthorized;
-    use crate::state::{Schedule, ALLOCATIONS};
+    use crate::contract::{
+        execute_add_vesting, execute_mint, execute_transfer, execute_update_config,
+        MAX_DEFAULT_CLIFF, VESTING_CLIFF,
+    };
+    use crate::error::ContractError::{
+        CliffExceedsDuration, DefaultCliffTooLong, NoFundsSupplied, Unauthorized,
+    };
+    use crate::msg::UpdateConfigMsg;
+    use crate::state::{load_allocations, Allocation, Schedule, CONFIG, LEGACY_ALLOCATIONS};
     use crate::testing::tests::_do_instantiate;
     use crate::testing::tests::_do_simple_update_config;
+    use crate::testing::tests::_do_update_config;
     use cosmwasm_std::testing::{mock_dependencies, mock_info};
     use cosmwasm_std::{Addr, Coin, Uint128};
 
@@ -269,12 +293,13 @@ mod add_vesting {
             Uint128::new(100),
             15,
             1000,
+            None,
         );
         assert!(res.is_ok());
 
-        let allocation = ALLOCATIONS
-            .load(&deps.storage, &Addr::unchecked("address"))
-            .unwrap();
+        let allocation = load_allocations(&deps.storage, &Addr::unchecked("address"))
+            .unwrap()
+            .remove(0);
         assert_eq!(allocation.allocated_amount, Uint128::new(100));
         assert_eq!(allocation.withdrawn_amount, Uint128::new(0));
         assert_eq!(
@@ -287,6 +312,68 @@ mod add_vesting {
         );
     }
 
+    #[test]
+    fn cannot_allocate_more_than_balance() {
+        let mut deps = mock_dependencies();
+        let (_info, _env) = _do_instantiate(deps.as_mut(), None);
+        let (_info, env) = _do_simple_update_config(deps.as_mut());
+        let airdrop_info = mock_info("airdrop_address", &[]);
+        let dao_info = mock_info("dao_address", &[Coin::new(1000, "untrn")]);
+
+        execute_mint(deps.as_mut(), env.clone(), dao_info).unwrap();
+
+        execute_transfer(
+            deps.as_mut(),
+            env.clone(),
+            airdrop_info.clone(),
+            "address".to_string(),
+            Uint128::new(150),
+        )
+        .unwrap();
+
+        execute_add_vesting(
+            deps.as_mut(),
+            env.clone(),
+            airdrop_info.clone(),
+            "address".to_string(),
+            Uint128::new(100),
+            15,
+            1000,
+            None,
+        )
+        .unwrap();
+        // the balance backs the first allocation, so only 50 tokens are left to allocate
+        let res = execute_add_vesting(
+            deps.as_mut(),
+            env.clone(),
+            airdrop_info.clone(),
+            "address".to_string(),
+            Uint128::new(100),
+            15,
+            1000,
+            None,
+        );
+        assert_eq!(res, Err(NoFundsSupplied()));
+        execute_add_vesting(
+            deps.as_mut(),
+            env.clone(),
+            airdrop_info.clone(),
+            "address".to_string(),
+            Uint128::new(50),
+            15,
+            1000,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            load_allocations(&deps.storage, &Addr::unchecked("address"))
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
     #[test]
     fn non_airdrop_addresses_cannot_set_vesting() {
         let mut deps = mock_dependencies();
@@ -302,12 +389,13 @@ mod add_vesting {
             Uint128::new(100),
             15,
             1000,
+            None,
         );
         assert_eq!(res, Err(Unauthorized));
     }
 
     #[test]
-    fn cannot_add_vesting_twice_to_same_address() {
+    fn adds_separate_allocation_for_each_vesting() {
         let mut deps = mock_dependencies();
         let (_info, _env) = _do_instantiate(deps.as_mut(), None);
         let (_info, env) = _do_simple_update_config(deps.as_mut());
@@ -317,6 +405,189 @@ mod add_vesting {
 
         execute_mint(deps.as_mut(), env.clone(), dao_info).unwrap();
 
+        execute_transfer(
+            deps.as_mut(),
+            env.clone(),
+            airdrop_info.clone(),
+            "address".to_string(),
+            Uint128::new(300),
+        )
+        .unwrap();
+
+        // the first allocation was stored when an address could only have one of them
+        let first_allocation = Allocation {
+            allocated_amount: Uint128::new(100),
+            withdrawn_amount: Uint128::new(10),
+            schedule: Schedule {
+                start_time: 15,
+                cliff: VESTING_CLIFF,
+                duration: 1000,
+            },
+            revoked_at: None,
+        };
+        LEGACY_ALLOCATIONS
+            .save(
+                &mut deps.storage,
+                &Addr::unchecked("address"),
+                &first_allocation,
+            )
+            .unwrap();
+
+        let res = execute_add_vesting(
+            deps.as_mut(),
+            env,
+            airdrop_info,
+            "address".to_string(),
+            Uint128::new(200),
+            2000,
+            500,
+            None,
+        );
+        assert!(res.is_ok());
+
+        assert_eq!(
+            load_allocations(&deps.storage, &Addr::unchecked("address")).unwrap(),
+            vec![
+                first_allocation,
+                Allocation {
+                    allocated_amount: Uint128::new(200),
+                    withdrawn_amount: Uint128::zero(),
+                    schedule: Schedule {
+                        start_time: 2000,
+                        cliff: VESTING_CLIFF,
+                        duration: 500,
+                    },
+                    revoked_at: None,
+                },
+            ]
+        );
+        assert!(!LEGACY_ALLOCATIONS.has(&deps.storage, &Addr::unchecked("address")));
+    }
+
+    #[test]
+    fn adds_vesting_with_cliff() {
+        let mut deps = mock_dependencies();
+        let (_info, _env) = _do_instantiate(deps.as_mut(), None);
+        let (_info, env) = _do_simple_update_config(deps.as_mut());
+        let airdrop_info = mock_info("airdrop_address", &[]);
+        let dao_info = mock_info("dao_address", &[Coin::new(1000, "untrn")]);
+
+        execute_mint(deps.as_mut(), env.clone(), dao_info).unwrap();
+
+        execute_transfer(
+            deps.as_mut(),
+            env.clone(),
+            airdrop_info.clone(),
+            "address".to_string(),
+            Uint128::new(300),
+        )
+        .unwrap();
+
+        // explicit cliff
+        execute_add_vesting(
+            deps.as_mut(),
+            env.clone(),
+            airdrop_info.clone(),
+            "address".to_string(),
+            Uint128::new(100),
+            15,
+            1000,
+            Some(100),
+        )
+        .unwrap();
+
+        _do_update_config(
+            deps.as_mut(),
+            UpdateConfigMsg {
+                airdrop_address: None,
+                lockdrop_address: None,
+                when_withdrawable: None,
+                default_cliff: Some(200),
+                user_transfers_enabled: None,
+            },
+        );
+
+        // cliff from the config
+        execute_add_vesting(
+            deps.as_mut(),
+            env.clone(),
+            airdrop_info.clone(),
+            "address".to_string(),
+            Uint128::new(100),
+            15,
+            1000,
+            None,
+        )
+        .unwrap();
+
+        // explicit cliff takes precedence over the config
+        execute_add_vesting(
+            deps.as_mut(),
+            env,
+            airdrop_info,
+            "address".to_string(),
+            Uint128::new(100),
+            15,
+            1000,
+            Some(0),
+        )
+        .unwrap();
+
+        let cliffs: Vec<u64> = load_allocations(&deps.storage, &Addr::unchecked("address"))
+            .unwrap()
+            .into_iter()
+            .map(|allocation| allocation.schedule.cliff)
+            .collect();
+        assert_eq!(cliffs, vec![100, 200, 0]);
+    }
+
+    #[test]
+    fn default_cliff_can_be_cleared() {
+        let mut deps = mock_dependencies();
+        let (_info, _env) = _do_instantiate(deps.as_mut(), None);
+        let (_info, env) = _do_simple_update_config(deps.as_mut());
+        let update_msg = |default_cliff: Option<u64>| UpdateConfigMsg {
+            airdrop_address: None,
+            lockdrop_address: None,
+            when_withdrawable: None,
+            default_cliff,
+            user_transfers_enabled: None,
+        };
+
+        _do_update_config(deps.as_mut(), update_msg(Some(200)));
+        assert_eq!(CONFIG.load(&deps.storage).unwrap().default_cliff, Some(200));
+
+        let res = execute_update_config(
+            deps.as_mut(),
+            env,
+            mock_info("dao_address", &[]),
+            update_msg(Some(MAX_DEFAULT_CLIFF + 1)),
+        );
+        assert_eq!(
+            res,
+            Err(DefaultCliffTooLong {
+                cliff: MAX_DEFAULT_CLIFF + 1,
+                max: MAX_DEFAULT_CLIFF
+            })
+        );
+
+        // an unset default cliff keeps the current one, while a zero one clears it
+        _do_update_config(deps.as_mut(), update_msg(None));
+        assert_eq!(CONFIG.load(&deps.storage).unwrap().default_cliff, Some(200));
+        _do_update_config(deps.as_mut(), update_msg(Some(0)));
+        assert_eq!(CONFIG.load(&deps.storage).unwrap().default_cliff, None);
+    }
+
+    #[test]
+    fn cliff_cannot_exceed_duration() {
+        let mut deps = mock_dependencies();
+        let (_info, _env) = _do_instantiate(deps.as_mut(), None);
+        let (_info, env) = _do_simple_update_config(deps.as_mut());
+        let airdrop_info = mock_info("airdrop_address", &[]);
+        let dao_info = mock_info("dao_address", &[Coin::new(1000, "untrn")]);
+
+        execute_mint(deps.as_mut(), env.clone(), dao_info).unwrap();
+
         execute_transfer(
             deps.as_mut(),
             env.clone(),
@@ -334,9 +605,27 @@ mod add_vesting {
             Uint128::new(100),
             15,
             1000,
+            Some(1001),
+        );
+        assert_eq!(
+            res,
+            Err(CliffExceedsDuration {
+                cliff: 1001,
+                duration: 1000
+            })
         );
-        assert!(res.is_ok());
 
+        // the default cliff is validated against the duration of each vesting
+        _do_update_config(
+            deps.as_mut(),
+            UpdateConfigMsg {
+                airdrop_address: None,
+                lockdrop_address: None,
+                when_withdrawable: None,
+                default_cliff: Some(2000),
+                user_transfers_enabled: None,
+            },
+        );
         let res = execute_add_vesting(
             deps.as_mut(),
             env,
@@ -345,21 +634,31 @@ mod add_vesting {
             Uint128::new(100),
             15,
             1000,
+            None,
         );
         assert_eq!(
             res,
-            Err(ContractError::AlreadyVested {
-                address: "address".to_string()
+            Err(CliffExceedsDuration {
+                cliff: 2000,
+                duration: 1000
             })
         );
     }
 }
 
 mod transfer {
-    use crate::contract::{execute_mint, execute_transfer, DEPOSITED_SYMBOL};
-    use crate::error::ContractError::{Cw20Error, Unauthorized};
+    use crate::contract::{
+        execute, execute_mint, execute_transfer, execute_transfer_from, execute_withdraw,
+        DEPOSITED_SYMBOL,
+    };
+    use crate::error::ContractError::{Cw20Error, NoFundsToClaim, Unauthorized};
+    use crate::msg::ExecuteMsg;
+    use crate::state::{load_allocations, Allocation};
     use crate::testing::tests::_do_instantiate;
     use crate::testing::tests::_do_simple_update_config;
+    use crate::testing::tests::{
+        _do_add_vesting, _enable_user_transfers, _instantiate_vest_to_somebody,
+    };
     use cosmwasm_std::testing::{mock_dependencies, mock_info};
     use cosmwasm_std::OverflowOperation::Sub;
     use cosmwasm_std::{coins, Addr, OverflowError, StdError, Uint128};
@@ -434,25 +733,232 @@ mod transfer {
         let airdrop_info = mock_info("somebody", &[]);
         let res = execute_transfer(
             deps.as_mut(),
-            env,
+            env.clone(),
             airdrop_info,
             "somebody".to_string(),
             Uint128::new(1_000),
         );
         assert_eq!(res, Err(Unauthorized));
+
+        let res = execute_transfer_from(
+            deps.as_mut(),
+            env,
+            mock_info("spender", &[]),
+            "somebody".to_string(),
+            "other".to_string(),
+            Uint128::new(1_000),
+        );
+        assert_eq!(res, Err(Unauthorized));
+    }
+
+    #[test]
+    fn moves_allocation_slice_between_users() {
+        let (mut deps, mut env) = _instantiate_vest_to_somebody(10_000_000, 100, None, 1000);
+        _enable_user_transfers(deps.as_mut());
+
+        // withdraw 50 (100 * 50%)
+        env.block.time = env.block.time.plus_seconds(500);
+        execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            None,
+            None,
+        )
+        .unwrap();
+
+        // transfer 40% of the balance left
+        execute_transfer(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            "other".to_string(),
+            Uint128::new(20),
+        )
+        .unwrap();
+
+        let schedule = load_allocations(&deps.storage, &Addr::unchecked("somebody"))
+            .unwrap()
+            .remove(0)
+            .schedule;
+        assert_eq!(
+            load_allocations(&deps.storage, &Addr::unchecked("somebody")).unwrap(),
+            vec![Allocation {
+                allocated_amount: Uint128::new(60),
+                withdrawn_amount: Uint128::new(30),
+                schedule: schedule.clone(),
+                revoked_at: None,
+            }]
+        );
+        assert_eq!(
+            load_allocations(&deps.storage, &Addr::unchecked("other")).unwrap(),
+            vec![Allocation {
+                allocated_amount: Uint128::new(40),
+                withdrawn_amount: Uint128::new(20),
+                schedule,
+                revoked_at: None,
+            }]
+        );
+
+        // nothing vested since the withdrawal on both sides
+        assert_eq!(
+            execute_withdraw(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("other", &[]),
+                None,
+                None
+            ),
+            Err(NoFundsToClaim)
+        );
+
+        // both sides withdraw the rest after the end of vesting
+        env.block.time = env.block.time.plus_seconds(500);
+        execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            None,
+            None,
+        )
+        .unwrap();
+        execute_withdraw(deps.as_mut(), env, mock_info("other", &[]), None, None).unwrap();
+        for address in ["somebody", "other"] {
+            let balance = BALANCES
+                .load(&deps.storage, &Addr::unchecked(address))
+                .unwrap();
+            assert_eq!(balance, Uint128::zero());
+        }
+    }
+
+    #[test]
+    fn moves_dust_with_allocations() {
+        let (mut deps, env) = _instantiate_vest_to_somebody(10_000_000, 50, None, 1000);
+        _enable_user_transfers(deps.as_mut());
+        execute_transfer(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("airdrop_address", &[]),
+            "somebody".to_string(),
+            Uint128::new(50),
+        )
+        .unwrap();
+        _do_add_vesting(
+            deps.as_mut(),
+            env.clone(),
+            "somebody".to_string(),
+            Uint128::new(50),
+            env.block.time.seconds(),
+            2000,
+        );
+        let schedules = load_allocations(&deps.storage, &Addr::unchecked("somebody"))
+            .unwrap()
+            .into_iter()
+            .map(|allocation| allocation.schedule)
+            .collect::<Vec<_>>();
+
+        // both slices round down to zero, the remainder moves with the first allocation,
+        // and the second transfer merges into the moved allocation
+        for _ in 0..2 {
+            execute_transfer(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("somebody", &[]),
+                "other".to_string(),
+                Uint128::new(1),
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            load_allocations(&deps.storage, &Addr::unchecked("somebody")).unwrap(),
+            vec![
+                Allocation {
+                    allocated_amount: Uint128::new(48),
+                    withdrawn_amount: Uint128::zero(),
+                    schedule: schedules[0].clone(),
+                    revoked_at: None,
+                },
+                Allocation {
+                    allocated_amount: Uint128::new(50),
+                    withdrawn_amount: Uint128::zero(),
+                    schedule: schedules[1].clone(),
+                    revoked_at: None,
+                }
+            ]
+        );
+        assert_eq!(
+            load_allocations(&deps.storage, &Addr::unchecked("other")).unwrap(),
+            vec![Allocation {
+                allocated_amount: Uint128::new(2),
+                withdrawn_amount: Uint128::zero(),
+                schedule: schedules[0].clone(),
+                revoked_at: None,
+            }]
+        );
+        let balance = BALANCES
+            .load(&deps.storage, &Addr::unchecked("other"))
+            .unwrap();
+        assert_eq!(balance, Uint128::new(2));
+    }
+
+    #[test]
+    fn moves_allocations_with_allowance() {
+        let (mut deps, env) = _instantiate_vest_to_somebody(10_000_000, 100, None, 1000);
+        _enable_user_transfers(deps.as_mut());
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            ExecuteMsg::IncreaseAllowance {
+                spender: "spender".to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            },
+        )
+        .unwrap();
+
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info("spender", &[]),
+            ExecuteMsg::TransferFrom {
+                owner: "somebody".to_string(),
+                recipient: "other".to_string(),
+                amount: Uint128::new(100),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            load_allocations(&deps.storage, &Addr::unchecked("somebody")).unwrap(),
+            vec![]
+        );
+        let allocations = load_allocations(&deps.storage, &Addr::unchecked("other")).unwrap();
+        assert_eq!(allocations.len(), 1);
+        assert_eq!(allocations[0].allocated_amount, Uint128::new(100));
+        assert_eq!(allocations[0].withdrawn_amount, Uint128::zero());
+        let balance = BALANCES
+            .load(&deps.storage, &Addr::unchecked("other"))
+            .unwrap();
+        assert_eq!(balance, Uint128::new(100));
     }
 }
 
 mod withdraw {
-    use crate::contract::execute_withdraw;
-    use crate::error::ContractError::{NoFundsToClaim, Std, TooEarlyToClaim};
-    use crate::msg::UpdateConfigMsg;
+    use crate::contract::{execute_transfer, execute_withdraw, query, DEPOSITED_SYMBOL};
+    use crate::error::ContractError::{
+        NoFundsToClaim, Std, TooEarlyToClaim, WithdrawAmountExceedsWithdrawable,
+    };
+    use crate::msg::{QueryMsg, UpdateConfigMsg, WithdrawableAmountResponse};
+    use crate::state::{load_allocations, Allocation};
     use crate::testing::tests::{
-        _assert_withdrawn, _do_instantiate, _do_simple_update_config, _do_update_config,
-        _instantiate_vest_to_somebody, _withdraw_rewards,
+        _assert_withdrawn, _do_add_vesting, _do_instantiate, _do_simple_update_config,
+        _do_update_config, _instantiate_vest_to_somebody, _withdraw_rewards,
     };
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::StdError;
+    use cosmwasm_std::{coins, from_json, Addr, BankMsg, StdError, Uint128};
 
     // withdrawing rewards (burn_from) should not fail withdrawing all funds later
     #[test]
@@ -466,7 +972,13 @@ mod withdraw {
         env.block.time = env.block.time.plus_seconds(750);
 
         // withdraw 75 (100 * 75%)
-        let res = execute_withdraw(deps.as_mut(), env.clone(), mock_info("somebody", &[]));
+        let res = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            None,
+            None,
+        );
         _assert_withdrawn(deps.as_mut(), res, 10_000_000, 100, 75, 75, 0);
 
         // now let's check that if we distribute rewards and skip all vesting, we still successfully withdraw what's left
@@ -480,7 +992,7 @@ mod withdraw {
         // after sending 10 to account, we only have 25-10=15 left
 
         // withdraw what's left
-        let res = execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]));
+        let res = execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]), None, None);
         _assert_withdrawn(deps.as_mut(), res, 10_000_000 - 75 - 10, 100, 90, 15, 10);
     }
 
@@ -500,7 +1012,7 @@ mod withdraw {
 
         // because vesting schedule is immutable we still have 50 to withdraw,
         // even though we withdrew 25 as rewards
-        let res = execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]));
+        let res = execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]), None, None);
         _assert_withdrawn(deps.as_mut(), res, 10_000_000 - 25, 100, 50, 50, 25);
     }
 
@@ -512,10 +1024,83 @@ mod withdraw {
         // at this point `somebody` has vested 100 NTRNs on 1000 seconds
 
         // call at 0% progress of vesting returns error
-        let res = execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]));
+        let res = execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]), None, None);
         assert_eq!(res, Err(NoFundsToClaim));
     }
 
+    #[test]
+    fn across_allocations() {
+        // instantiate
+        let (mut deps, mut env) = _instantiate_vest_to_somebody(10_000_000, 100, None, 1000);
+
+        // at this point `somebody` has vested 100 NTRNs on 1000 seconds,
+        // add 200 NTRNs vested on 1000 seconds starting in 500 seconds
+        execute_transfer(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("airdrop_address", &[]),
+            "somebody".to_string(),
+            Uint128::new(200),
+        )
+        .unwrap();
+        _do_add_vesting(
+            deps.as_mut(),
+            env.clone(),
+            "somebody".to_string(),
+            Uint128::new(200),
+            env.block.time.plus_seconds(500).seconds(),
+            1000,
+        );
+
+        // pass 750 seconds: 75 (100 * 75%) + 50 (200 * 25%)
+        env.block.time = env.block.time.plus_seconds(750);
+        let query_res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::WithdrawableAmount {
+                address: "somebody".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            from_json::<WithdrawableAmountResponse>(&query_res)
+                .unwrap()
+                .amount,
+            Uint128::new(125)
+        );
+
+        let res = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            None,
+            None,
+        );
+        assert_eq!(
+            res.unwrap().messages[0].msg,
+            BankMsg::Send {
+                to_address: "somebody".to_string(),
+                amount: coins(125, DEPOSITED_SYMBOL)
+            }
+            .into()
+        );
+
+        let query_res = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Allocations {
+                address: "somebody".to_string(),
+            },
+        )
+        .unwrap();
+        let withdrawn_amounts: Vec<_> = from_json::<Vec<Allocation>>(&query_res)
+            .unwrap()
+            .into_iter()
+            .map(|allocation| allocation.withdrawn_amount)
+            .collect();
+        assert_eq!(withdrawn_amounts, vec![Uint128::new(75), Uint128::new(50)]);
+    }
+
     #[test]
     fn does_not_withdraw_if_vesting_does_not_started() {
         // instantiate
@@ -529,7 +1114,7 @@ mod withdraw {
         // at this point `somebody` has vested 100 NTRNs on 1000 seconds
 
         // call at 0% progress of vesting returns error
-        let res = execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]));
+        let res = execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]), None, None);
         assert_eq!(res, Err(NoFundsToClaim));
     }
 
@@ -545,10 +1130,12 @@ mod withdraw {
                 airdrop_address: Some("airdrop_address".to_string()),
                 lockdrop_address: Some("lockdrop_address".to_string()),
                 when_withdrawable: Some(mock_env().block.time.plus_seconds(1_000_000).seconds()),
+                default_cliff: None,
+                user_transfers_enabled: None,
             },
         );
 
-        let res = execute_withdraw(deps.as_mut(), env, somebody_info);
+        let res = execute_withdraw(deps.as_mut(), env, somebody_info, None, None);
         assert_eq!(res, Err(TooEarlyToClaim));
     }
 
@@ -561,12 +1148,88 @@ mod withdraw {
 
         // check
         let somebody_info = mock_info("somebody", &[]);
-        let res = execute_withdraw(deps.as_mut(), env, somebody_info);
+        let res = execute_withdraw(deps.as_mut(), env, somebody_info, None, None);
         assert_eq!(
             res,
             Err(Std(StdError::not_found("type: credits::state::Allocation; key: [00, 0B, 61, 6C, 6C, 6F, 63, 61, 74, 69, 6F, 6E, 73, 73, 6F, 6D, 65, 62, 6F, 64, 79]")))
         );
     }
+
+    #[test]
+    fn partial_to_recipient() {
+        // instantiate
+        let (mut deps, mut env) = _instantiate_vest_to_somebody(10_000_000, 100, None, 1000);
+
+        // pass 50% time, withdraw rewards for that account
+        env.block.time = env.block.time.plus_seconds(500);
+        _withdraw_rewards(deps.as_mut(), env.clone(), 60);
+
+        // withdrawable amount is capped by the balance left
+        let res: WithdrawableAmountResponse = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::WithdrawableAmount {
+                    address: "somebody".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.amount, Uint128::new(40));
+
+        let res = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            Some(Uint128::new(41)),
+            None,
+        );
+        assert_eq!(
+            res,
+            Err(WithdrawAmountExceedsWithdrawable {
+                amount: Uint128::new(41),
+                withdrawable: Uint128::new(40)
+            })
+        );
+
+        let res = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            Some(Uint128::new(15)),
+            Some("recipient".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "recipient".to_string(),
+                amount: coins(15, DEPOSITED_SYMBOL)
+            }
+            .into()
+        );
+
+        let res: WithdrawableAmountResponse = from_json(
+            query(
+                deps.as_ref(),
+                env,
+                QueryMsg::WithdrawableAmount {
+                    address: "somebody".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.amount, Uint128::new(25));
+        assert_eq!(
+            load_allocations(&deps.storage, &Addr::unchecked("somebody"))
+                .unwrap()
+                .remove(0)
+                .withdrawn_amount,
+            Uint128::new(15)
+        );
+    }
 }
 
 mod burn {
@@ -789,6 +1452,137 @@ mod mint {
     }
 }
 
+mod revoke_allocation {
+    use crate::contract::{execute_revoke_allocation, execute_withdraw, DEPOSITED_SYMBOL};
+    use crate::error::ContractError::{AllocationAlreadyRevoked, Unauthorized};
+    use crate::state::load_allocations;
+    use crate::testing::tests::{_instantiate_vest_to_somebody, _withdraw_rewards};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{attr, coins, Addr, BankMsg, Uint128};
+    use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+    #[test]
+    fn burns_unvested_and_keeps_vested() {
+        // instantiate
+        let (mut deps, mut env) = _instantiate_vest_to_somebody(10_000_000, 100, None, 1000);
+
+        // at this point `somebody` has vested 100 NTRNs on 1000 seconds
+
+        // pass 25% time, revoke 75 unvested
+        env.block.time = env.block.time.plus_seconds(250);
+        let res = execute_revoke_allocation(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("dao_address", &[]),
+            "somebody".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "dao_address".to_string(),
+                amount: coins(75, DEPOSITED_SYMBOL)
+            }
+            .into()
+        );
+        assert!(res
+            .attributes
+            .contains(&attr("action", "revoke_allocation")));
+        assert!(res.attributes.contains(&attr("address", "somebody")));
+        assert!(res.attributes.contains(&attr("revoked_amount", "75")));
+
+        let balance = BALANCES
+            .load(&deps.storage, &Addr::unchecked("somebody"))
+            .unwrap();
+        assert_eq!(balance, Uint128::new(25));
+        let token_info = TOKEN_INFO.load(&deps.storage).unwrap();
+        assert_eq!(token_info.total_supply, Uint128::new(10_000_000 - 75));
+        let allocation = load_allocations(&deps.storage, &Addr::unchecked("somebody"))
+            .unwrap()
+            .remove(0);
+        assert_eq!(allocation.revoked_at, Some(env.block.time.seconds()));
+
+        // only what had vested before the revocation is withdrawn after the end of vesting
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("somebody", &[]),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "somebody".to_string(),
+                amount: coins(25, DEPOSITED_SYMBOL)
+            }
+            .into()
+        );
+
+        let res = execute_revoke_allocation(
+            deps.as_mut(),
+            env,
+            mock_info("dao_address", &[]),
+            "somebody".to_string(),
+        );
+        assert_eq!(res, Err(AllocationAlreadyRevoked));
+    }
+
+    #[test]
+    fn keeps_vested_after_rewards_burn() {
+        let (mut deps, mut env) = _instantiate_vest_to_somebody(10_000_000, 100, None, 1000);
+
+        // lockdrop rewards burn 50 NTRNs without counting them as withdrawn
+        _withdraw_rewards(deps.as_mut(), env.clone(), 50);
+
+        // pass 25% time, only the balance above the 25 vested NTRNs is revoked
+        env.block.time = env.block.time.plus_seconds(250);
+        let res = execute_revoke_allocation(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("dao_address", &[]),
+            "somebody".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "dao_address".to_string(),
+                amount: coins(25, DEPOSITED_SYMBOL)
+            }
+            .into()
+        );
+        assert!(res.attributes.contains(&attr("revoked_amount", "25")));
+
+        // the vested NTRNs can still be withdrawn
+        let res =
+            execute_withdraw(deps.as_mut(), env, mock_info("somebody", &[]), None, None).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "somebody".to_string(),
+                amount: coins(25, DEPOSITED_SYMBOL)
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn unauthorized_for_non_dao_addresses() {
+        let (mut deps, env) = _instantiate_vest_to_somebody(10_000_000, 100, None, 1000);
+
+        let res = execute_revoke_allocation(
+            deps.as_mut(),
+            env,
+            mock_info("airdrop_address", &[]),
+            "somebody".to_string(),
+        );
+        assert_eq!(res, Err(Unauthorized));
+    }
+}
+
 mod compute_withdrawable_amount {
     use crate::contract::{compute_withdrawable_amount, VESTING_CLIFF};
     use crate::state::Schedule;
@@ -179,6 +179,7 @@ pub fn execute_claim(
             amount,
             start_time: vesting_start,
             duration: vesting_duration,
+            cliff: None,
         })?,
         funds: vec![],
     };
@@ -154,6 +154,7 @@ fn claim() {
                 amount: test_data.amount,
                 start_time: vesting_start,
                 duration: vesting_duration_seconds,
+                cliff: None,
             })
             .unwrap(),
             funds: vec![],
@@ -268,6 +269,7 @@ fn multiple_claim() {
                     amount: account.amount,
                     start_time: vesting_start,
                     duration: vesting_duration_seconds,
+                    cliff: None,
                 })
                 .unwrap(),
                 funds: vec![],
@@ -469,6 +471,8 @@ fn withdraw_all() {
                 airdrop_address: Some("contract1".to_string()),
                 lockdrop_address: Some("contract2".to_string()),
                 when_withdrawable: Some(Default::default()),
+                default_cliff: None,
+                user_transfers_enabled: None,
             },
         },
         &[],
@@ -504,6 +508,8 @@ fn withdraw_all() {
                 airdrop_address: Some("contract1".to_string()),
                 lockdrop_address: Some("contract2".to_string()),
                 when_withdrawable: Some(Default::default()),
+                default_cliff: None,
+                user_transfers_enabled: None,
             },
         },
         &[],
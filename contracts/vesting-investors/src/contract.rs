@@ -3,9 +3,8 @@ use cosmwasm_std::{entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Respons
 use cw2::set_contract_version;
 use vesting_base::builder::VestingBaseBuilder;
 use vesting_base::error::ContractError;
-use vesting_base::handlers::{
-    execute as base_execute, migrate as base_migrate, query as base_query,
-};
+use vesting_base::handlers::{execute as base_execute, query as base_query};
+use vesting_base::migration::VestingMigrationBuilder;
 use vesting_base::msg::{ExecuteMsg, MigrateMsg, QueryMsg};
 
 /// Contract name that is used for migration.
@@ -23,11 +22,16 @@ pub fn instantiate(
 ) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    VestingBaseBuilder::default().historical().managed().build(
-        deps,
-        msg.owner,
-        msg.token_info_manager,
-    )?;
+    let mut builder = VestingBaseBuilder::default();
+    builder.historical().managed();
+    if let Some(unbonding_period) = msg.unbonding_period {
+        builder.staking(unbonding_period);
+    }
+    builder.pausable(msg.guardian);
+    if let Some(dao) = msg.dao {
+        builder.voting(dao);
+    }
+    builder.build(deps, msg.owner, msg.token_info_manager)?;
     Ok(Response::default())
 }
 
@@ -51,5 +55,5 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 /// Exposes migrate function.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
-    base_migrate(deps, env, msg)
+    VestingMigrationBuilder::new(CONTRACT_NAME, CONTRACT_VERSION).migrate(deps, env, msg.extensions)
 }
@@ -7,4 +7,10 @@ pub struct InstantiateMsg {
     pub owner: String,
     /// Token info manager address
     pub token_info_manager: String,
+    /// The unbonding period of the chain in seconds. Enables the staking of the vesting tokens
+    pub unbonding_period: Option<u64>,
+    /// The address that can pause and resume the contract besides the owner
+    pub guardian: Option<String>,
+    /// The DAO the contract provides voting power to. Enables the voting extension
+    pub dao: Option<String>,
 }
@@ -2,15 +2,21 @@ use crate::msg::InstantiateMsg;
 use astroport::asset::{native_asset_info, token_asset_info};
 use astroport::querier::query_balance;
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
-use cosmwasm_std::{coin, coins, to_json_binary, Addr, StdResult, Timestamp, Uint128};
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{
+    coin, coins, to_json_binary, Addr, Decimal, StdResult, Timestamp, Uint128, Validator,
+};
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
-use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_multi_test::{App, ContractWrapper, Executor, StakingInfo, StakingSudo, SudoMsg};
 use cw_utils::PaymentError;
 use vesting_base::error::ContractError;
-use vesting_base::msg::{Cw20HookMsg, ExecuteMsg, ExecuteMsgManaged, QueryMsg, QueryMsgHistorical};
+use vesting_base::msg::{
+    Cw20HookMsg, ExecuteMsg, ExecuteMsgManaged, ExecuteMsgStaking, QueryMsg, QueryMsgHistorical,
+    QueryMsgStaking,
+};
 use vesting_base::types::{
-    Config, VestingAccount, VestingAccountResponse, VestingSchedule, VestingSchedulePoint,
-    VestingState,
+    Config, StakingDelegationResponse, StakingInfoResponse, VestingAccount, VestingAccountResponse,
+    VestingSchedule, VestingSchedulePoint, VestingState,
 };
 
 const OWNER1: &str = "owner1";
@@ -20,6 +26,8 @@ const USER2: &str = "user2";
 const TOKEN_INITIAL_AMOUNT: u128 = 1_000_000_000_000_000;
 const VESTING_TOKEN: &str = "vesting_token";
 const BLOCK_TIME: u64 = 5;
+const VALIDATOR: &str = "validator";
+const UNBONDING_PERIOD: u64 = 600;
 
 #[test]
 fn claim() {
@@ -50,6 +58,7 @@ fn claim() {
                             time: Timestamp::from_seconds(101).seconds(),
                             amount: Uint128::new(200),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -60,6 +69,7 @@ fn claim() {
                             time: Timestamp::from_seconds(110).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -70,6 +80,7 @@ fn claim() {
                             time: Timestamp::from_seconds(200).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                 ],
             }],
@@ -98,6 +109,7 @@ fn claim() {
                             time: Timestamp::from_seconds(101).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -108,6 +120,7 @@ fn claim() {
                             time: Timestamp::from_seconds(110).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -118,6 +131,7 @@ fn claim() {
                             time: Timestamp::from_seconds(200).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                 ],
             }],
@@ -225,6 +239,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -250,6 +265,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -260,6 +276,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(110).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -270,6 +287,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
             ],
         }],
@@ -395,6 +413,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(100).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -421,6 +440,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -456,6 +476,7 @@ fn register_vesting_accounts() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(100),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -507,6 +528,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -554,6 +576,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(10),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -648,6 +671,7 @@ fn register_vesting_accounts_native() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -673,6 +697,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(100),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -731,6 +756,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(200),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -779,6 +805,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(200).seconds(),
                     amount: Uint128::new(10),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -872,6 +899,7 @@ fn query_at_height() {
                                 .seconds(),
                             amount: Uint128::new(50),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -886,6 +914,7 @@ fn query_at_height() {
                                 .seconds(),
                             amount: Uint128::new(150),
                         }),
+                        ..Default::default()
                     },
                 ],
             },
@@ -904,6 +933,7 @@ fn query_at_height() {
                             .seconds(),
                         amount: Uint128::new(1000),
                     }),
+                    ..Default::default()
                 }],
             },
         ],
@@ -1060,13 +1090,14 @@ fn remove_vesting_accounts() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
                         time: Timestamp::from_seconds(100).seconds(),
                         amount: Uint128::new(100),
                     },
-                    end_point: None,
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -1077,6 +1108,7 @@ fn remove_vesting_accounts() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
             ],
         }],
@@ -1180,13 +1212,14 @@ fn remove_vesting_accounts() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
                         time: Timestamp::from_seconds(100).seconds(),
                         amount: Uint128::new(100),
                     },
-                    end_point: None,
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -1197,6 +1230,7 @@ fn remove_vesting_accounts() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
             ],
         }],
@@ -1298,6 +1332,605 @@ fn remove_vesting_accounts() {
     assert_eq!(vesting_state.total_released.clone(), Uint128::new(0u128));
 }
 
+#[test]
+fn staking() {
+    let user1 = Addr::unchecked(USER1);
+    let owner = Addr::unchecked(OWNER1);
+
+    let mut app = mock_app_with_staking(&owner);
+
+    let vesting_instance = instantiate_vesting_with_staking(&mut app);
+
+    let start_time = app.block_info().time.seconds();
+    let msg = ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: user1.to_string(),
+            schedules: vec![VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: start_time,
+                    amount: Uint128::zero(),
+                },
+                end_point: Some(VestingSchedulePoint {
+                    time: start_time + 1000,
+                    amount: Uint128::new(1_000_000_000),
+                }),
+                ..Default::default()
+            }],
+        }],
+    };
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &msg,
+        &coins(1_000_000_000, VESTING_TOKEN),
+    )
+    .unwrap();
+
+    // only the unclaimed tokens of the account can be delegated
+    let delegate_msg = |amount: u128| ExecuteMsg::StakingExtension {
+        msg: ExecuteMsgStaking::Delegate {
+            validator: VALIDATOR.to_string(),
+            amount: Uint128::new(amount),
+        },
+    };
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            vesting_instance.clone(),
+            &delegate_msg(1_000_000_001),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::AmountIsNotAvailable {},
+        err.downcast().unwrap()
+    );
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &delegate_msg(600_000_000),
+        &[],
+    )
+    .unwrap();
+
+    // half of the tokens are vested, but the delegated ones can't be claimed
+    app.update_block(|b| {
+        b.height += 100;
+        b.time = b.time.plus_seconds(500);
+    });
+    let query_available_amount = |app: &App| -> Uint128 {
+        app.wrap()
+            .query_wasm_smart(
+                vesting_instance.clone(),
+                &QueryMsg::AvailableAmount {
+                    address: user1.to_string(),
+                },
+            )
+            .unwrap()
+    };
+    assert_eq!(query_available_amount(&app), Uint128::new(400_000_000));
+
+    let staking_info: StakingInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            vesting_instance.clone(),
+            &QueryMsg::StakingExtension {
+                msg: QueryMsgStaking::StakingInfo {
+                    address: user1.to_string(),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        staking_info.delegations,
+        vec![StakingDelegationResponse {
+            validator: VALIDATOR.to_string(),
+            amount: Uint128::new(600_000_000),
+        }],
+    );
+    assert!(!staking_info.pending_rewards.is_zero());
+
+    // the staking rewards are sent to the vesting account owner
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::StakingExtension {
+            msg: ExecuteMsgStaking::ClaimRewards {},
+        },
+        &[],
+    )
+    .unwrap();
+    let rewards = staking_info.pending_rewards.u128();
+    let balance = query_balance(&app.wrap(), &user1, VESTING_TOKEN).unwrap();
+    assert_eq!(balance.u128(), rewards);
+
+    // the undelegated tokens can't be claimed until the unbonding period ends
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::StakingExtension {
+            msg: ExecuteMsgStaking::Undelegate {
+                validator: VALIDATOR.to_string(),
+                amount: Uint128::new(600_000_000),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_available_amount(&app), Uint128::new(400_000_000));
+
+    app.update_block(|b| {
+        b.height += 100;
+        b.time = b.time.plus_seconds(UNBONDING_PERIOD);
+    });
+    app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+        .unwrap();
+    assert_eq!(query_available_amount(&app), Uint128::new(1_000_000_000));
+
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::Claim {
+            recipient: None,
+            amount: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let balance = query_balance(&app.wrap(), &user1, VESTING_TOKEN).unwrap();
+    assert_eq!(balance.u128(), rewards + 1_000_000_000);
+}
+
+#[test]
+fn staking_clawback() {
+    let user1 = Addr::unchecked(USER1);
+    let owner = Addr::unchecked(OWNER1);
+
+    let mut app = mock_app_with_staking(&owner);
+
+    let vesting_instance = instantiate_vesting_with_staking(&mut app);
+
+    let start_time = app.block_info().time.seconds();
+    let msg = ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: user1.to_string(),
+            schedules: vec![VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: start_time,
+                    amount: Uint128::zero(),
+                },
+                end_point: Some(VestingSchedulePoint {
+                    time: start_time + 1000,
+                    amount: Uint128::new(1_000_000_000),
+                }),
+                ..Default::default()
+            }],
+        }],
+    };
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &msg,
+        &coins(1_000_000_000, VESTING_TOKEN),
+    )
+    .unwrap();
+
+    // the tokens vesting after a scheduled termination can't be delegated
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::ManagedExtension {
+            msg: ExecuteMsgManaged::ScheduleVestingTermination {
+                vesting_account: user1.to_string(),
+                termination_time: start_time + 500,
+                clawback_account: owner.to_string(),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    let delegate_msg = |amount: u128| ExecuteMsg::StakingExtension {
+        msg: ExecuteMsgStaking::Delegate {
+            validator: VALIDATOR.to_string(),
+            amount: Uint128::new(amount),
+        },
+    };
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            vesting_instance.clone(),
+            &delegate_msg(500_000_001),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::AmountIsNotAvailable {},
+        err.downcast().unwrap()
+    );
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::ManagedExtension {
+            msg: ExecuteMsgManaged::CancelVestingTermination {
+                vesting_account: user1.to_string(),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &delegate_msg(600_000_000),
+        &[],
+    )
+    .unwrap();
+
+    // the delegated tokens block the clawback until they are undelegated
+    let remove_msg = ExecuteMsg::ManagedExtension {
+        msg: ExecuteMsgManaged::RemoveVestingAccounts {
+            vesting_accounts: vec![user1.to_string()],
+            clawback_account: owner.to_string(),
+        },
+    };
+    let err = app
+        .execute_contract(owner.clone(), vesting_instance.clone(), &remove_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::VestingAccountStakedError(user1.to_string()),
+        err.downcast().unwrap()
+    );
+
+    let force_undelegate_msg = ExecuteMsg::StakingExtension {
+        msg: ExecuteMsgStaking::ForceUndelegate {
+            vesting_account: user1.to_string(),
+        },
+    };
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            vesting_instance.clone(),
+            &force_undelegate_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &force_undelegate_msg,
+        &[],
+    )
+    .unwrap();
+
+    // the account can't delegate again to block the clawback
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            vesting_instance.clone(),
+            &delegate_msg(100_000_000),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::VestingAccountStakingFrozen(user1.to_string()),
+        err.downcast().unwrap()
+    );
+    let err = app
+        .execute_contract(owner.clone(), vesting_instance.clone(), &remove_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::VestingAccountStakedError(user1.to_string()),
+        err.downcast().unwrap()
+    );
+
+    // the clawback settles once the unbonding period ends
+    app.update_block(|b| {
+        b.height += 100;
+        b.time = b.time.plus_seconds(UNBONDING_PERIOD);
+    });
+    app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+        .unwrap();
+    let owner_balance = query_balance(&app.wrap(), &owner, VESTING_TOKEN).unwrap();
+    app.execute_contract(owner.clone(), vesting_instance.clone(), &remove_msg, &[])
+        .unwrap();
+    assert_eq!(
+        query_balance(&app.wrap(), &owner, VESTING_TOKEN).unwrap(),
+        owner_balance + Uint128::new(1_000_000_000)
+    );
+}
+
+#[test]
+fn staking_slashing() {
+    let user1 = Addr::unchecked(USER1);
+    let owner = Addr::unchecked(OWNER1);
+
+    let mut app = mock_app_with_staking(&owner);
+
+    let vesting_instance = instantiate_vesting_with_staking(&mut app);
+
+    let start_time = app.block_info().time.seconds();
+    let msg = ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: user1.to_string(),
+            schedules: vec![VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: start_time,
+                    amount: Uint128::zero(),
+                },
+                end_point: Some(VestingSchedulePoint {
+                    time: start_time + 1000,
+                    amount: Uint128::new(1_000_000_000),
+                }),
+                ..Default::default()
+            }],
+        }],
+    };
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &msg,
+        &coins(1_000_000_000, VESTING_TOKEN),
+    )
+    .unwrap();
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::StakingExtension {
+            msg: ExecuteMsgStaking::Delegate {
+                validator: VALIDATOR.to_string(),
+                amount: Uint128::new(600_000_000),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the slashed tokens are taken from the account's delegation
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: VALIDATOR.to_string(),
+        percentage: Decimal::percent(10),
+    }))
+    .unwrap();
+    let staking_info: StakingInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            vesting_instance.clone(),
+            &QueryMsg::StakingExtension {
+                msg: QueryMsgStaking::StakingInfo {
+                    address: user1.to_string(),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        staking_info.delegations,
+        vec![StakingDelegationResponse {
+            validator: VALIDATOR.to_string(),
+            amount: Uint128::new(540_000_000),
+        }],
+    );
+
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::StakingExtension {
+            msg: ExecuteMsgStaking::Undelegate {
+                validator: VALIDATOR.to_string(),
+                amount: Uint128::new(540_000_000),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the slashed tokens are recorded, so the rest can be claimed
+    app.update_block(|b| {
+        b.height += 100;
+        b.time = b.time.plus_seconds(1000 + UNBONDING_PERIOD);
+    });
+    app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+        .unwrap();
+    let available_amount: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            vesting_instance.clone(),
+            &QueryMsg::AvailableAmount {
+                address: user1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(available_amount, Uint128::new(940_000_000));
+
+    let balance = query_balance(&app.wrap(), &user1, VESTING_TOKEN).unwrap();
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::Claim {
+            recipient: None,
+            amount: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_balance(&app.wrap(), &user1, VESTING_TOKEN).unwrap(),
+        balance + Uint128::new(940_000_000)
+    );
+}
+
+#[test]
+fn staking_slashing_clawback() {
+    let user1 = Addr::unchecked(USER1);
+    let owner = Addr::unchecked(OWNER1);
+
+    let mut app = mock_app_with_staking(&owner);
+
+    let vesting_instance = instantiate_vesting_with_staking(&mut app);
+
+    let start_time = app.block_info().time.seconds();
+    let msg = ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: user1.to_string(),
+            schedules: vec![VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: start_time,
+                    amount: Uint128::zero(),
+                },
+                end_point: Some(VestingSchedulePoint {
+                    time: start_time + 10_000,
+                    amount: Uint128::new(1_000_000_000),
+                }),
+                ..Default::default()
+            }],
+        }],
+    };
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &msg,
+        &coins(1_000_000_000, VESTING_TOKEN),
+    )
+    .unwrap();
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::StakingExtension {
+            msg: ExecuteMsgStaking::Delegate {
+                validator: VALIDATOR.to_string(),
+                amount: Uint128::new(600_000_000),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::StakingExtension {
+            msg: ExecuteMsgStaking::Undelegate {
+                validator: VALIDATOR.to_string(),
+                amount: Uint128::new(200_000_000),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // both the delegation and the unbonding lose half of the still vesting tokens
+    app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+        validator: VALIDATOR.to_string(),
+        percentage: Decimal::percent(50),
+    }))
+    .unwrap();
+    app.update_block(|b| {
+        b.height += 20;
+        b.time = b.time.plus_seconds(100);
+    });
+
+    // the slashed amount exceeds the vested amount, so nothing is available yet
+    let balance = query_balance(&app.wrap(), &user1, VESTING_TOKEN).unwrap();
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::Claim {
+            recipient: None,
+            amount: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_balance(&app.wrap(), &user1, VESTING_TOKEN).unwrap(),
+        balance
+    );
+    let available_amount: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            vesting_instance.clone(),
+            &QueryMsg::AvailableAmount {
+                address: user1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(available_amount, Uint128::zero());
+
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::StakingExtension {
+            msg: ExecuteMsgStaking::ForceUndelegate {
+                vesting_account: user1.to_string(),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(|b| {
+        b.height += 100;
+        b.time = b.time.plus_seconds(UNBONDING_PERIOD);
+    });
+    app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+        .unwrap();
+
+    // 70_000_000 tokens have vested and cover a part of the slashed tokens, the rest of them
+    // is missing from the clawback
+    let owner_balance = query_balance(&app.wrap(), &owner, VESTING_TOKEN).unwrap();
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::ManagedExtension {
+            msg: ExecuteMsgManaged::TruncateVestingAccounts {
+                vesting_accounts: vec![user1.to_string()],
+                clawback_account: owner.to_string(),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_balance(&app.wrap(), &owner, VESTING_TOKEN).unwrap(),
+        owner_balance + Uint128::new(700_000_000)
+    );
+
+    // the truncated account has nothing to claim, and the claims don't fail
+    app.update_block(|b| {
+        b.height += 100;
+        b.time = b.time.plus_seconds(10_000);
+    });
+    let available_amount: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            vesting_instance.clone(),
+            &QueryMsg::AvailableAmount {
+                address: user1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(available_amount, Uint128::zero());
+    app.execute_contract(
+        user1.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::Claim {
+            recipient: None,
+            amount: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let vesting_state: VestingState = app
+        .wrap()
+        .query_wasm_smart(vesting_instance, &QueryMsg::VestingState {})
+        .unwrap();
+    assert_eq!(
+        vesting_state,
+        VestingState {
+            total_granted: Uint128::new(70_000_000),
+            total_released: Uint128::new(70_000_000),
+        }
+    );
+}
+
 fn mock_app(owner: &Addr) -> App {
     App::new(|app, _, storage| {
         app.bank
@@ -1313,6 +1946,40 @@ fn mock_app(owner: &Addr) -> App {
     })
 }
 
+fn mock_app_with_staking(owner: &Addr) -> App {
+    App::new(|router, api, storage| {
+        router
+            .bank
+            .init_balance(storage, owner, coins(TOKEN_INITIAL_AMOUNT, VESTING_TOKEN))
+            .unwrap();
+        router
+            .staking
+            .setup(
+                storage,
+                StakingInfo {
+                    bonded_denom: VESTING_TOKEN.to_string(),
+                    unbonding_time: UNBONDING_PERIOD,
+                    apr: Decimal::percent(10),
+                },
+            )
+            .unwrap();
+        router
+            .staking
+            .add_validator(
+                api,
+                storage,
+                &mock_env().block,
+                Validator {
+                    address: VALIDATOR.to_string(),
+                    commission: Decimal::zero(),
+                    max_commission: Decimal::one(),
+                    max_change_rate: Decimal::one(),
+                },
+            )
+            .unwrap();
+    })
+}
+
 fn store_token_code(app: &mut App) -> u64 {
     let cw20_token_contract = Box::new(ContractWrapper::new_with_empty(
         astroport_token::contract::execute,
@@ -1362,6 +2029,9 @@ fn instantiate_vesting(app: &mut App, cw20_token_instance: &Addr) -> Addr {
     let init_msg = InstantiateMsg {
         owner: OWNER1.to_string(),
         token_info_manager: TOKEN_MANAGER.to_string(),
+        unbonding_period: None,
+        guardian: None,
+        dao: None,
     };
 
     let vesting_instance = app
@@ -1410,6 +2080,41 @@ fn instantiate_vesting_remote_chain(app: &mut App) -> Addr {
     let init_msg = InstantiateMsg {
         owner: OWNER1.to_string(),
         token_info_manager: TOKEN_MANAGER.to_string(),
+        unbonding_period: None,
+        guardian: None,
+        dao: None,
+    };
+
+    let res = app
+        .instantiate_contract(vesting_code_id, owner, &init_msg, &[], "Vesting", None)
+        .unwrap();
+
+    let msg = ExecuteMsg::SetVestingToken {
+        vesting_token: native_asset_info(VESTING_TOKEN.to_string()),
+    };
+
+    app.execute_contract(token_manager, res.clone(), &msg, &[])
+        .unwrap();
+
+    res
+}
+
+fn instantiate_vesting_with_staking(app: &mut App) -> Addr {
+    let vesting_contract = Box::new(ContractWrapper::new_with_empty(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    ));
+    let owner = Addr::unchecked(OWNER1);
+    let token_manager = Addr::unchecked(TOKEN_MANAGER);
+    let vesting_code_id = app.store_code(vesting_contract);
+
+    let init_msg = InstantiateMsg {
+        owner: OWNER1.to_string(),
+        token_info_manager: TOKEN_MANAGER.to_string(),
+        unbonding_period: Some(UNBONDING_PERIOD),
+        guardian: None,
+        dao: None,
     };
 
     let res = app
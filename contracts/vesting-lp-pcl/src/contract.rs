@@ -1,6 +1,6 @@
 use crate::msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg};
 use crate::state::XYK_VESTING_LP_CONTRACT;
-use astroport::asset::token_asset_info;
+use astroport::asset::{token_asset_info, AssetInfo};
 use cosmwasm_std::{
     entry_point, from_json, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
     Storage, Uint128,
@@ -8,11 +8,12 @@ use cosmwasm_std::{
 use cw2::set_contract_version;
 use cw20::Cw20ReceiveMsg;
 use vesting_base::error::ContractError;
+use vesting_base::handlers::{add_released_amount, get_vesting_token, register_vesting_accounts};
 use vesting_base::handlers::{execute as base_execute, query as base_query};
-use vesting_base::handlers::{get_vesting_token, register_vesting_accounts};
-use vesting_base::msg::{ExecuteMsg as BaseExecute, QueryMsg};
-use vesting_base::state::{vesting_info, vesting_state, CONFIG, VESTING_MANAGERS};
-use vesting_base::types::{Config, Extensions, VestingInfo};
+use vesting_base::migration::VestingMigrationBuilder;
+use vesting_base::msg::{ExecuteMsg as BaseExecute, MigrateMsg, QueryMsg};
+use vesting_base::state::{CONFIG, DAO, GUARDIAN, VESTING_MANAGERS};
+use vesting_base::types::{Config, Extensions, VestingAccount, VestingInfo};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "neutron-vesting-lp-pcl";
@@ -40,9 +41,20 @@ pub fn instantiate(
                 historical: true,
                 managed: false,
                 with_managers: true,
+                transferable: false,
+                with_operators: false,
+                voting: msg.dao.is_some(),
+                staking: false,
+                pausable: true,
             },
         },
     )?;
+    if let Some(guardian) = msg.guardian {
+        GUARDIAN.save(deps.storage, &deps.api.addr_validate(&guardian)?)?;
+    }
+    if let Some(dao) = msg.dao {
+        DAO.save(deps.storage, &deps.api.addr_validate(&dao)?)?;
+    }
     for m in msg.vesting_managers.iter() {
         let ma = deps.api.addr_validate(m)?;
         VESTING_MANAGERS.save(deps.storage, ma, &())?;
@@ -96,7 +108,13 @@ fn receive_cw20(
             {
                 return Err(ContractError::Unauthorized {});
             }
-            register_vesting_accounts(deps, vesting_accounts, cw20_msg.amount, env.block.height)
+            register_vesting_accounts(
+                deps,
+                vesting_accounts,
+                &vesting_token,
+                cw20_msg.amount,
+                env.block.height,
+            )
         }
         Cw20HookMsg::MigrateXYKLiquidity {
             user_address_raw,
@@ -108,7 +126,17 @@ fn receive_cw20(
             ) {
                 return Err(ContractError::Unauthorized {});
             }
-            handle_migrate_xyk_liquidity(deps, env, user_address_raw, user_vesting_info)
+            if token_asset_info(sender) != vesting_token {
+                return Err(ContractError::Unauthorized {});
+            }
+            handle_migrate_xyk_liquidity(
+                deps,
+                env,
+                user_address_raw,
+                user_vesting_info,
+                &vesting_token,
+                cw20_msg.amount,
+            )
         }
     }
 }
@@ -133,40 +161,45 @@ fn is_sender_xyk_vesting_lp(store: &mut dyn Storage, sender: &Addr) -> bool {
     false
 }
 
+/// Registers the vesting schedules of a user migrated from the XYK vesting contract. The
+/// schedules are added to the user's existing ones. The released amount stands for the share of
+/// the schedules the user had claimed before the migration, so the migrated tokens should match
+/// the total amount of the schedules minus the released amount.
+///
+/// * **user_vesting_info** the rescaled vesting information of the user.
+///
+/// * **amount** the amount of the migrated tokens.
 fn handle_migrate_xyk_liquidity(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     user_addr: Addr,
     user_vesting_info: VestingInfo,
+    vesting_token: &AssetInfo,
+    amount: Uint128,
 ) -> Result<Response, ContractError> {
-    let height = env.block.height;
-    let config = CONFIG.load(deps.storage)?;
-
-    let mut to_deposit = Uint128::zero();
-    for sch in &user_vesting_info.schedules {
-        let amount = if let Some(end_point) = &sch.end_point {
-            end_point.amount
-        } else {
-            sch.start_point.amount
-        };
-        to_deposit = to_deposit.checked_add(amount)?;
+    // only the vesting token is migrated
+    if !user_vesting_info.released_assets.is_empty() {
+        return Err(ContractError::VestingStateInvariantError(
+            user_addr.to_string(),
+        ));
     }
 
-    let vesting_info = vesting_info(config.extensions.historical);
-
-    vesting_info.save(deps.storage, user_addr, &user_vesting_info, height)?;
-
-    vesting_state(config.extensions.historical).update::<_, ContractError>(
-        deps.storage,
-        height,
-        |s| {
-            let mut state = s.unwrap_or_default();
-            state.total_granted = state.total_granted.checked_add(to_deposit)?;
-            Ok(state)
-        },
+    let released_amount = user_vesting_info.released_amount;
+    let response = register_vesting_accounts(
+        deps.branch(),
+        vec![VestingAccount {
+            address: user_addr.to_string(),
+            schedules: user_vesting_info.schedules,
+        }],
+        vesting_token,
+        amount.checked_add(released_amount)?,
+        env.block.height,
     )?;
+    if !released_amount.is_zero() {
+        add_released_amount(deps.storage, user_addr, released_amount, env.block.height)?;
+    }
 
-    Ok(Response::default())
+    Ok(response)
 }
 
 /// Exposes all the queries available in the contract.
@@ -174,3 +207,9 @@ fn handle_migrate_xyk_liquidity(
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     base_query(deps, env, msg)
 }
+
+/// Exposes migrate function.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    VestingMigrationBuilder::new(CONTRACT_NAME, CONTRACT_VERSION).migrate(deps, env, msg.extensions)
+}
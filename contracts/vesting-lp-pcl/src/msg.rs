@@ -3,7 +3,8 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Uint128};
 use cw20::Cw20ReceiveMsg;
 use vesting_base::msg::{
-    ExecuteMsg as BaseExecute, ExecuteMsgHistorical, ExecuteMsgManaged, ExecuteMsgWithManagers,
+    ExecuteMsg as BaseExecute, ExecuteMsgHistorical, ExecuteMsgManaged, ExecuteMsgPausable,
+    ExecuteMsgWithManagers,
 };
 use vesting_base::types::{VestingAccount, VestingInfo};
 
@@ -18,6 +19,10 @@ pub struct InstantiateMsg {
     pub token_info_manager: String,
     pub xyk_vesting_lp_contract: String,
     pub vesting_token: AssetInfo,
+    /// The address that can pause and resume the contract besides the owner
+    pub guardian: Option<String>,
+    /// The DAO the contract provides voting power to. Enables the voting extension
+    pub dao: Option<String>,
 }
 
 #[cw_serde]
@@ -29,6 +34,29 @@ pub enum ExecuteMsg {
         /// The amount of tokens to claim
         amount: Option<Uint128>,
     },
+    /// Claims vested tokens of a specific asset and sends them to a recipient
+    ClaimAsset {
+        /// The asset to claim
+        asset_info: AssetInfo,
+        /// The address that receives the vested tokens
+        recipient: Option<String>,
+        /// The amount of tokens to claim
+        amount: Option<Uint128>,
+    },
+    /// Claims the available tokens of all the assets of multiple vesting accounts and sends them
+    /// to the accounts. Addresses without a vesting account are skipped.
+    /// The number of addresses per call is limited by the claim_for limit.
+    /// ## Executor
+    /// Only the current owner or a vesting manager can execute this
+    ClaimFor {
+        addresses: Vec<String>,
+    },
+    /// Sets the maximum number of addresses a single ClaimFor message can process
+    /// ## Executor
+    /// Only the current owner can execute this
+    SetClaimForLimit {
+        limit: u32,
+    },
     /// RegisterVestingAccounts registers vesting targets/accounts
     RegisterVestingAccounts {
         vesting_accounts: Vec<VestingAccount>,
@@ -56,6 +84,14 @@ pub enum ExecuteMsg {
     SetVestingToken {
         vesting_token: AssetInfo,
     },
+    /// Overwrites the vesting state of the vesting token, e.g. to reconcile it with the vesting
+    /// accounts after an accounting error.
+    /// ## Executor
+    /// Only the current owner can execute this
+    RepairVestingState {
+        total_granted: Uint128,
+        total_released: Uint128,
+    },
     /// Contains messages associated with the managed extension for vesting contracts.
     ManagedExtension {
         msg: ExecuteMsgManaged,
@@ -68,6 +104,10 @@ pub enum ExecuteMsg {
     HistoricalExtension {
         msg: ExecuteMsgHistorical,
     },
+    /// Contains messages associated with the pausable extension for vesting contracts.
+    PausableExtension {
+        msg: ExecuteMsgPausable,
+    },
     Receive(Cw20ReceiveMsg),
 }
 
@@ -90,6 +130,17 @@ impl From<ExecuteMsg> for BaseExecute {
     fn from(item: ExecuteMsg) -> Self {
         match item {
             ExecuteMsg::Claim { recipient, amount } => BaseExecute::Claim { recipient, amount },
+            ExecuteMsg::ClaimAsset {
+                asset_info,
+                recipient,
+                amount,
+            } => BaseExecute::ClaimAsset {
+                asset_info,
+                recipient,
+                amount,
+            },
+            ExecuteMsg::ClaimFor { addresses } => BaseExecute::ClaimFor { addresses },
+            ExecuteMsg::SetClaimForLimit { limit } => BaseExecute::SetClaimForLimit { limit },
             ExecuteMsg::RegisterVestingAccounts { vesting_accounts } => {
                 BaseExecute::RegisterVestingAccounts { vesting_accounts }
             }
@@ -101,9 +152,17 @@ impl From<ExecuteMsg> for BaseExecute {
             ExecuteMsg::SetVestingToken { vesting_token } => {
                 BaseExecute::SetVestingToken { vesting_token }
             }
+            ExecuteMsg::RepairVestingState {
+                total_granted,
+                total_released,
+            } => BaseExecute::RepairVestingState {
+                total_granted,
+                total_released,
+            },
             ExecuteMsg::ManagedExtension { msg } => BaseExecute::ManagedExtension { msg },
             ExecuteMsg::WithManagersExtension { msg } => BaseExecute::WithManagersExtension { msg },
             ExecuteMsg::HistoricalExtension { msg } => BaseExecute::HistoricalExtension { msg },
+            ExecuteMsg::PausableExtension { msg } => BaseExecute::PausableExtension { msg },
             _ => panic!("Unhandled ExecuteMsg variant"),
         }
     }
@@ -13,12 +13,16 @@ use cw2::set_contract_version;
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 use vesting_base::builder::VestingBaseBuilder;
 use vesting_base::error::ContractError;
+use vesting_base::ext_pausable::assert_not_paused;
 use vesting_base::handlers::execute as base_execute;
 use vesting_base::handlers::query as base_query;
+use vesting_base::handlers::{clear_vesting_account, compute_total_amount};
+use vesting_base::migration::VestingMigrationBuilder;
 use vesting_base::msg::{ExecuteMsg as BaseExecute, QueryMsg};
-use vesting_base::state::{vesting_info, vesting_state, CONFIG};
+use vesting_base::state::{vesting_info, CONFIG};
 use vesting_base::types::{
-    VestingAccountFullInfo, VestingInfo, VestingSchedule, VestingSchedulePoint,
+    VestingAccountFullInfo, VestingInfo, VestingSchedule, VestingSchedulePeriodic,
+    VestingSchedulePoint,
 };
 
 /// Contract name that is used for migration.
@@ -36,10 +40,15 @@ pub fn instantiate(
 ) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    VestingBaseBuilder::default()
+    let mut builder = VestingBaseBuilder::default();
+    builder
         .historical()
         .with_managers(msg.vesting_managers)
-        .build(deps, msg.owner, msg.token_info_manager)?;
+        .pausable(msg.guardian);
+    if let Some(dao) = msg.dao {
+        builder.voting(dao);
+    }
+    builder.build(deps, msg.owner, msg.token_info_manager)?;
     Ok(Response::default())
 }
 
@@ -69,25 +78,46 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     base_query(deps, env, msg)
 }
 
-/// Manages contract migration.
+/// Manages contract migration. The liquidity migration config is only set by the migration
+/// to the version that introduced it.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
-    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-
-    XYK_TO_CL_MIGRATION_CONFIG.save(
-        deps.storage,
-        &XykToClMigrationConfig {
-            max_slippage: msg.max_slippage,
-            ntrn_denom: msg.ntrn_denom,
-            xyk_pair: deps.api.addr_validate(msg.xyk_pair.as_str())?,
-            paired_denom: msg.paired_denom,
-            cl_pair: deps.api.addr_validate(msg.cl_pair.as_str())?,
-            new_lp_token: deps.api.addr_validate(msg.new_lp_token.as_str())?,
-            pcl_vesting: deps.api.addr_validate(msg.pcl_vesting.as_str())?,
-        },
-    )?;
-
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let extensions = msg.extensions.clone();
+    VestingMigrationBuilder::new(CONTRACT_NAME, CONTRACT_VERSION)
+        .step("1.2.0", move |deps, _env| {
+            let config = match (
+                msg.max_slippage,
+                msg.ntrn_denom,
+                msg.paired_denom,
+                msg.xyk_pair,
+                msg.cl_pair,
+                msg.new_lp_token,
+                msg.pcl_vesting,
+            ) {
+                (
+                    Some(max_slippage),
+                    Some(ntrn_denom),
+                    Some(paired_denom),
+                    Some(xyk_pair),
+                    Some(cl_pair),
+                    Some(new_lp_token),
+                    Some(pcl_vesting),
+                ) => XykToClMigrationConfig {
+                    max_slippage,
+                    ntrn_denom,
+                    xyk_pair: deps.api.addr_validate(&xyk_pair)?,
+                    paired_denom,
+                    cl_pair: deps.api.addr_validate(&cl_pair)?,
+                    new_lp_token: deps.api.addr_validate(&new_lp_token)?,
+                    pcl_vesting: deps.api.addr_validate(&pcl_vesting)?,
+                },
+                // the liquidity migration can't work without its whole config
+                _ => return Err(ContractError::MigrationError {}),
+            };
+            XYK_TO_CL_MIGRATION_CONFIG.save(deps.storage, &config)?;
+            Ok(())
+        })
+        .migrate(deps, env, extensions)
 }
 
 fn execute_migrate_liquidity(
@@ -97,6 +127,7 @@ fn execute_migrate_liquidity(
     slippage_tolerance: Option<Decimal>,
     user: Option<String>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage)?;
     let config = CONFIG.load(deps.storage)?;
     let migration_config: XykToClMigrationConfig = XYK_TO_CL_MIGRATION_CONFIG.load(deps.storage)?;
     let address = match user {
@@ -105,6 +136,10 @@ fn execute_migrate_liquidity(
     };
     let vesting_info = vesting_info(config.extensions.historical);
     let info = vesting_info.load(deps.storage, address.clone())?;
+    // only the vesting token is migrated, so the other vested assets would be lost
+    if !info.additional_asset_infos().is_empty() {
+        return Err(ContractError::MigrationError {});
+    }
     let mut resp = Response::default();
     let user = VestingAccountFullInfo {
         address,
@@ -137,15 +172,7 @@ fn execute_migrate_liquidity(
         }
 
         if !info.schedules.is_empty() {
-            vesting_info.save(
-                deps.storage,
-                user.address.clone(),
-                &VestingInfo {
-                    schedules: vec![],
-                    released_amount: Uint128::zero(),
-                },
-                env.block.height,
-            )?;
+            clear_vesting_account(deps.storage, user.address, &info, env.block.height)?;
         }
         return Ok(resp);
     };
@@ -267,16 +294,6 @@ fn migrate_liquidity_to_cl_pair_callback(
         })?,
         funds: vec![],
     }));
-    let config = CONFIG.load(deps.storage)?;
-    vesting_state(config.extensions.historical).update::<_, ContractError>(
-        deps.storage,
-        env.block.height,
-        |s| {
-            let mut state = s.unwrap_or_default();
-            state.total_granted = state.total_granted.checked_sub(amount)?;
-            Ok(state)
-        },
-    )?;
     // push the next migration step as a callback message
     msgs.push(
         CallbackMsg::ProvideLiquidityToClPairAfterWithdrawal {
@@ -366,7 +383,6 @@ fn post_migration_vesting_reschedule_callback(
     user: &VestingAccountFullInfo,
     init_balance_pcl_lp: Uint128,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
     let migration_config: XykToClMigrationConfig = XYK_TO_CL_MIGRATION_CONFIG.load(deps.storage)?;
     let balance_response: BalanceResponse = deps.querier.query_wasm_smart(
         &migration_config.new_lp_token,
@@ -376,39 +392,15 @@ fn post_migration_vesting_reschedule_callback(
     )?;
     let balance_diff = balance_response.balance.checked_sub(init_balance_pcl_lp)?;
 
-    let schedule = user
-        .info
-        .schedules
-        .last()
-        .ok_or(ContractError::VestingScheduleExtractError(
-            user.address.to_string(),
-        ))?;
-
-    let new_end_point = match &schedule.end_point {
-        Some(end_point) => Option::from(VestingSchedulePoint {
-            time: end_point.time,
-            amount: balance_diff,
-        }),
-        None => None,
-    };
+    let (new_schedules, released_amount) =
+        rescale_schedules(&user.address, &user.info, balance_diff)?;
 
-    let new_schedule = VestingSchedule {
-        start_point: VestingSchedulePoint {
-            time: schedule.start_point.time,
-            amount: Uint128::zero(),
-        },
-        end_point: new_end_point,
-    };
-
-    let vesting_info = vesting_info(config.extensions.historical);
-
-    vesting_info.save(
+    // the account leaves the contract along with its granted and released amounts, so that the
+    // vesting state stays the sum of the remaining accounts
+    clear_vesting_account(
         deps.storage,
         user.address.clone(),
-        &VestingInfo {
-            schedules: vec![],
-            released_amount: Uint128::zero(),
-        },
+        &user.info,
         env.block.height,
     )?;
     if !balance_diff.is_zero() {
@@ -421,8 +413,10 @@ fn post_migration_vesting_reschedule_callback(
                 msg: to_json_binary(&vesting_lp_pcl::msg::Cw20HookMsg::MigrateXYKLiquidity {
                     user_address_raw: user.address.clone(),
                     user_vesting_info: VestingInfo {
-                        schedules: vec![new_schedule],
-                        released_amount: Uint128::zero(),
+                        schedules: new_schedules,
+                        released_amount,
+                        released_assets: vec![],
+                        slashed_amount: Uint128::zero(),
                     },
                 })?,
             })?,
@@ -437,10 +431,135 @@ fn post_migration_vesting_reschedule_callback(
 fn compute_share(vesting_info: &VestingInfo) -> StdResult<Uint128> {
     let mut available_amount: Uint128 = Uint128::zero();
     for sch in &vesting_info.schedules {
-        if let Some(end_point) = &sch.end_point {
-            available_amount = available_amount.checked_add(end_point.amount)?
-        }
+        available_amount = available_amount.checked_add(compute_total_amount(sch)?)?;
     }
 
     Ok(available_amount.checked_sub(vesting_info.released_amount)?)
 }
+
+/// Scales every vesting schedule of an account so that the unclaimed part of the schedules
+/// grants the migrated amount of the new LP tokens. The schedules keep their times, cliffs,
+/// intermediate points and tranches, and the released amount is scaled by the same ratio, so the
+/// account doesn't receive its claimed tokens once more. Returns the new schedules and the new
+/// released amount.
+///
+/// Schedules left granting nothing are dropped and schedules that no longer vest over time are
+/// replaced with a single point at their cliff, while the rounding remainder is added to the last
+/// schedule. If no schedule is left, the whole amount unlocks at the end of the old schedules.
+///
+/// * **address** the vesting account being migrated.
+///
+/// * **vesting_info** the vesting information of the account.
+///
+/// * **amount** the amount of the new LP tokens the unclaimed part of the schedules should grant.
+fn rescale_schedules(
+    address: &Addr,
+    vesting_info: &VestingInfo,
+    amount: Uint128,
+) -> Result<(Vec<VestingSchedule>, Uint128), ContractError> {
+    let unclaimed_amount = compute_share(vesting_info)?;
+    if unclaimed_amount.is_zero() {
+        return Err(ContractError::VestingScheduleExtractError(
+            address.to_string(),
+        ));
+    }
+    let released_amount = vesting_info
+        .released_amount
+        .multiply_ratio(amount, unclaimed_amount);
+    let target_amount = amount.checked_add(released_amount)?;
+
+    let scale = |point: &VestingSchedulePoint| VestingSchedulePoint {
+        time: point.time,
+        amount: point.amount.multiply_ratio(amount, unclaimed_amount),
+    };
+    let mut new_schedules: Vec<VestingSchedule> = vec![];
+    let mut end_time = 0;
+    for sch in &vesting_info.schedules {
+        let mut new_sch = VestingSchedule {
+            start_point: scale(&sch.start_point),
+            end_point: sch.end_point.as_ref().map(scale),
+            cliff: sch.cliff,
+            intermediate_points: sch
+                .intermediate_points
+                .as_ref()
+                .map(|points| points.iter().map(scale).collect()),
+            periodic: sch
+                .periodic
+                .as_ref()
+                .map(|periodic| VestingSchedulePeriodic {
+                    period: periodic.period,
+                    num_periods: periodic.num_periods,
+                    amount_per_period: periodic
+                        .amount_per_period
+                        .multiply_ratio(amount, unclaimed_amount),
+                }),
+            // the new LP token is the vesting token of the PCL vesting contract
+            asset_info: None,
+            voting_weight: sch.voting_weight,
+        };
+        end_time = end_time.max(schedule_end_time(sch));
+
+        let total_amount = compute_total_amount(&new_sch)?;
+        if total_amount.is_zero() {
+            continue;
+        }
+        let vests_over_time = match (&new_sch.end_point, &new_sch.periodic) {
+            (Some(end_point), _) => end_point.amount > new_sch.start_point.amount,
+            (None, Some(periodic)) => !periodic.amount_per_period.is_zero(),
+            (None, None) => true,
+        };
+        if !vests_over_time {
+            new_sch = VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: new_sch.cliff.unwrap_or_default().max(sch.start_point.time),
+                    amount: total_amount,
+                },
+                end_point: None,
+                cliff: None,
+                intermediate_points: None,
+                periodic: None,
+                ..new_sch
+            };
+        }
+        new_schedules.push(new_sch);
+    }
+
+    let mut new_total_amount = Uint128::zero();
+    for sch in &new_schedules {
+        new_total_amount = new_total_amount.checked_add(compute_total_amount(sch)?)?;
+    }
+    let remainder = target_amount.checked_sub(new_total_amount)?;
+    match new_schedules.last_mut() {
+        Some(last) => match &mut last.end_point {
+            Some(end_point) => end_point.amount = end_point.amount.checked_add(remainder)?,
+            None => last.start_point.amount = last.start_point.amount.checked_add(remainder)?,
+        },
+        None => new_schedules.push(VestingSchedule {
+            start_point: VestingSchedulePoint {
+                time: end_time,
+                amount: remainder,
+            },
+            end_point: None,
+            cliff: None,
+            intermediate_points: None,
+            periodic: None,
+            asset_info: None,
+            voting_weight: None,
+        }),
+    }
+
+    Ok((new_schedules, released_amount))
+}
+
+/// Returns the time at which a vesting schedule is fully vested.
+fn schedule_end_time(sch: &VestingSchedule) -> u64 {
+    match (&sch.end_point, &sch.periodic) {
+        (Some(end_point), _) => end_point.time,
+        (None, Some(periodic)) => sch
+            .start_point
+            .time
+            .saturating_add(periodic.period.saturating_mul(periodic.num_periods)),
+        (None, None) => sch.start_point.time,
+    }
+    .max(sch.cliff.unwrap_or_default())
+}
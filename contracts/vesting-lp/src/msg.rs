@@ -3,9 +3,10 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, Decimal, Env, StdResult, Uint128, WasmMsg};
 use cw20::Cw20ReceiveMsg;
 use vesting_base::msg::{
-    ExecuteMsg as BaseExecute, ExecuteMsgHistorical, ExecuteMsgManaged, ExecuteMsgWithManagers,
+    ExecuteMsg as BaseExecute, ExecuteMsgHistorical, ExecuteMsgManaged, ExecuteMsgPausable,
+    ExecuteMsgWithManagers,
 };
-use vesting_base::types::{VestingAccount, VestingAccountFullInfo};
+use vesting_base::types::{ExtensionsMigration, VestingAccount, VestingAccountFullInfo};
 
 /// This structure describes the parameters used for creating a contract.
 #[cw_serde]
@@ -16,6 +17,10 @@ pub struct InstantiateMsg {
     pub vesting_managers: Vec<String>,
     /// Token info manager address
     pub token_info_manager: String,
+    /// The address that can pause and resume the contract besides the owner
+    pub guardian: Option<String>,
+    /// The DAO the contract provides voting power to. Enables the voting extension
+    pub dao: Option<String>,
 }
 
 #[cw_serde]
@@ -27,6 +32,25 @@ pub enum ExecuteMsg {
         /// The amount of tokens to claim
         amount: Option<Uint128>,
     },
+    /// Claims vested tokens of a specific asset and sends them to a recipient
+    ClaimAsset {
+        /// The asset to claim
+        asset_info: AssetInfo,
+        /// The address that receives the vested tokens
+        recipient: Option<String>,
+        /// The amount of tokens to claim
+        amount: Option<Uint128>,
+    },
+    /// Claims the available tokens of all the assets of multiple vesting accounts and sends them
+    /// to the accounts. Addresses without a vesting account are skipped.
+    /// The number of addresses per call is limited by the claim_for limit.
+    /// ## Executor
+    /// Only the current owner or a vesting manager can execute this
+    ClaimFor { addresses: Vec<String> },
+    /// Sets the maximum number of addresses a single ClaimFor message can process
+    /// ## Executor
+    /// Only the current owner can execute this
+    SetClaimForLimit { limit: u32 },
     /// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template
     Receive(Cw20ReceiveMsg),
     /// RegisterVestingAccounts registers vesting targets/accounts
@@ -54,12 +78,22 @@ pub enum ExecuteMsg {
     /// ## Executor
     /// Only the current owner or token info manager can execute this
     SetVestingToken { vesting_token: AssetInfo },
+    /// Overwrites the vesting state of the vesting token, e.g. to reconcile it with the vesting
+    /// accounts after an accounting error.
+    /// ## Executor
+    /// Only the current owner can execute this
+    RepairVestingState {
+        total_granted: Uint128,
+        total_released: Uint128,
+    },
     /// Contains messages associated with the managed extension for vesting contracts.
     ManagedExtension { msg: ExecuteMsgManaged },
     /// Contains messages associated with the with_managers extension for vesting contracts.
     WithManagersExtension { msg: ExecuteMsgWithManagers },
     /// Contains messages associated with the historical extension for vesting contracts.
     HistoricalExtension { msg: ExecuteMsgHistorical },
+    /// Contains messages associated with the pausable extension for vesting contracts.
+    PausableExtension { msg: ExecuteMsgPausable },
     #[serde(rename = "migrate_liquidity_to_pcl_pool")]
     MigrateLiquidityToPCLPool { user: Option<String> },
     /// Callbacks; only callable by the contract itself.
@@ -105,24 +139,38 @@ impl CallbackMsg {
     }
 }
 
-/// This structure describes a migration message.
-/// We currently take no arguments for migrations.
+/// This structure describes a migration message. The liquidity migration fields are only
+/// required by the migration to the version that introduced the liquidity migration.
 #[cw_serde]
 #[serde(rename_all = "snake_case")]
 pub struct MigrateMsg {
-    pub max_slippage: Decimal,
-    pub ntrn_denom: String,
-    pub paired_denom: String,
-    pub xyk_pair: String,
-    pub cl_pair: String,
-    pub new_lp_token: String,
-    pub pcl_vesting: String,
+    pub max_slippage: Option<Decimal>,
+    pub ntrn_denom: Option<String>,
+    pub paired_denom: Option<String>,
+    pub xyk_pair: Option<String>,
+    pub cl_pair: Option<String>,
+    pub new_lp_token: Option<String>,
+    pub pcl_vesting: Option<String>,
+    /// The extensions to enable or disable
+    #[serde(default)]
+    pub extensions: ExtensionsMigration,
 }
 
 impl From<ExecuteMsg> for BaseExecute {
     fn from(item: ExecuteMsg) -> Self {
         match item {
             ExecuteMsg::Claim { recipient, amount } => BaseExecute::Claim { recipient, amount },
+            ExecuteMsg::ClaimAsset {
+                asset_info,
+                recipient,
+                amount,
+            } => BaseExecute::ClaimAsset {
+                asset_info,
+                recipient,
+                amount,
+            },
+            ExecuteMsg::ClaimFor { addresses } => BaseExecute::ClaimFor { addresses },
+            ExecuteMsg::SetClaimForLimit { limit } => BaseExecute::SetClaimForLimit { limit },
             ExecuteMsg::Receive(msg) => BaseExecute::Receive(msg),
             ExecuteMsg::RegisterVestingAccounts { vesting_accounts } => {
                 BaseExecute::RegisterVestingAccounts { vesting_accounts }
@@ -135,9 +183,17 @@ impl From<ExecuteMsg> for BaseExecute {
             ExecuteMsg::SetVestingToken { vesting_token } => {
                 BaseExecute::SetVestingToken { vesting_token }
             }
+            ExecuteMsg::RepairVestingState {
+                total_granted,
+                total_released,
+            } => BaseExecute::RepairVestingState {
+                total_granted,
+                total_released,
+            },
             ExecuteMsg::ManagedExtension { msg } => BaseExecute::ManagedExtension { msg },
             ExecuteMsg::WithManagersExtension { msg } => BaseExecute::WithManagersExtension { msg },
             ExecuteMsg::HistoricalExtension { msg } => BaseExecute::HistoricalExtension { msg },
+            ExecuteMsg::PausableExtension { msg } => BaseExecute::PausableExtension { msg },
             _ => panic!("Unhandled ExecuteMsg variant"),
         }
     }
@@ -1,19 +1,29 @@
-use crate::msg::InstantiateMsg;
+use crate::msg::{CallbackMsg, ExecuteMsg as VestingLpExecuteMsg, InstantiateMsg, MigrateMsg};
+use crate::state::{XykToClMigrationConfig, XYK_TO_CL_MIGRATION_CONFIG};
 use astroport::asset::{native_asset_info, token_asset_info};
 use astroport::querier::query_balance;
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
-use cosmwasm_std::{coin, coins, to_json_binary, Addr, StdResult, Timestamp, Uint128};
-use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    coin, coins, from_json, to_json_binary, Addr, ContractResult, CosmosMsg, Decimal, StdResult,
+    SystemResult, Timestamp, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse};
 use cw_multi_test::{App, ContractWrapper, Executor};
 use cw_utils::PaymentError;
 use vesting_base::error::ContractError;
+use vesting_base::handlers::compute_total_amount;
 use vesting_base::msg::{
     Cw20HookMsg, ExecuteMsg, ExecuteMsgWithManagers, QueryMsg, QueryMsgHistorical,
     QueryMsgWithManagers,
 };
+use vesting_base::state::vesting_info;
 use vesting_base::types::{
-    Config, VestingAccount, VestingAccountResponse, VestingSchedule, VestingSchedulePoint,
+    Config, VestingAccount, VestingAccountFullInfo, VestingAccountResponse, VestingInfo,
+    VestingSchedule, VestingSchedulePeriodic, VestingSchedulePoint,
 };
+use vesting_lp_pcl::msg::Cw20HookMsg as PclCw20HookMsg;
 
 const OWNER1: &str = "owner1";
 const TOKEN_MANAGER: &str = "token_manager";
@@ -52,6 +62,7 @@ fn claim() {
                             time: Timestamp::from_seconds(101).seconds(),
                             amount: Uint128::new(200),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -62,6 +73,7 @@ fn claim() {
                             time: Timestamp::from_seconds(110).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -72,6 +84,7 @@ fn claim() {
                             time: Timestamp::from_seconds(200).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                 ],
             }],
@@ -100,6 +113,7 @@ fn claim() {
                             time: Timestamp::from_seconds(101).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -110,6 +124,7 @@ fn claim() {
                             time: Timestamp::from_seconds(110).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -120,6 +135,7 @@ fn claim() {
                             time: Timestamp::from_seconds(200).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                 ],
             }],
@@ -227,6 +243,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -252,6 +269,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -262,6 +280,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(110).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -272,6 +291,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
             ],
         }],
@@ -397,6 +417,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(100).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -423,6 +444,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -458,6 +480,7 @@ fn register_vesting_accounts() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(100),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -509,6 +532,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -556,6 +580,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(10),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -650,6 +675,7 @@ fn register_vesting_accounts_native() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -675,6 +701,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(100),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -733,6 +760,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(200),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -781,6 +809,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(200).seconds(),
                     amount: Uint128::new(10),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -874,6 +903,7 @@ fn query_at_height() {
                                 .seconds(),
                             amount: Uint128::new(50),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -888,6 +918,7 @@ fn query_at_height() {
                                 .seconds(),
                             amount: Uint128::new(150),
                         }),
+                        ..Default::default()
                     },
                 ],
             },
@@ -906,6 +937,7 @@ fn query_at_height() {
                             .seconds(),
                         amount: Uint128::new(1000),
                     }),
+                    ..Default::default()
                 }],
             },
         ],
@@ -1055,6 +1087,7 @@ fn vesting_managers() {
                         .seconds(),
                     amount: Uint128::new(50),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -1131,6 +1164,433 @@ fn vesting_managers() {
     assert_eq!(vesting_res.len(), 0);
 }
 
+#[test]
+fn claim_for() {
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+    let owner = Addr::unchecked(OWNER1);
+
+    let mut app = mock_app(&owner);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let cw20_token_instance =
+        instantiate_token(&mut app, token_code_id, "NTRN", Some(1_000_000_000_000_000));
+
+    let vesting_instance = instantiate_vesting(&mut app, &cw20_token_instance);
+
+    let schedule = VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: Timestamp::from_seconds(100).seconds(),
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: Timestamp::from_seconds(200).seconds(),
+            amount: Uint128::new(100),
+        }),
+        ..Default::default()
+    };
+    let msg = Cw20ExecuteMsg::Send {
+        contract: vesting_instance.to_string(),
+        msg: to_json_binary(&Cw20HookMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![
+                VestingAccount {
+                    address: user1.to_string(),
+                    schedules: vec![schedule.clone()],
+                },
+                VestingAccount {
+                    address: user2.to_string(),
+                    schedules: vec![schedule],
+                },
+            ],
+        })
+        .unwrap(),
+        amount: Uint128::from(200u128),
+    };
+    app.execute_contract(owner.clone(), cw20_token_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let msg = ExecuteMsg::ClaimFor {
+        addresses: vec![user1.to_string(), user2.to_string()],
+    };
+
+    // only the owner and vesting managers can claim for other addresses
+    let res = app
+        .execute_contract(user1.clone(), vesting_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, res.downcast().unwrap());
+
+    // the number of addresses is limited
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::SetClaimForLimit { limit: 1 },
+        &[],
+    )
+    .unwrap();
+    let limit: u32 = app
+        .wrap()
+        .query_wasm_smart(vesting_instance.clone(), &QueryMsg::ClaimForLimit {})
+        .unwrap();
+    assert_eq!(limit, 1);
+    let res = app
+        .execute_contract(owner.clone(), vesting_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ClaimForLimitExceeded { limit: 1 },
+        res.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        vesting_instance.clone(),
+        &ExecuteMsg::SetClaimForLimit { limit: 2 },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(owner.clone(), vesting_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // the available tokens are sent to the vesting accounts themselves
+    check_token_balance(&mut app, &cw20_token_instance, &user1, 100u128);
+    check_token_balance(&mut app, &cw20_token_instance, &user2, 100u128);
+    check_token_balance(&mut app, &cw20_token_instance, &vesting_instance, 0u128);
+    for user in [&user1, &user2] {
+        let vesting_res: VestingAccountResponse = app
+            .wrap()
+            .query_wasm_smart(
+                vesting_instance.clone(),
+                &QueryMsg::VestingAccount {
+                    address: user.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(vesting_res.info.released_amount, Uint128::from(100u128));
+    }
+}
+
+#[test]
+fn liquidity_migration_keeps_schedule_shapes() {
+    let point = |time: u64, amount: u128| VestingSchedulePoint {
+        time,
+        amount: Uint128::new(amount),
+    };
+    let piecewise_schedule = |scale: u128| VestingSchedule {
+        start_point: point(100, 0),
+        end_point: Some(point(400, 6 * scale)),
+        cliff: Some(150),
+        intermediate_points: Some(vec![point(200, 3 * scale), point(300, 3 * scale)]),
+        voting_weight: Some(Decimal::percent(200)),
+        ..Default::default()
+    };
+    let periodic_schedule = |scale: u128| VestingSchedule {
+        start_point: point(100, scale),
+        periodic: Some(VestingSchedulePeriodic {
+            period: 100,
+            num_periods: 3,
+            amount_per_period: Uint128::new(scale),
+        }),
+        ..Default::default()
+    };
+
+    // every schedule is scaled to the PCL LP tokens and keeps its shape
+    assert_eq!(
+        migrate_vesting_account(vec![piecewise_schedule(100), periodic_schedule(100)], 0, 500),
+        VestingInfo {
+            schedules: vec![piecewise_schedule(50), periodic_schedule(50)],
+            released_amount: Uint128::zero(),
+            released_assets: vec![],
+            slashed_amount: Uint128::zero(),
+        },
+    );
+
+    // the claimed tokens are scaled along with the schedules, so only the unclaimed ones are
+    // granted by the PCL LP tokens
+    assert_eq!(
+        migrate_vesting_account(
+            vec![piecewise_schedule(100), periodic_schedule(100)],
+            200,
+            400
+        ),
+        VestingInfo {
+            schedules: vec![piecewise_schedule(50), periodic_schedule(50)],
+            released_amount: Uint128::new(100),
+            released_assets: vec![],
+            slashed_amount: Uint128::zero(),
+        },
+    );
+}
+
+#[test]
+fn liquidity_migration_of_small_balances() {
+    let point = |time: u64, amount: u128| VestingSchedulePoint {
+        time,
+        amount: Uint128::new(amount),
+    };
+    let point_schedule = |time: u64, amount: u128| VestingSchedule {
+        start_point: point(time, amount),
+        ..Default::default()
+    };
+    let linear_schedule = |scale: u128| VestingSchedule {
+        start_point: point(100, 8 * scale),
+        end_point: Some(point(400, 10 * scale)),
+        cliff: Some(200),
+        ..Default::default()
+    };
+    let periodic_schedule = |scale: u128| VestingSchedule {
+        start_point: point(100, scale),
+        periodic: Some(VestingSchedulePeriodic {
+            period: 100,
+            num_periods: 3,
+            amount_per_period: Uint128::new(scale),
+        }),
+        ..Default::default()
+    };
+    // 900 tokens are granted and 100 of them are claimed
+    let schedules = vec![linear_schedule(50), periodic_schedule(100)];
+    let vesting_info = |schedules: Vec<VestingSchedule>, released_amount: u128| VestingInfo {
+        schedules,
+        released_amount: Uint128::new(released_amount),
+        released_assets: vec![],
+        slashed_amount: Uint128::zero(),
+    };
+
+    assert_eq!(
+        migrate_vesting_account(schedules.clone(), 100, 16),
+        vesting_info(vec![linear_schedule(1), periodic_schedule(2)], 2),
+    );
+    // the periodic schedule grants nothing and is dropped, while the linear one doesn't vest
+    // over time anymore and unlocks at its cliff
+    assert_eq!(
+        migrate_vesting_account(schedules.clone(), 100, 4),
+        vesting_info(vec![point_schedule(200, 4)], 0),
+    );
+    // none of the schedules grants anything, so the tokens unlock at the end of the schedules
+    assert_eq!(
+        migrate_vesting_account(schedules, 100, 1),
+        vesting_info(vec![point_schedule(400, 1)], 0),
+    );
+}
+
+#[test]
+fn migration_liquidity_config() {
+    let mut deps = mock_dependencies();
+    crate::contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER1, &[]),
+        InstantiateMsg {
+            owner: OWNER1.to_string(),
+            token_info_manager: TOKEN_MANAGER.to_string(),
+            vesting_managers: vec![],
+            guardian: None,
+            dao: None,
+        },
+    )
+    .unwrap();
+    let empty_msg = MigrateMsg {
+        max_slippage: None,
+        ntrn_denom: None,
+        paired_denom: None,
+        xyk_pair: None,
+        cl_pair: None,
+        new_lp_token: None,
+        pcl_vesting: None,
+        extensions: Default::default(),
+    };
+
+    // the liquidity migration config isn't needed once it's set
+    crate::contract::migrate(deps.as_mut(), mock_env(), empty_msg.clone()).unwrap();
+    assert!(XYK_TO_CL_MIGRATION_CONFIG
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
+
+    // the migration introducing the liquidity migration requires the whole config
+    set_contract_version(deps.as_mut().storage, "neutron-vesting-lp", "1.1.0").unwrap();
+    assert_eq!(
+        crate::contract::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                max_slippage: Some(Decimal::percent(5)),
+                ..empty_msg
+            },
+        )
+        .unwrap_err(),
+        ContractError::MigrationError {},
+    );
+    crate::contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            max_slippage: Some(Decimal::percent(5)),
+            ntrn_denom: Some("untrn".to_string()),
+            paired_denom: Some("uibcusdc".to_string()),
+            xyk_pair: Some("xyk_pair".to_string()),
+            cl_pair: Some("cl_pair".to_string()),
+            new_lp_token: Some("pcl_lp".to_string()),
+            pcl_vesting: Some("pcl_vesting".to_string()),
+            extensions: Default::default(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        XYK_TO_CL_MIGRATION_CONFIG
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        XykToClMigrationConfig {
+            max_slippage: Decimal::percent(5),
+            ntrn_denom: "untrn".to_string(),
+            xyk_pair: Addr::unchecked("xyk_pair"),
+            paired_denom: "uibcusdc".to_string(),
+            cl_pair: Addr::unchecked("cl_pair"),
+            new_lp_token: Addr::unchecked("pcl_lp"),
+            pcl_vesting: Addr::unchecked("pcl_vesting"),
+        },
+    );
+}
+
+/// Registers a vesting account with the schedules, claims the tokens and migrates its liquidity
+/// to the PCL pair for the PCL LP tokens. Returns the vesting information sent to the PCL vesting
+/// contract.
+fn migrate_vesting_account(
+    schedules: Vec<VestingSchedule>,
+    claimed_amount: u128,
+    pcl_lp_amount: u128,
+) -> VestingInfo {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER1, &[]),
+        InstantiateMsg {
+            owner: OWNER1.to_string(),
+            token_info_manager: TOKEN_MANAGER.to_string(),
+            vesting_managers: vec![],
+            guardian: None,
+            dao: None,
+        },
+    )
+    .unwrap();
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TOKEN_MANAGER, &[]),
+        VestingLpExecuteMsg::SetVestingToken {
+            vesting_token: token_asset_info(Addr::unchecked("xyk_lp")),
+        },
+    )
+    .unwrap();
+    XYK_TO_CL_MIGRATION_CONFIG
+        .save(
+            deps.as_mut().storage,
+            &XykToClMigrationConfig {
+                max_slippage: Decimal::percent(5),
+                ntrn_denom: "untrn".to_string(),
+                xyk_pair: Addr::unchecked("xyk_pair"),
+                paired_denom: "uibcusdc".to_string(),
+                cl_pair: Addr::unchecked("cl_pair"),
+                new_lp_token: Addr::unchecked("pcl_lp"),
+                pcl_vesting: Addr::unchecked("pcl_vesting"),
+            },
+        )
+        .unwrap();
+    // the liquidity of the account is provided to the PCL pair for the PCL LP tokens
+    deps.querier.update_wasm(move |_| {
+        SystemResult::Ok(ContractResult::Ok(
+            to_json_binary(&BalanceResponse {
+                balance: Uint128::new(pcl_lp_amount),
+            })
+            .unwrap(),
+        ))
+    });
+
+    let mut amount = Uint128::zero();
+    for sch in &schedules {
+        amount += compute_total_amount(sch).unwrap();
+    }
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("xyk_lp", &[]),
+        VestingLpExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: OWNER1.to_string(),
+            amount,
+            msg: to_json_binary(&Cw20HookMsg::RegisterVestingAccounts {
+                vesting_accounts: vec![VestingAccount {
+                    address: USER1.to_string(),
+                    schedules,
+                }],
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+    if claimed_amount > 0 {
+        crate::contract::execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(USER1, &[]),
+            VestingLpExecuteMsg::Claim {
+                recipient: None,
+                amount: Some(Uint128::new(claimed_amount)),
+            },
+        )
+        .unwrap();
+    }
+
+    let info = vesting_info(true)
+        .load(deps.as_ref().storage, Addr::unchecked(USER1))
+        .unwrap();
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(env.contract.address.as_str(), &[]),
+        VestingLpExecuteMsg::Callback(CallbackMsg::PostMigrationVestingReschedule {
+            user: VestingAccountFullInfo {
+                address: Addr::unchecked(USER1),
+                info,
+            },
+            init_balance_pcl_lp: Uint128::zero(),
+        }),
+    )
+    .unwrap();
+
+    let send_msg = match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }) => {
+            assert_eq!(contract_addr, "pcl_lp");
+            from_json::<Cw20ExecuteMsg>(msg).unwrap()
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    };
+    let hook_msg = match send_msg {
+        Cw20ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => {
+            assert_eq!(contract, "pcl_vesting");
+            assert_eq!(amount, Uint128::new(pcl_lp_amount));
+            from_json::<PclCw20HookMsg>(&msg).unwrap()
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    };
+    match hook_msg {
+        PclCw20HookMsg::MigrateXYKLiquidity {
+            user_address_raw,
+            user_vesting_info,
+        } => {
+            assert_eq!(user_address_raw, Addr::unchecked(USER1));
+            user_vesting_info
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
 fn mock_app(owner: &Addr) -> App {
     App::new(|app, _, storage| {
         app.bank
@@ -1196,6 +1656,8 @@ fn instantiate_vesting(app: &mut App, cw20_token_instance: &Addr) -> Addr {
         owner: OWNER1.to_string(),
         token_info_manager: TOKEN_MANAGER.to_string(),
         vesting_managers: vec![],
+        guardian: None,
+        dao: None,
     };
 
     let vesting_instance = app
@@ -1249,6 +1711,8 @@ fn instantiate_vesting_remote_chain(app: &mut App) -> Addr {
         owner: OWNER1.to_string(),
         token_info_manager: TOKEN_MANAGER.to_string(),
         vesting_managers: vec![],
+        guardian: None,
+        dao: None,
     };
 
     let res = app
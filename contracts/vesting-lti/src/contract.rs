@@ -5,7 +5,8 @@ use vesting_base::msg::MigrateMsg;
 use vesting_base::{
     builder::VestingBaseBuilder,
     error::ContractError,
-    handlers::{execute as base_execute, migrate as base_migrate, query as base_query},
+    handlers::{execute as base_execute, query as base_query},
+    migration::VestingMigrationBuilder,
     msg::{ExecuteMsg, QueryMsg},
 };
 
@@ -33,9 +34,17 @@ pub fn instantiate(
 ) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    VestingBaseBuilder::default()
-        .managed()
-        .build(deps, msg.owner, msg.token_info_manager)?;
+    let mut builder = VestingBaseBuilder::default();
+    builder.managed();
+    if let Some(unbonding_period) = msg.unbonding_period {
+        builder.staking(unbonding_period);
+    }
+    builder.pausable(msg.guardian);
+    // the voting extension requires the historical one
+    if let Some(dao) = msg.dao {
+        builder.historical().voting(dao);
+    }
+    builder.build(deps, msg.owner, msg.token_info_manager)?;
     Ok(Response::default())
 }
 
@@ -59,5 +68,5 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 // Exposes migrate function.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
-    base_migrate(deps, env, msg)
+    VestingMigrationBuilder::new(CONTRACT_NAME, CONTRACT_VERSION).migrate(deps, env, msg.extensions)
 }
@@ -53,6 +53,7 @@ fn claim() {
                             time: Timestamp::from_seconds(101).seconds(),
                             amount: Uint128::new(200),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -63,6 +64,7 @@ fn claim() {
                             time: Timestamp::from_seconds(110).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -73,6 +75,7 @@ fn claim() {
                             time: Timestamp::from_seconds(200).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                 ],
             }],
@@ -101,6 +104,7 @@ fn claim() {
                             time: Timestamp::from_seconds(101).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -111,6 +115,7 @@ fn claim() {
                             time: Timestamp::from_seconds(110).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                     VestingSchedule {
                         start_point: VestingSchedulePoint {
@@ -121,6 +126,7 @@ fn claim() {
                             time: Timestamp::from_seconds(200).seconds(),
                             amount: Uint128::new(100),
                         }),
+                        ..Default::default()
                     },
                 ],
             }],
@@ -229,6 +235,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -254,6 +261,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -264,6 +272,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(110).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -274,6 +283,7 @@ fn claim_native() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
             ],
         }],
@@ -403,6 +413,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(100).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -429,6 +440,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -464,6 +476,7 @@ fn register_vesting_accounts() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(100),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -515,6 +528,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -562,6 +576,7 @@ fn register_vesting_accounts() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(10),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -656,6 +671,7 @@ fn register_vesting_accounts_native() {
                         time: Timestamp::from_seconds(150).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 }],
             }],
         })
@@ -681,6 +697,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(100),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -739,6 +756,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(150).seconds(),
                     amount: Uint128::new(200),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -787,6 +805,7 @@ fn register_vesting_accounts_native() {
                     time: Timestamp::from_seconds(200).seconds(),
                     amount: Uint128::new(10),
                 }),
+                ..Default::default()
             }],
         }],
     };
@@ -892,13 +911,14 @@ fn remove_vesting_accounts() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
                         time: Timestamp::from_seconds(100).seconds(),
                         amount: Uint128::new(100),
                     },
-                    end_point: None,
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -909,6 +929,7 @@ fn remove_vesting_accounts() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
             ],
         }],
@@ -1012,13 +1033,14 @@ fn remove_vesting_accounts() {
                         time: Timestamp::from_seconds(101).seconds(),
                         amount: Uint128::new(200),
                     }),
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
                         time: Timestamp::from_seconds(100).seconds(),
                         amount: Uint128::new(100),
                     },
-                    end_point: None,
+                    ..Default::default()
                 },
                 VestingSchedule {
                     start_point: VestingSchedulePoint {
@@ -1029,6 +1051,7 @@ fn remove_vesting_accounts() {
                         time: Timestamp::from_seconds(200).seconds(),
                         amount: Uint128::new(100),
                     }),
+                    ..Default::default()
                 },
             ],
         }],
@@ -1130,6 +1153,43 @@ fn remove_vesting_accounts() {
     assert_eq!(vesting_state.total_released.clone(), Uint128::new(0u128));
 }
 
+#[test]
+fn instantiate_with_dao() {
+    let owner = Addr::unchecked(OWNER1);
+
+    let mut app = mock_app(&owner);
+
+    let vesting_contract = Box::new(ContractWrapper::new_with_empty(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    ));
+    let vesting_code_id = app.store_code(vesting_contract);
+    let init_msg = InstantiateMsg {
+        owner: OWNER1.to_string(),
+        token_info_manager: TOKEN_MANAGER.to_string(),
+        unbonding_period: None,
+        guardian: None,
+        dao: Some(String::from("dao")),
+    };
+    let vesting_instance = app
+        .instantiate_contract(vesting_code_id, owner, &init_msg, &[], "Vesting", None)
+        .unwrap();
+
+    // the voting extension enables the historical one it requires
+    let config: Config = app
+        .wrap()
+        .query_wasm_smart(vesting_instance.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert!(config.extensions.voting);
+    assert!(config.extensions.historical);
+    let dao: Addr = app
+        .wrap()
+        .query_wasm_smart(vesting_instance, &QueryMsg::Dao {})
+        .unwrap();
+    assert_eq!(dao, Addr::unchecked("dao"));
+}
+
 fn mock_app(owner: &Addr) -> App {
     App::new(|app, _, storage| {
         app.bank
@@ -1194,6 +1254,9 @@ fn instantiate_vesting(app: &mut App, astro_token_instance: &Addr) -> Addr {
     let init_msg = InstantiateMsg {
         owner: OWNER1.to_string(),
         token_info_manager: TOKEN_MANAGER.to_string(),
+        unbonding_period: None,
+        guardian: None,
+        dao: None,
     };
 
     let vesting_instance = app
@@ -1242,6 +1305,9 @@ fn instantiate_vesting_remote_chain(app: &mut App) -> Addr {
     let init_msg = InstantiateMsg {
         owner: OWNER1.to_string(),
         token_info_manager: TOKEN_MANAGER.to_string(),
+        unbonding_period: None,
+        guardian: None,
+        dao: None,
     };
 
     let res = app
@@ -1,6 +1,6 @@
-use crate::state::{CONFIG, VESTING_MANAGERS};
+use crate::state::{CONFIG, DAO, GUARDIAN, PAUSED, UNBONDING_PERIOD, VESTING_MANAGERS};
 use crate::types::{Config, Extensions};
-use cosmwasm_std::{DepsMut, StdResult};
+use cosmwasm_std::{DepsMut, StdError, StdResult};
 
 /// A builder for vesting contracts with different extensions.
 #[derive(Default)]
@@ -9,6 +9,12 @@ pub struct VestingBaseBuilder {
     historical: bool,
     managed: bool,
     with_managers: bool,
+    transferable: bool,
+    with_operators: bool,
+    dao: Option<String>,
+    unbonding_period: Option<u64>,
+    pausable: bool,
+    guardian: Option<String>,
 }
 
 impl VestingBaseBuilder {
@@ -31,8 +37,50 @@ impl VestingBaseBuilder {
         self
     }
 
+    /// Appends the `transferable` extension to the created vesting contract.
+    pub fn transferable(&mut self) -> &mut VestingBaseBuilder {
+        self.transferable = true;
+        self
+    }
+
+    /// Appends the `with_operators` extension to the created vesting contract.
+    pub fn with_operators(&mut self) -> &mut VestingBaseBuilder {
+        self.with_operators = true;
+        self
+    }
+
+    /// Appends the `voting` extension to the created vesting contract. The extension requires
+    /// the `historical` extension.
+    pub fn voting(&mut self, dao: String) -> &mut VestingBaseBuilder {
+        self.dao = Some(dao);
+        self
+    }
+
+    /// Appends the `staking` extension to the created vesting contract.
+    ///
+    /// * **unbonding_period** the unbonding period of the chain in seconds.
+    pub fn staking(&mut self, unbonding_period: u64) -> &mut VestingBaseBuilder {
+        self.unbonding_period = Some(unbonding_period);
+        self
+    }
+
+    /// Appends the `pausable` extension to the created vesting contract.
+    ///
+    /// * **guardian** the address that can pause and resume the contract besides the owner.
+    pub fn pausable(&mut self, guardian: Option<String>) -> &mut VestingBaseBuilder {
+        self.pausable = true;
+        self.guardian = guardian;
+        self
+    }
+
     /// Validates the inputs and initialises the created contract state.
     pub fn build(&self, deps: DepsMut, owner: String, token_info_manager: String) -> StdResult<()> {
+        if self.dao.is_some() && !self.historical {
+            return Err(StdError::generic_err(
+                "The voting extension requires the historical extension",
+            ));
+        }
+
         let owner = deps.api.addr_validate(&owner)?;
         CONFIG.save(
             deps.storage,
@@ -44,6 +92,11 @@ impl VestingBaseBuilder {
                     historical: self.historical,
                     managed: self.managed,
                     with_managers: self.with_managers,
+                    transferable: self.transferable,
+                    with_operators: self.with_operators,
+                    voting: self.dao.is_some(),
+                    staking: self.unbonding_period.is_some(),
+                    pausable: self.pausable,
                 },
             },
         )?;
@@ -55,6 +108,22 @@ impl VestingBaseBuilder {
             }
         };
 
+        if let Some(unbonding_period) = self.unbonding_period {
+            UNBONDING_PERIOD.save(deps.storage, &unbonding_period)?;
+        }
+
+        if self.pausable {
+            PAUSED.save(deps.storage, &false)?;
+        }
+
+        if let Some(guardian) = &self.guardian {
+            GUARDIAN.save(deps.storage, &deps.api.addr_validate(guardian)?)?;
+        }
+
+        if let Some(dao) = &self.dao {
+            DAO.save(deps.storage, &deps.api.addr_validate(dao)?)?;
+        }
+
         Ok(())
     }
 }
@@ -20,15 +20,111 @@ pub enum ContractError {
     #[error("Vesting schedule error on addr: {0}. Should satisfy: (start < end and at_start < total) or (start = end and at_start = total)")]
     VestingScheduleError(String),
 
+    #[error("Vesting schedule error on addr: {0}. Cliff should satisfy: start <= cliff <= end")]
+    VestingScheduleCliffError(String),
+
+    #[error("Vesting schedule error on addr: {0}. Intermediate points should be placed between the start and end points in non-decreasing order of time and amount")]
+    VestingScheduleIntermediatePointsError(String),
+
+    #[error("Vesting schedule error on addr: {0}. Periodic schedule should have non-zero period, number of periods and amount per period, and no end point")]
+    VestingSchedulePeriodicError(String),
+
+    #[error("Vesting schedule asset error. All the vesting schedules registered at once should vest the same asset")]
+    VestingScheduleAssetError {},
+
     #[error("Vesting schedule error on addr: {0}. No schedule found")]
     VestingScheduleExtractError(String),
 
+    #[error("Vesting schedule amend error on addr: {0}. Only schedules with an end point can be amended, and an amendment can't decrease the end amount or the amount vested so far")]
+    VestingScheduleAmendError(String),
+
+    #[error("Vesting template error on template: {0}. Should satisfy: start_share <= 1, cliff <= duration and no cliff when start_share == 1")]
+    VestingTemplateError(String),
+
+    #[error("Vesting template {0} is not found")]
+    VestingTemplateNotFound(String),
+
+    #[error("Merkle root {0} is not found")]
+    MerkleRootNotFound(u64),
+
+    #[error("Merkle root error. Should be a hex-encoded 32-byte hash")]
+    MerkleRootError {},
+
+    #[error("Merkle proof verification failed on addr: {0}")]
+    MerkleProofError(String),
+
+    #[error("Vesting account {0} is already registered from the Merkle root")]
+    MerkleRootAlreadyRegistered(String),
+
+    #[error("Merkle root amount error. The registered accounts exceed the deposit of the root")]
+    MerkleRootAmountError {},
+
+    #[error("Merkle root expiration error. The expiration should be in the future")]
+    MerkleRootExpirationError {},
+
+    #[error("Merkle root {0} has expired")]
+    MerkleRootExpired(u64),
+
+    #[error("Merkle root {0} hasn't expired yet")]
+    MerkleRootNotExpired(u64),
+
+    #[error("Vesting state invariant error on addr: {0}. The vesting information of the account doesn't match the vesting state")]
+    VestingStateInvariantError(String),
+
+    #[error("Vesting state repair error. Should satisfy: total_released <= total_granted")]
+    VestingStateRepairError {},
+
+    #[error("Vesting termination error on addr: {0}. Termination time should be in the future")]
+    VestingTerminationTimeError(String),
+
+    #[error("Vesting termination error on addr: {0}. No reached termination found")]
+    VestingTerminationNotReachedError(String),
+
+    #[error("Vesting transfer error on addr: {0}. The account can't be transferred to itself")]
+    VestingTransferToSelfError(String),
+
+    #[error("Vesting transfer error on addr: {0}. The account has a scheduled termination")]
+    VestingTransferTerminationError(String),
+
+    #[error("Too many addresses to claim for. The limit is {limit}")]
+    ClaimForLimitExceeded { limit: u32 },
+
+    #[error("Operator approval has expired")]
+    OperatorApprovalExpired {},
+
+    #[error("Vesting account error on addr: {0}. The account has staked tokens")]
+    VestingAccountStakedError(String),
+
+    #[error("Vesting account error on addr: {0}. The account's tokens were undelegated by the owner and can't be delegated")]
+    VestingAccountStakingFrozen(String),
+
+    #[error("Vesting token can't be staked. It should be the native staking token")]
+    VestingTokenNotStakeable {},
+
+    #[error("Validator {0} is not found")]
+    ValidatorNotFound(String),
+
     #[error("Vesting schedule amount error. The total amount should be equal to the CW20 receive amount.")]
     VestingScheduleAmountError {},
 
     #[error("Contract can't be migrated!")]
     MigrationError {},
 
+    #[error("Contract {actual} can't be migrated to {expected}")]
+    MigrationContractError { expected: String, actual: String },
+
+    #[error("Contract can't be downgraded from version {from} to {to}")]
+    MigrationDowngradeError { from: String, to: String },
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Contract is not paused")]
+    NotPaused {},
+
     #[error("Vesting token is not set!")]
     VestingTokenIsNotSet {},
 
@@ -42,6 +138,12 @@ impl From<OverflowError> for ContractError {
     }
 }
 
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
+}
+
 pub fn ext_unsupported_err(extension: impl Into<String> + std::fmt::Display) -> StdError {
     StdError::generic_err(format!(
         "Extension is not enabled for the contract: {}.",
@@ -1,10 +1,16 @@
 use crate::error::{ext_unsupported_err, ContractError};
+use crate::handlers::{asset_schedules, compute_total_amount};
 use crate::msg::{ExecuteMsgHistorical, QueryMsgHistorical};
-use crate::state::{vesting_info, vesting_state, CONFIG};
-use crate::types::VestingInfo;
+use crate::state::{vesting_info, vesting_state, CONFIG, DEFAULT_LIMIT, MAX_LIMIT};
+use crate::types::{
+    UnclaimedAmountResponse, UnclaimedAmountsAtHeightResponse, VestingInfo, VestingInfoHistoryEntry,
+};
+use astroport::asset::addr_opt_validate;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128,
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+    Uint128,
 };
+use cw_storage_plus::Bound;
 
 /// Contains the historical extension check and routing of the message.
 pub(crate) fn handle_execute_historical_msg(
@@ -40,6 +46,26 @@ pub(crate) fn handle_query_historical_msg(
         QueryMsgHistorical::UnclaimedTotalAmountAtHeight { height } => {
             to_json_binary(&query_total_unclaimed_amount_at_height(deps, height)?)
         }
+        QueryMsgHistorical::UnclaimedAmountsAtHeight {
+            height,
+            start_after,
+            limit,
+        } => to_json_binary(&query_unclaimed_amounts_at_height(
+            deps,
+            height,
+            start_after,
+            limit,
+        )?),
+        QueryMsgHistorical::VestingInfoHistory {
+            address,
+            start_after,
+            limit,
+        } => to_json_binary(&query_vesting_info_history(
+            deps,
+            address,
+            start_after,
+            limit,
+        )?),
     }
 }
 
@@ -80,22 +106,124 @@ fn query_total_unclaimed_amount_at_height(deps: Deps, height: u64) -> StdResult<
     }
 }
 
+/// Returns the available amounts of distributed and yet to be claimed tokens for a page of vesting
+/// recipients at certain height. Every recipient that has ever vested tokens is visited, so
+/// a page can contain fewer accounts than the limit, and only `last_address` tells whether there
+/// are more pages.
+///
+/// * **height** the height we querying unclaimed amounts for
+///
+/// * **start_after** address from which to start reading vesting recipients.
+///
+/// * **limit** amount of vesting recipients to visit.
+fn query_unclaimed_amounts_at_height(
+    deps: Deps,
+    height: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<UnclaimedAmountsAtHeightResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_info = vesting_info(config.extensions.historical);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let mut last_address = addr_opt_validate(deps.api, &start_after)?;
+
+    // every write of a vesting information is recorded in the changelog, so it holds every
+    // address that had vesting information at any height
+    let mut accounts = vec![];
+    for _ in 0..limit {
+        let start = last_address
+            .clone()
+            .map(|address| Bound::exclusive((address, u64::MAX)));
+        let address = match vesting_info
+            .changelog()
+            .keys(deps.storage, start, None, Order::Ascending)
+            .next()
+        {
+            Some(item) => item?.0,
+            None => {
+                last_address = None;
+                break;
+            }
+        };
+
+        if let Some(info) =
+            vesting_info.may_load_at_height(deps.storage, address.clone(), height)?
+        {
+            accounts.push(UnclaimedAmountResponse {
+                address: address.clone(),
+                amount: compute_unclaimed_amount(&info)?,
+            });
+        }
+        last_address = Some(address);
+    }
+
+    Ok(UnclaimedAmountsAtHeightResponse {
+        accounts,
+        last_address,
+    })
+}
+
+/// Returns the changes of the vesting information of a specific vesting recipient.
+///
+/// * **address** vesting recipient for which to return the changes.
+///
+/// * **start_after** height from which to start reading changes.
+///
+/// * **limit** amount of changes to return.
+fn query_vesting_info_history(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<VestingInfoHistoryEntry>> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_info = vesting_info(config.extensions.historical);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // the changelog keeps the value preceding every change, so the value set by a change is
+    // the one preceding the next change, or the current value for the latest change
+    let changes = vesting_info
+        .changelog()
+        .prefix(address.clone())
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut history = vec![];
+    for (i, (height, _)) in changes.iter().enumerate().take(limit) {
+        let info = match changes.get(i + 1) {
+            Some((_, next_change)) => next_change.old.clone(),
+            None => vesting_info.may_load(deps.storage, address.clone())?,
+        };
+        history.push(VestingInfoHistoryEntry {
+            height: *height,
+            info,
+        });
+    }
+
+    Ok(history)
+}
+
 /// Computes the amount of distributed and yet unclaimed tokens for a specific vesting recipient at certain height.
 /// Returns the computed amount if the operation is successful.
+/// Tokens locked behind a not yet reached cliff are still unclaimed, so cliffs don't affect the result.
+/// Only the vesting token is taken into account.
 ///
 /// * **vesting_info** vesting schedules for which to compute the amount of tokens
 /// that are vested and can be claimed by the recipient.
-fn compute_unclaimed_amount(vesting_info: &VestingInfo) -> StdResult<Uint128> {
+pub(crate) fn compute_unclaimed_amount(vesting_info: &VestingInfo) -> StdResult<Uint128> {
     let mut available_amount: Uint128 = Uint128::zero();
-    for sch in &vesting_info.schedules {
-        if let Some(end_point) = &sch.end_point {
-            available_amount = available_amount.checked_add(end_point.amount)?;
-        } else {
-            available_amount = available_amount.checked_add(sch.start_point.amount)?;
-        }
+    for sch in asset_schedules(vesting_info, None) {
+        available_amount = available_amount.checked_add(compute_total_amount(sch)?)?;
     }
 
     available_amount
-        .checked_sub(vesting_info.released_amount)
+        .checked_sub(vesting_info.spent_amount_of(None)?)
         .map_err(StdError::from)
 }
@@ -1,11 +1,24 @@
 use crate::error::{ext_unsupported_err, ContractError};
-use crate::handlers::get_vesting_token;
+use crate::ext_staking::assert_not_staked;
+use crate::handlers::{
+    asset_schedules, compute_total_amount, compute_vested_amount, get_vesting_token,
+};
 use crate::msg::{ExecuteMsgManaged, QueryMsgManaged};
-use crate::state::{vesting_info, vesting_state, CONFIG};
-use astroport::asset::AssetInfoExt;
+use crate::state::{
+    remove_vesting_info, save_vesting_info, update_vesting_state, vesting_info, CONFIG,
+    DEFAULT_LIMIT, MAX_LIMIT, STAKING_FROZEN_ACCOUNTS, VESTING_TERMINATIONS,
+};
+use crate::types::{
+    VestingInfo, VestingSchedule, VestingSchedulePoint, VestingTermination,
+    VestingTerminationResponse,
+};
+use astroport::asset::{addr_opt_validate, AssetInfo, AssetInfoExt};
 use cosmwasm_std::{
-    attr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg, Uint128,
+    attr, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
+    StdResult, Storage, SubMsg, Uint128,
 };
+use cw_storage_plus::Bound;
+use std::iter;
 
 /// Contains the managed extension check and routing of the message.
 pub(crate) fn handle_execute_managed_msg(
@@ -24,6 +37,28 @@ pub(crate) fn handle_execute_managed_msg(
             vesting_accounts,
             clawback_account,
         } => remove_vesting_accounts(deps, env, info, vesting_accounts, clawback_account),
+        ExecuteMsgManaged::TruncateVestingAccounts {
+            vesting_accounts,
+            clawback_account,
+        } => truncate_vesting_accounts(deps, env, info, vesting_accounts, clawback_account),
+        ExecuteMsgManaged::ScheduleVestingTermination {
+            vesting_account,
+            termination_time,
+            clawback_account,
+        } => schedule_vesting_termination(
+            deps,
+            env,
+            info,
+            vesting_account,
+            termination_time,
+            clawback_account,
+        ),
+        ExecuteMsgManaged::CancelVestingTermination { vesting_account } => {
+            cancel_vesting_termination(deps, env, info, vesting_account)
+        }
+        ExecuteMsgManaged::ClawBackTerminatedAccounts { vesting_accounts } => {
+            claw_back_terminated_accounts(deps, env, info, vesting_accounts)
+        }
     }
 }
 
@@ -31,15 +66,18 @@ pub(crate) fn handle_execute_managed_msg(
 pub(crate) fn handle_query_managed_msg(
     deps: Deps,
     _env: Env,
-    _msg: QueryMsgManaged,
+    msg: QueryMsgManaged,
 ) -> StdResult<Binary> {
     let config = CONFIG.load(deps.storage)?;
     if !config.extensions.managed {
         return Err(ext_unsupported_err("managed"));
     }
 
-    // empty handler kept for uniformity with other extensions
-    unimplemented!()
+    match msg {
+        QueryMsgManaged::PendingTerminations { start_after, limit } => {
+            to_json_binary(&query_pending_terminations(deps, start_after, limit)?)
+        }
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -65,53 +103,66 @@ fn remove_vesting_accounts(
     // from the storage, and decrease the total granted metric.
     for vesting_account in vesting_accounts {
         let account_address = deps.api.addr_validate(&vesting_account)?;
+        assert_not_staked(deps.storage, &deps.querier, &env, &account_address)?;
 
         let config = CONFIG.load(deps.storage)?;
         let vesting_info = vesting_info(config.extensions.historical);
         if let Some(account_info) = vesting_info.may_load(deps.storage, account_address.clone())? {
-            let mut total_granted_for_user = Uint128::zero();
-            for sch in account_info.schedules {
-                if let Some(end_point) = sch.end_point {
+            // the vesting token is clawed back first, then the rest of the vested assets
+            let asset_infos =
+                iter::once(None).chain(account_info.additional_asset_infos().into_iter().map(Some));
+            for asset_info in asset_infos {
+                let mut total_granted_for_user = Uint128::zero();
+                for sch in asset_schedules(&account_info, asset_info.as_ref()) {
                     total_granted_for_user =
-                        total_granted_for_user.checked_add(end_point.amount)?;
-                } else {
-                    total_granted_for_user =
-                        total_granted_for_user.checked_add(sch.start_point.amount)?;
+                        total_granted_for_user.checked_add(compute_total_amount(sch)?)?;
                 }
-            }
+                // the slashed tokens have already left the contract
+                let released_amount = account_info.spent_amount_of(asset_info.as_ref())?;
 
-            let amount_to_claw_back =
-                total_granted_for_user.checked_sub(account_info.released_amount)?;
+                let amount_to_claw_back = total_granted_for_user.checked_sub(released_amount)?;
 
-            let transfer_msg = vesting_token
-                .with_balance(amount_to_claw_back)
-                .into_msg(&deps.querier, clawback_address.clone())?;
-            response = response.add_submessage(SubMsg::new(transfer_msg));
+                if !amount_to_claw_back.is_zero() {
+                    let transfer_msg = asset_info
+                        .as_ref()
+                        .unwrap_or(&vesting_token)
+                        .with_balance(amount_to_claw_back)
+                        .into_msg(&deps.querier, clawback_address.clone())?;
+                    response = response.add_submessage(SubMsg::new(transfer_msg));
+                }
 
-            vesting_state(config.extensions.historical).update::<_, ContractError>(
+                update_vesting_state(
+                    deps.storage,
+                    config.extensions.historical,
+                    asset_info.as_ref(),
+                    env.block.height,
+                    |s| {
+                        // Here we choose the "forget about everything" strategy. E.g., if we granted a user
+                        // 300 tokens, and they claimed 150 tokens, the vesting state is
+                        // { total_granted: 300, total_released: 150 }.
+                        // If after that we remove the user's vesting account, we set the vesting state to
+                        // { total_granted: 0, total_released: 0 }.
+                        //
+                        // If we decided to set it to { total_granted: 150, total_released: 150 }., the
+                        // .total_released value of the vesting state would not be equal to the sum of the
+                        // .released_amount values of all registered accounts.
+                        let mut state = s.ok_or(ContractError::AmountIsNotAvailable {})?;
+                        state.total_granted =
+                            state.total_granted.checked_sub(total_granted_for_user)?;
+                        state.total_released = state.total_released.checked_sub(released_amount)?;
+                        Ok(state)
+                    },
+                )?;
+            }
+            remove_vesting_info(
                 deps.storage,
+                config.extensions.historical,
+                account_address.clone(),
                 env.block.height,
-                |s| {
-                    // Here we choose the "forget about everything" strategy. E.g., if we granted a user
-                    // 300 tokens, and they claimed 150 tokens, the vesting state is
-                    // { total_granted: 300, total_released: 150 }.
-                    // If after that we remove the user's vesting account, we set the vesting state to
-                    // { total_granted: 0, total_released: 0 }.
-                    //
-                    // If we decided to set it to { total_granted: 150, total_released: 150 }., the
-                    // .total_released value of the vesting state would not be equal to the sum of the
-                    // .released_amount values of all registered accounts.
-                    let mut state = s.ok_or(ContractError::AmountIsNotAvailable {})?;
-                    state.total_granted =
-                        state.total_granted.checked_sub(total_granted_for_user)?;
-                    state.total_released = state
-                        .total_released
-                        .checked_sub(account_info.released_amount)?;
-                    Ok(state)
-                },
             )?;
-            vesting_info.remove(deps.storage, account_address, env.block.height)?;
+            VESTING_TERMINATIONS.remove(deps.storage, account_address.clone());
         }
+        STAKING_FROZEN_ACCOUNTS.remove(deps.storage, account_address);
     }
 
     Ok(response.add_attributes(vec![
@@ -119,3 +170,298 @@ fn remove_vesting_accounts(
         attr("sender", &info.sender),
     ]))
 }
+
+fn truncate_vesting_accounts(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vesting_accounts: Vec<String>,
+    clawback_account: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let vesting_token = get_vesting_token(&config)?;
+
+    let mut response = Response::new();
+
+    let clawback_address = deps.api.addr_validate(&clawback_account)?;
+
+    for vesting_account in vesting_accounts {
+        let account_address = deps.api.addr_validate(&vesting_account)?;
+        assert_not_staked(deps.storage, &deps.querier, &env, &account_address)?;
+
+        let assets_to_claw_back = truncate_vesting_account(
+            deps.storage,
+            config.extensions.historical,
+            account_address.clone(),
+            env.block.time.seconds(),
+            env.block.height,
+        )?;
+        for (asset_info, amount_to_claw_back) in assets_to_claw_back {
+            let transfer_msg = asset_info
+                .as_ref()
+                .unwrap_or(&vesting_token)
+                .with_balance(amount_to_claw_back)
+                .into_msg(&deps.querier, clawback_address.clone())?;
+            response = response.add_submessage(SubMsg::new(transfer_msg));
+        }
+        // nothing is left to vest, so a scheduled termination makes no sense anymore
+        VESTING_TERMINATIONS.remove(deps.storage, account_address.clone());
+        STAKING_FROZEN_ACCOUNTS.remove(deps.storage, account_address);
+    }
+
+    Ok(response.add_attributes(vec![
+        attr("action", "truncate_vesting_accounts"),
+        attr("sender", &info.sender),
+    ]))
+}
+
+fn schedule_vesting_termination(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vesting_account: String,
+    termination_time: u64,
+    clawback_account: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let account_address = deps.api.addr_validate(&vesting_account)?;
+    if termination_time <= env.block.time.seconds() {
+        return Err(ContractError::VestingTerminationTimeError(vesting_account));
+    }
+    // make sure the account exists
+    vesting_info(config.extensions.historical).load(deps.storage, account_address.clone())?;
+
+    VESTING_TERMINATIONS.save(
+        deps.storage,
+        account_address,
+        &VestingTermination {
+            termination_time,
+            clawback_account: deps.api.addr_validate(&clawback_account)?,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "schedule_vesting_termination"),
+        attr("address", vesting_account),
+        attr("termination_time", termination_time.to_string()),
+        attr("clawback_account", clawback_account),
+    ]))
+}
+
+fn cancel_vesting_termination(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    vesting_account: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let account_address = deps.api.addr_validate(&vesting_account)?;
+    VESTING_TERMINATIONS.remove(deps.storage, account_address.clone());
+    STAKING_FROZEN_ACCOUNTS.remove(deps.storage, account_address);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_vesting_termination"),
+        attr("address", vesting_account),
+    ]))
+}
+
+fn claw_back_terminated_accounts(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vesting_accounts: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_token = get_vesting_token(&config)?;
+
+    let mut response = Response::new();
+
+    for vesting_account in vesting_accounts {
+        let account_address = deps.api.addr_validate(&vesting_account)?;
+        let termination =
+            match VESTING_TERMINATIONS.may_load(deps.storage, account_address.clone())? {
+                Some(termination) if termination.termination_time <= env.block.time.seconds() => {
+                    termination
+                }
+                _ => {
+                    return Err(ContractError::VestingTerminationNotReachedError(
+                        vesting_account,
+                    ))
+                }
+            };
+        if info.sender != config.owner && info.sender != termination.clawback_account {
+            return Err(ContractError::Unauthorized {});
+        }
+        assert_not_staked(deps.storage, &deps.querier, &env, &account_address)?;
+
+        let assets_to_claw_back = truncate_vesting_account(
+            deps.storage,
+            config.extensions.historical,
+            account_address.clone(),
+            termination.termination_time,
+            env.block.height,
+        )?;
+        for (asset_info, amount_to_claw_back) in assets_to_claw_back {
+            let transfer_msg = asset_info
+                .as_ref()
+                .unwrap_or(&vesting_token)
+                .with_balance(amount_to_claw_back)
+                .into_msg(&deps.querier, termination.clawback_account.clone())?;
+            response = response.add_submessage(SubMsg::new(transfer_msg));
+        }
+        VESTING_TERMINATIONS.remove(deps.storage, account_address.clone());
+        STAKING_FROZEN_ACCOUNTS.remove(deps.storage, account_address);
+    }
+
+    Ok(response.add_attributes(vec![
+        attr("action", "claw_back_terminated_accounts"),
+        attr("sender", &info.sender),
+    ]))
+}
+
+/// Calculates the amount of tokens of a vesting account that are still vesting at the
+/// `truncation_time` and replaces each of the account's schedules with a schedule holding the
+/// amount it has vested by that time, keeping its asset and voting weight. The released amounts
+/// are kept intact, so the vested but unclaimed tokens stay claimable, and only the total
+/// granted metrics are decreased. Slashed tokens are covered by the vested but unclaimed
+/// tokens first, and the rest of them is deducted from the tokens clawed back.
+/// Returns the non-zero amounts of tokens to claw back per asset, where `None` stands for the
+/// vesting token.
+fn truncate_vesting_account(
+    storage: &mut dyn Storage,
+    historical: bool,
+    address: Addr,
+    truncation_time: u64,
+    height: u64,
+) -> Result<Vec<(Option<AssetInfo>, Uint128)>, ContractError> {
+    let vesting_info = vesting_info(historical);
+    let account_info = match vesting_info.may_load(storage, address.clone())? {
+        Some(account_info) => account_info,
+        None => return Ok(vec![]),
+    };
+
+    let mut schedules = vec![];
+    let mut assets_to_claw_back = vec![];
+    let mut slashed_amount = account_info.slashed_amount;
+    let mut truncated = false;
+    let asset_infos =
+        iter::once(None).chain(account_info.additional_asset_infos().into_iter().map(Some));
+    for asset_info in asset_infos {
+        let mut total_granted_for_user = Uint128::zero();
+        let mut total_vested_for_user = Uint128::zero();
+        for sch in asset_schedules(&account_info, asset_info.as_ref()) {
+            let vested_amount = compute_vested_amount(truncation_time, sch)?;
+            total_granted_for_user =
+                total_granted_for_user.checked_add(compute_total_amount(sch)?)?;
+            total_vested_for_user = total_vested_for_user.checked_add(vested_amount)?;
+
+            // the voting weight of each schedule is kept for the amount it has vested
+            if !vested_amount.is_zero() {
+                schedules.push(VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: truncation_time,
+                        amount: vested_amount,
+                    },
+                    end_point: None,
+                    cliff: None,
+                    intermediate_points: None,
+                    periodic: None,
+                    asset_info: sch.asset_info.clone(),
+                    voting_weight: sch.voting_weight,
+                });
+            }
+        }
+
+        let granted_to_claw_back = total_granted_for_user.checked_sub(total_vested_for_user)?;
+        // the slashed tokens are paid from the vested but unclaimed tokens first, the rest of
+        // them is missing from the tokens clawed back
+        let mut unvested_slashed_amount = Uint128::zero();
+        if asset_info.is_none() {
+            let unclaimed_amount =
+                total_vested_for_user.saturating_sub(account_info.released_amount);
+            unvested_slashed_amount = account_info.slashed_amount.saturating_sub(unclaimed_amount);
+            slashed_amount = account_info.slashed_amount - unvested_slashed_amount;
+        }
+        let amount_to_claw_back = granted_to_claw_back.checked_sub(unvested_slashed_amount)?;
+        if !granted_to_claw_back.is_zero() {
+            update_vesting_state(storage, historical, asset_info.as_ref(), height, |s| {
+                let mut state = s.ok_or(ContractError::AmountIsNotAvailable {})?;
+                state.total_granted = state.total_granted.checked_sub(granted_to_claw_back)?;
+                state.total_released = state.total_released.checked_sub(unvested_slashed_amount)?;
+                Ok(state)
+            })?;
+            truncated = true;
+        }
+        if !amount_to_claw_back.is_zero() {
+            assets_to_claw_back.push((asset_info, amount_to_claw_back));
+        }
+    }
+
+    if truncated {
+        save_vesting_info(
+            storage,
+            historical,
+            address,
+            &VestingInfo {
+                schedules,
+                released_amount: account_info.released_amount,
+                released_assets: account_info.released_assets,
+                slashed_amount,
+            },
+            height,
+        )?;
+    }
+
+    Ok(assets_to_claw_back)
+}
+
+/// Returns the time the vesting of a specific account should be computed for. The vesting
+/// of the terminated accounts stops at their termination time.
+pub(crate) fn vesting_time(
+    storage: &dyn Storage,
+    address: Addr,
+    current_time: u64,
+) -> StdResult<u64> {
+    Ok(VESTING_TERMINATIONS
+        .may_load(storage, address)?
+        .map_or(current_time, |termination| {
+            termination.termination_time.min(current_time)
+        }))
+}
+
+/// Returns a list of scheduled vesting terminations.
+///
+/// * **start_after** address from which to start reading terminations.
+///
+/// * **limit** amount of terminations to return.
+fn query_pending_terminations(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<VestingTerminationResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = addr_opt_validate(deps.api, &start_after)?.map(Bound::exclusive);
+
+    VESTING_TERMINATIONS
+        .range(deps.storage, start_after, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, termination) = item?;
+            Ok(VestingTerminationResponse {
+                address,
+                termination,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,120 @@
+use crate::error::{ext_unsupported_err, ContractError};
+use crate::msg::{ExecuteMsgPausable, QueryMsgPausable};
+use crate::state::{CONFIG, GUARDIAN, PAUSED};
+use crate::types::IsPausedResponse;
+use astroport::asset::addr_opt_validate;
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Storage,
+};
+
+/// Contains the pausable extension check and routing of the message.
+pub(crate) fn handle_execute_pausable_msg(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsgPausable,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.extensions.pausable {
+        return Err(ext_unsupported_err("pausable").into());
+    }
+
+    match msg {
+        ExecuteMsgPausable::Pause {} => pause(deps, env, info),
+        ExecuteMsgPausable::Resume {} => resume(deps, env, info),
+        ExecuteMsgPausable::SetGuardian { guardian } => set_guardian(deps, env, info, guardian),
+    }
+}
+
+/// Contains the pausable extension check and routing of the message.
+pub(crate) fn handle_query_pausable_msg(
+    deps: Deps,
+    _env: Env,
+    msg: QueryMsgPausable,
+) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.extensions.pausable {
+        return Err(ext_unsupported_err("pausable"));
+    }
+
+    match msg {
+        QueryMsgPausable::IsPaused {} => to_json_binary(&query_is_paused(deps)?),
+        QueryMsgPausable::Guardian {} => to_json_binary(&GUARDIAN.may_load(deps.storage)?),
+    }
+}
+
+/// Returns an error if the contract is paused. Contracts without the pausable extension are
+/// never paused.
+pub fn assert_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if PAUSED.may_load(storage)?.unwrap_or(false) {
+        return Err(ContractError::Paused {});
+    }
+    Ok(())
+}
+
+/// Checks that the sender is the owner or the guardian of the contract.
+fn assert_owner_or_guardian(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if *sender != config.owner && GUARDIAN.may_load(deps.storage)?.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+/// Freezes claims, registrations, transfers, staking and liquidity migrations. Queries and
+/// the owner's management messages are still available.
+fn pause(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_owner_or_guardian(deps.as_ref(), &info.sender)?;
+
+    PAUSED.save(deps.storage, &true)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "pause"), attr("paused", "true")]))
+}
+
+/// Lifts the freeze set by the `Pause` message.
+fn resume(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_owner_or_guardian(deps.as_ref(), &info.sender)?;
+
+    let is_paused = PAUSED.may_load(deps.storage)?.unwrap_or(false);
+    if !is_paused {
+        return Err(ContractError::NotPaused {});
+    }
+
+    PAUSED.save(deps.storage, &false)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "resume"), attr("paused", "false")]))
+}
+
+/// Sets or removes the guardian that can pause and resume the contract besides the owner.
+///
+/// * **guardian** the new guardian. `None` removes the current guardian.
+fn set_guardian(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let guardian = addr_opt_validate(deps.api, &guardian)?;
+    match &guardian {
+        Some(guardian) => GUARDIAN.save(deps.storage, guardian)?,
+        None => GUARDIAN.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_guardian"),
+        attr(
+            "guardian",
+            guardian.map_or_else(|| String::from("none"), String::from),
+        ),
+    ]))
+}
+
+/// Returns whether the contract is paused.
+fn query_is_paused(deps: Deps) -> StdResult<IsPausedResponse> {
+    let is_paused = PAUSED.may_load(deps.storage)?.unwrap_or(false);
+    Ok(IsPausedResponse { is_paused })
+}
@@ -0,0 +1,824 @@
+use crate::error::{ext_unsupported_err, ContractError};
+use crate::ext_pausable::assert_not_paused;
+use crate::handlers::{
+    asset_schedules, compute_granted_amount, compute_vested_amount, get_vesting_token,
+};
+use crate::msg::{ExecuteMsgStaking, QueryMsgStaking};
+use crate::state::{
+    save_vesting_info, update_vesting_state, vesting_info, CONFIG, STAKING_DELEGATIONS,
+    STAKING_FROZEN_ACCOUNTS, STAKING_REWARDS, STAKING_UNBONDINGS, STAKING_VALIDATORS,
+    UNBONDING_PERIOD, VESTING_TERMINATIONS,
+};
+use crate::types::{
+    StakingDelegation, StakingDelegationResponse, StakingInfoResponse, StakingUnbonding,
+    StakingValidator,
+};
+use astroport::asset::native_asset_info;
+use cosmwasm_std::{
+    attr, coin, coins, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut,
+    DistributionMsg, Env, MessageInfo, Order, QuerierWrapper, Response, StakingMsg, StdError,
+    StdResult, Storage, Uint128,
+};
+
+/// Contains the staking extension check and routing of the message.
+pub(crate) fn handle_execute_staking_msg(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsgStaking,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.extensions.staking {
+        return Err(ext_unsupported_err("staking").into());
+    }
+    assert_not_paused(deps.storage)?;
+
+    match msg {
+        ExecuteMsgStaking::Delegate { validator, amount } => {
+            delegate(deps, env, info, validator, amount)
+        }
+        ExecuteMsgStaking::Redelegate {
+            src_validator,
+            dst_validator,
+            amount,
+        } => redelegate(deps, env, info, src_validator, dst_validator, amount),
+        ExecuteMsgStaking::Undelegate { validator, amount } => {
+            undelegate(deps, env, info, validator, amount)
+        }
+        ExecuteMsgStaking::ClaimRewards {} => claim_rewards(deps, env, info),
+        ExecuteMsgStaking::ForceUndelegate { vesting_account } => {
+            force_undelegate(deps, env, info, vesting_account)
+        }
+    }
+}
+
+/// Contains the staking extension check and routing of the message.
+pub(crate) fn handle_query_staking_msg(
+    deps: Deps,
+    env: Env,
+    msg: QueryMsgStaking,
+) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.extensions.staking {
+        return Err(ext_unsupported_err("staking"));
+    }
+
+    match msg {
+        QueryMsgStaking::StakingInfo { address } => {
+            to_json_binary(&query_staking_info(deps, env, address)?)
+        }
+    }
+}
+
+/// Delegates unclaimed vesting tokens of the sender to a validator. Both the vested and the
+/// still vesting tokens can be delegated, except for the tokens which vest after a scheduled
+/// termination of the account.
+///
+/// * **validator** validator to delegate the tokens to.
+///
+/// * **amount** amount of tokens to delegate.
+fn delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    validator: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let denom = staking_denom(deps.as_ref())?;
+    assert_validator_exists(deps.as_ref(), &validator)?;
+
+    if STAKING_FROZEN_ACCOUNTS.has(deps.storage, info.sender.clone()) {
+        return Err(ContractError::VestingAccountStakingFrozen(
+            info.sender.to_string(),
+        ));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let account_info = vesting_info(config.extensions.historical)
+        .may_load(deps.storage, info.sender.clone())?
+        .ok_or(ContractError::AmountIsNotAvailable {})?;
+    // the tokens vesting after a scheduled termination are going to be clawed back
+    let granted_amount = match VESTING_TERMINATIONS.may_load(deps.storage, info.sender.clone())? {
+        Some(termination) => {
+            let mut vested_amount = Uint128::zero();
+            for sch in asset_schedules(&account_info, None) {
+                vested_amount = vested_amount
+                    .checked_add(compute_vested_amount(termination.termination_time, sch)?)?;
+            }
+            vested_amount
+        }
+        None => compute_granted_amount(&account_info, None)?,
+    };
+    let liquid_amount = granted_amount
+        .saturating_sub(account_info.spent_amount_of(None)?)
+        .saturating_sub(staked_amount(
+            deps.storage,
+            info.sender.clone(),
+            env.block.time.seconds(),
+        )?);
+    if amount.is_zero() || amount > liquid_amount {
+        return Err(ContractError::AmountIsNotAvailable {});
+    }
+
+    let mut messages = vec![];
+    let mut validator_state = update_validator_rewards(
+        deps.storage,
+        &deps.querier,
+        &env.contract.address,
+        &validator,
+        &denom,
+        &mut messages,
+    )?;
+    let mut delegation = distribute_rewards(
+        deps.storage,
+        &info.sender,
+        &validator,
+        &validator_state,
+        env.block.height,
+    )?;
+    delegation.amount = delegation.amount.checked_add(amount)?;
+    validator_state.amount = validator_state.amount.checked_add(amount)?;
+    save_delegation(
+        deps.storage,
+        &info.sender,
+        &validator,
+        &delegation,
+        &validator_state,
+    )?;
+
+    messages.push(CosmosMsg::Staking(StakingMsg::Delegate {
+        validator: validator.clone(),
+        amount: coin(amount.u128(), &denom),
+    }));
+    messages.extend(take_rewards(deps.storage, &info.sender, &denom)?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "delegate"),
+        attr("address", &info.sender),
+        attr("validator", validator),
+        attr("amount", amount),
+    ]))
+}
+
+/// Moves delegated tokens of the sender from one validator to another.
+///
+/// * **src_validator** validator to move the tokens from.
+///
+/// * **dst_validator** validator to move the tokens to.
+///
+/// * **amount** amount of tokens to move.
+fn redelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    src_validator: String,
+    dst_validator: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let denom = staking_denom(deps.as_ref())?;
+    if src_validator == dst_validator {
+        return Err(StdError::generic_err("Can't redelegate to the same validator").into());
+    }
+    assert_validator_exists(deps.as_ref(), &dst_validator)?;
+
+    let mut messages = vec![];
+    let mut src_validator_state = update_validator_rewards(
+        deps.storage,
+        &deps.querier,
+        &env.contract.address,
+        &src_validator,
+        &denom,
+        &mut messages,
+    )?;
+    let mut src_delegation = distribute_rewards(
+        deps.storage,
+        &info.sender,
+        &src_validator,
+        &src_validator_state,
+        env.block.height,
+    )?;
+    if amount.is_zero() || amount > src_delegation.amount {
+        return Err(ContractError::AmountIsNotAvailable {});
+    }
+    src_delegation.amount -= amount;
+    src_validator_state.amount -= amount;
+    save_delegation(
+        deps.storage,
+        &info.sender,
+        &src_validator,
+        &src_delegation,
+        &src_validator_state,
+    )?;
+
+    let mut dst_validator_state = update_validator_rewards(
+        deps.storage,
+        &deps.querier,
+        &env.contract.address,
+        &dst_validator,
+        &denom,
+        &mut messages,
+    )?;
+    let mut dst_delegation = distribute_rewards(
+        deps.storage,
+        &info.sender,
+        &dst_validator,
+        &dst_validator_state,
+        env.block.height,
+    )?;
+    dst_delegation.amount = dst_delegation.amount.checked_add(amount)?;
+    dst_validator_state.amount = dst_validator_state.amount.checked_add(amount)?;
+    save_delegation(
+        deps.storage,
+        &info.sender,
+        &dst_validator,
+        &dst_delegation,
+        &dst_validator_state,
+    )?;
+
+    messages.push(CosmosMsg::Staking(StakingMsg::Redelegate {
+        src_validator: src_validator.clone(),
+        dst_validator: dst_validator.clone(),
+        amount: coin(amount.u128(), &denom),
+    }));
+    messages.extend(take_rewards(deps.storage, &info.sender, &denom)?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "redelegate"),
+        attr("address", &info.sender),
+        attr("src_validator", src_validator),
+        attr("dst_validator", dst_validator),
+        attr("amount", amount),
+    ]))
+}
+
+/// Undelegates tokens of the sender from a validator. The tokens stay locked until the unbonding
+/// period ends.
+///
+/// * **validator** validator to undelegate the tokens from.
+///
+/// * **amount** amount of tokens to undelegate.
+fn undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    validator: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let denom = staking_denom(deps.as_ref())?;
+
+    let mut messages = vec![];
+    undelegate_tokens(
+        deps.storage,
+        &deps.querier,
+        &env,
+        &info.sender,
+        &validator,
+        Some(amount),
+        &denom,
+        &mut messages,
+    )?;
+    messages.extend(take_rewards(deps.storage, &info.sender, &denom)?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "undelegate"),
+        attr("address", &info.sender),
+        attr("validator", validator),
+        attr("amount", amount),
+    ]))
+}
+
+/// Undelegates all the tokens of a vesting account and forbids the account to delegate again.
+/// Once the unbonding period ends, nothing is staked anymore, so the account's tokens can be
+/// clawed back by the owner.
+///
+/// * **vesting_account** vesting account whose tokens to undelegate.
+fn force_undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    vesting_account: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let denom = staking_denom(deps.as_ref())?;
+    let address = deps.api.addr_validate(&vesting_account)?;
+
+    let validators = STAKING_DELEGATIONS
+        .prefix(address.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut messages = vec![];
+    let mut undelegated_amount = Uint128::zero();
+    for validator in validators {
+        let amount = undelegate_tokens(
+            deps.storage,
+            &deps.querier,
+            &env,
+            &address,
+            &validator,
+            None,
+            &denom,
+            &mut messages,
+        )?;
+        undelegated_amount = undelegated_amount.checked_add(amount)?;
+    }
+    STAKING_FROZEN_ACCOUNTS.save(deps.storage, address.clone(), &true)?;
+    messages.extend(take_rewards(deps.storage, &address, &denom)?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "force_undelegate"),
+        attr("address", address),
+        attr("amount", undelegated_amount),
+    ]))
+}
+
+/// Withdraws the staking rewards of all the sender's delegations and sends the sender's share
+/// of them to the sender.
+fn claim_rewards(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let denom = staking_denom(deps.as_ref())?;
+
+    let validators = STAKING_DELEGATIONS
+        .prefix(info.sender.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut messages = vec![];
+    for validator in validators {
+        let validator_state = update_validator_rewards(
+            deps.storage,
+            &deps.querier,
+            &env.contract.address,
+            &validator,
+            &denom,
+            &mut messages,
+        )?;
+        let delegation = distribute_rewards(
+            deps.storage,
+            &info.sender,
+            &validator,
+            &validator_state,
+            env.block.height,
+        )?;
+        save_delegation(
+            deps.storage,
+            &info.sender,
+            &validator,
+            &delegation,
+            &validator_state,
+        )?;
+    }
+    let rewards = STAKING_REWARDS
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    messages.extend(take_rewards(deps.storage, &info.sender, &denom)?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "claim_rewards"),
+        attr("address", &info.sender),
+        attr("rewards", rewards),
+    ]))
+}
+
+/// Returns the staking position of a vesting account. The pending rewards include the rewards
+/// accrued by the contract's delegations and not yet withdrawn.
+///
+/// * **address** vesting account for which to return the staking position.
+fn query_staking_info(deps: Deps, env: Env, address: String) -> StdResult<StakingInfoResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let denom = deps.querier.query_bonded_denom()?;
+
+    let mut pending_rewards = STAKING_REWARDS
+        .may_load(deps.storage, address.clone())?
+        .unwrap_or_default();
+    let mut delegations = vec![];
+    for item in STAKING_DELEGATIONS.prefix(address.clone()).range(
+        deps.storage,
+        None,
+        None,
+        Order::Ascending,
+    ) {
+        let (validator, mut delegation) = item?;
+        let mut validator_state = STAKING_VALIDATORS.load(deps.storage, validator.clone())?;
+        sync_validator_state(
+            &deps.querier,
+            &env.contract.address,
+            &validator,
+            &denom,
+            &mut validator_state,
+        )?;
+        let (rewards, _) = update_delegation(&mut delegation, &validator_state)?;
+        pending_rewards = pending_rewards.checked_add(rewards)?;
+        delegations.push(StakingDelegationResponse {
+            validator,
+            amount: delegation.amount,
+        });
+    }
+
+    let current_time = env.block.time.seconds();
+    let unbondings = STAKING_UNBONDINGS
+        .may_load(deps.storage, address)?
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|unbonding| unbonding.release_time > current_time)
+        .collect();
+
+    Ok(StakingInfoResponse {
+        delegations,
+        unbondings,
+        pending_rewards,
+    })
+}
+
+/// Returns the amount of tokens of a vesting account that are delegated or still unbonding.
+/// These tokens are not in the contract's balance, so they can't be claimed.
+pub(crate) fn staked_amount(
+    storage: &dyn Storage,
+    address: Addr,
+    current_time: u64,
+) -> StdResult<Uint128> {
+    let mut staked_amount = Uint128::zero();
+    for item in
+        STAKING_DELEGATIONS
+            .prefix(address.clone())
+            .range(storage, None, None, Order::Ascending)
+    {
+        let (_, delegation) = item?;
+        staked_amount = staked_amount.checked_add(delegation.amount)?;
+    }
+    for unbonding in STAKING_UNBONDINGS
+        .may_load(storage, address)?
+        .unwrap_or_default()
+    {
+        if unbonding.release_time > current_time {
+            staked_amount = staked_amount.checked_add(unbonding.amount)?;
+        }
+    }
+
+    Ok(staked_amount)
+}
+
+/// Settles the unbondings of a vesting account and returns an error if the account has delegated
+/// or still unbonding tokens, which can't be moved or clawed back.
+pub(crate) fn assert_not_staked(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    settle_unbondings(storage, querier, env, address)?;
+    if !staked_amount(storage, address.clone(), env.block.time.seconds())?.is_zero() {
+        return Err(ContractError::VestingAccountStakedError(
+            address.to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns an error if a validator is not found among the chain's validators.
+fn assert_validator_exists(deps: Deps, validator: &str) -> Result<(), ContractError> {
+    if !deps
+        .querier
+        .query_all_validators()?
+        .iter()
+        .any(|v| v.address == validator)
+    {
+        return Err(ContractError::ValidatorNotFound(validator.to_string()));
+    }
+    Ok(())
+}
+
+/// Returns the staking denom if it is the vesting token of the contract.
+fn staking_denom(deps: Deps) -> Result<String, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_token = get_vesting_token(&config)?;
+    let denom = deps.querier.query_bonded_denom()?;
+    if vesting_token != native_asset_info(denom.clone()) {
+        return Err(ContractError::VestingTokenNotStakeable {});
+    }
+    Ok(denom)
+}
+
+/// Applies the slashings and the staking rewards of the contract's delegation to a validator
+/// since the last update to the state of the validator. Returns the amount of rewards accrued
+/// and not yet withdrawn.
+fn sync_validator_state(
+    querier: &QuerierWrapper,
+    contract: &Addr,
+    validator: &str,
+    denom: &str,
+    validator_state: &mut StakingValidator,
+) -> StdResult<Uint128> {
+    let delegation = querier.query_delegation(contract, validator)?;
+    let delegated_amount = delegation
+        .as_ref()
+        .map_or(Uint128::zero(), |delegation| delegation.amount.amount);
+    apply_validator_slashing(delegated_amount, validator_state)?;
+
+    let accrued_rewards = delegation
+        .and_then(|delegation| {
+            delegation
+                .accumulated_rewards
+                .into_iter()
+                .find(|reward| reward.denom == denom)
+        })
+        .map_or(Uint128::zero(), |reward| reward.amount);
+    if !accrued_rewards.is_zero() && !validator_state.amount.is_zero() {
+        validator_state.reward_index = validator_state
+            .reward_index
+            .checked_add(Decimal::from_ratio(accrued_rewards, validator_state.amount))?;
+    }
+
+    Ok(accrued_rewards)
+}
+
+/// Applies the slashings of the contract's delegation to a validator to the state of the
+/// validator. A slashing reduces the delegations and the unbondings of all the vesting accounts
+/// pro rata.
+fn apply_validator_slashing(
+    delegated_amount: Uint128,
+    validator_state: &mut StakingValidator,
+) -> StdResult<()> {
+    if delegated_amount < validator_state.amount {
+        validator_state.slash_index =
+            validator_state
+                .slash_index
+                .checked_mul(Decimal::from_ratio(
+                    delegated_amount,
+                    validator_state.amount,
+                ))?;
+        validator_state.amount = delegated_amount;
+    }
+    Ok(())
+}
+
+/// Applies the slashings and the staking rewards of the contract's delegation to a validator to
+/// the state of the validator, and appends the message withdrawing the rewards to `messages`.
+fn update_validator_rewards(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    contract: &Addr,
+    validator: &str,
+    denom: &str,
+    messages: &mut Vec<CosmosMsg>,
+) -> StdResult<StakingValidator> {
+    let mut validator_state = STAKING_VALIDATORS
+        .may_load(storage, validator.to_string())?
+        .unwrap_or_default();
+    if validator_state.amount.is_zero() {
+        return Ok(validator_state);
+    }
+
+    let accrued_rewards =
+        sync_validator_state(querier, contract, validator, denom, &mut validator_state)?;
+    if !accrued_rewards.is_zero() && !validator_state.amount.is_zero() {
+        messages.push(CosmosMsg::Distribution(
+            DistributionMsg::WithdrawDelegatorReward {
+                validator: validator.to_string(),
+            },
+        ));
+    }
+    STAKING_VALIDATORS.save(storage, validator.to_string(), &validator_state)?;
+
+    Ok(validator_state)
+}
+
+/// Applies the slashings and the rewards of a validator since the last update of a delegation
+/// to it. Returns the rewards and the slashed tokens of the delegation.
+fn update_delegation(
+    delegation: &mut StakingDelegation,
+    validator_state: &StakingValidator,
+) -> StdResult<(Uint128, Uint128)> {
+    let mut slashed_amount = Uint128::zero();
+    if delegation.slash_index != validator_state.slash_index && !delegation.slash_index.is_zero() {
+        let amount = delegation.amount.multiply_ratio(
+            validator_state.slash_index.atomics(),
+            delegation.slash_index.atomics(),
+        );
+        slashed_amount = delegation.amount.saturating_sub(amount);
+        delegation.amount -= slashed_amount;
+    }
+
+    let rewards = delegation.amount
+        * validator_state
+            .reward_index
+            .checked_sub(delegation.reward_index)?;
+    delegation.reward_index = validator_state.reward_index;
+    delegation.slash_index = validator_state.slash_index;
+
+    Ok((rewards, slashed_amount))
+}
+
+/// Moves the rewards of a vesting account's delegation accrued since the last distribution to
+/// the pending rewards of the account, and records the slashed tokens of the delegation, since
+/// they have left the contract. Returns the updated delegation.
+fn distribute_rewards(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    validator: &str,
+    validator_state: &StakingValidator,
+    height: u64,
+) -> Result<StakingDelegation, ContractError> {
+    let mut delegation = STAKING_DELEGATIONS
+        .may_load(storage, (address.clone(), validator.to_string()))?
+        .unwrap_or(StakingDelegation {
+            amount: Uint128::zero(),
+            reward_index: validator_state.reward_index,
+            slash_index: validator_state.slash_index,
+        });
+
+    let (rewards, slashed_amount) = update_delegation(&mut delegation, validator_state)?;
+    if !rewards.is_zero() {
+        STAKING_REWARDS.update(storage, address.clone(), |pending| -> StdResult<_> {
+            Ok(pending.unwrap_or_default().checked_add(rewards)?)
+        })?;
+    }
+    if !slashed_amount.is_zero() {
+        record_slashed_tokens(storage, address, slashed_amount, height)?;
+    }
+
+    Ok(delegation)
+}
+
+/// Applies the slashings of the validators since the last update of the unbondings of a vesting
+/// account, and removes the unbondings which have ended. Should be called before the tokens of
+/// the account are claimed or clawed back, so that the slashed tokens are never sent out. The
+/// slashings are observed through the contract's delegations, so they are only applied while
+/// the contract still has tokens delegated to the validator.
+pub(crate) fn settle_unbondings(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    let unbondings = match STAKING_UNBONDINGS.may_load(storage, address.clone())? {
+        Some(unbondings) => unbondings,
+        None => return Ok(()),
+    };
+
+    let current_time = env.block.time.seconds();
+    let mut slashed_amount = Uint128::zero();
+    let mut pending_unbondings = vec![];
+    for mut unbonding in unbondings {
+        let mut validator_state = STAKING_VALIDATORS
+            .may_load(storage, unbonding.validator.clone())?
+            .unwrap_or_default();
+        // only the slashings are synced, the rewards are left for the next reward distribution
+        if !validator_state.amount.is_zero() {
+            let delegated_amount = querier
+                .query_delegation(&env.contract.address, &unbonding.validator)?
+                .map_or(Uint128::zero(), |delegation| delegation.amount.amount);
+            apply_validator_slashing(delegated_amount, &mut validator_state)?;
+            STAKING_VALIDATORS.save(storage, unbonding.validator.clone(), &validator_state)?;
+        }
+
+        if unbonding.slash_index != validator_state.slash_index && !unbonding.slash_index.is_zero()
+        {
+            let amount = unbonding.amount.multiply_ratio(
+                validator_state.slash_index.atomics(),
+                unbonding.slash_index.atomics(),
+            );
+            slashed_amount = slashed_amount.checked_add(unbonding.amount.saturating_sub(amount))?;
+            unbonding.amount = amount.min(unbonding.amount);
+            unbonding.slash_index = validator_state.slash_index;
+        }
+        if unbonding.release_time > current_time {
+            pending_unbondings.push(unbonding);
+        }
+    }
+
+    if pending_unbondings.is_empty() {
+        STAKING_UNBONDINGS.remove(storage, address.clone());
+    } else {
+        STAKING_UNBONDINGS.save(storage, address.clone(), &pending_unbondings)?;
+    }
+    if !slashed_amount.is_zero() {
+        record_slashed_tokens(storage, address, slashed_amount, env.block.height)?;
+    }
+
+    Ok(())
+}
+
+/// Adds the slashed tokens of a vesting account to its slashed amount, so that they aren't
+/// counted as claimable or clawable anymore.
+fn record_slashed_tokens(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    amount: Uint128,
+    height: u64,
+) -> Result<(), ContractError> {
+    let config = CONFIG.load(storage)?;
+    let historical = config.extensions.historical;
+    let mut account_info = vesting_info(historical).load(storage, address.clone())?;
+    account_info.slashed_amount = account_info.slashed_amount.checked_add(amount)?;
+    save_vesting_info(storage, historical, address.clone(), &account_info, height)?;
+    update_vesting_state(storage, historical, None, height, |s| {
+        let mut state = s.ok_or(ContractError::AmountIsNotAvailable {})?;
+        state.total_released = state.total_released.checked_add(amount)?;
+        Ok(state)
+    })?;
+
+    Ok(())
+}
+
+/// Saves a delegation of a vesting account along with the delegations of all the vesting accounts
+/// to the validator. Empty delegations are removed.
+fn save_delegation(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    validator: &str,
+    delegation: &StakingDelegation,
+    validator_state: &StakingValidator,
+) -> StdResult<()> {
+    let key = (address.clone(), validator.to_string());
+    if delegation.amount.is_zero() {
+        STAKING_DELEGATIONS.remove(storage, key);
+    } else {
+        STAKING_DELEGATIONS.save(storage, key, delegation)?;
+    }
+    STAKING_VALIDATORS.save(storage, validator.to_string(), validator_state)
+}
+
+/// Undelegates tokens of a vesting account from a validator and saves the unbonding, appending
+/// the messages to `messages`. `None` undelegates all the tokens the account has delegated to the
+/// validator. Returns the undelegated amount.
+#[allow(clippy::too_many_arguments)]
+fn undelegate_tokens(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    address: &Addr,
+    validator: &str,
+    amount: Option<Uint128>,
+    denom: &str,
+    messages: &mut Vec<CosmosMsg>,
+) -> Result<Uint128, ContractError> {
+    let mut validator_state = update_validator_rewards(
+        storage,
+        querier,
+        &env.contract.address,
+        validator,
+        denom,
+        messages,
+    )?;
+    let mut delegation = distribute_rewards(
+        storage,
+        address,
+        validator,
+        &validator_state,
+        env.block.height,
+    )?;
+    let amount = match amount {
+        Some(amount) if amount.is_zero() || amount > delegation.amount => {
+            return Err(ContractError::AmountIsNotAvailable {})
+        }
+        Some(amount) => amount,
+        None => delegation.amount,
+    };
+    delegation.amount -= amount;
+    validator_state.amount = validator_state.amount.saturating_sub(amount);
+    save_delegation(storage, address, validator, &delegation, &validator_state)?;
+    if amount.is_zero() {
+        return Ok(amount);
+    }
+
+    // the ended unbondings are kept until they are settled
+    let current_time = env.block.time.seconds();
+    let mut unbondings = STAKING_UNBONDINGS
+        .may_load(storage, address.clone())?
+        .unwrap_or_default();
+    unbondings.push(StakingUnbonding {
+        amount,
+        release_time: current_time + UNBONDING_PERIOD.load(storage)?,
+        validator: validator.to_string(),
+        slash_index: validator_state.slash_index,
+    });
+    STAKING_UNBONDINGS.save(storage, address.clone(), &unbondings)?;
+
+    messages.push(CosmosMsg::Staking(StakingMsg::Undelegate {
+        validator: validator.to_string(),
+        amount: coin(amount.u128(), denom),
+    }));
+
+    Ok(amount)
+}
+
+/// Returns the message sending the pending staking rewards of a vesting account to it, if any.
+fn take_rewards(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    denom: &str,
+) -> StdResult<Option<CosmosMsg>> {
+    let rewards = STAKING_REWARDS
+        .may_load(storage, address.clone())?
+        .unwrap_or_default();
+    if rewards.is_zero() {
+        return Ok(None);
+    }
+    STAKING_REWARDS.remove(storage, address.clone());
+
+    Ok(Some(CosmosMsg::Bank(BankMsg::Send {
+        to_address: address.to_string(),
+        amount: coins(rewards.u128(), denom),
+    })))
+}
@@ -0,0 +1,171 @@
+use crate::error::ext_unsupported_err;
+use crate::ext_historical::compute_unclaimed_amount;
+use crate::handlers::{asset_schedules, compute_total_amount};
+use crate::state::{vesting_info, vesting_state, weighted_voting_state, CONFIG, DAO};
+use crate::types::{
+    InfoResponse, TotalPowerAtHeightResponse, VestingInfo, VotingPowerAtHeightResponse,
+};
+use cosmwasm_std::{Addr, Decimal, Deps, Env, StdResult, Storage, Uint128};
+use cw2::get_contract_version;
+
+/// Contains the voting extension check.
+fn check_voting_extension(deps: Deps) -> StdResult<()> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.extensions.voting {
+        return Err(ext_unsupported_err("voting"));
+    }
+    Ok(())
+}
+
+/// Returns the voting power of a specific address at certain height.
+///
+/// * **address** address for which to return the voting power.
+///
+/// * **height** the height we are querying the voting power for. Defaults to the current height.
+pub(crate) fn query_voting_power_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    height: Option<u64>,
+) -> StdResult<VotingPowerAtHeightResponse> {
+    check_voting_extension(deps)?;
+    let address = deps.api.addr_validate(&address)?;
+    let height = height.unwrap_or(env.block.height);
+
+    let config = CONFIG.load(deps.storage)?;
+    let power = match vesting_info(config.extensions.historical).may_load_at_height(
+        deps.storage,
+        address,
+        height,
+    )? {
+        Some(info) => compute_voting_power(&info)?,
+        None => Uint128::zero(),
+    };
+
+    Ok(VotingPowerAtHeightResponse { power, height })
+}
+
+/// Returns the total voting power at certain height.
+///
+/// * **height** the height we are querying the total voting power for. Defaults to the current height.
+pub(crate) fn query_total_power_at_height(
+    deps: Deps,
+    env: Env,
+    height: Option<u64>,
+) -> StdResult<TotalPowerAtHeightResponse> {
+    check_voting_extension(deps)?;
+    let height = height.unwrap_or(env.block.height);
+
+    let config = CONFIG.load(deps.storage)?;
+    let state = vesting_state(config.extensions.historical)
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+    let weighted_state = weighted_voting_state(config.extensions.historical)
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+
+    // the unclaimed tokens of the accounts with weighted schedules are replaced with their power
+    let power = state
+        .total_granted
+        .checked_sub(state.total_released)?
+        .checked_sub(weighted_state.unclaimed_amount)?
+        .checked_add(weighted_state.voting_power)?;
+
+    Ok(TotalPowerAtHeightResponse { power, height })
+}
+
+/// Returns the name and the version of the contract.
+pub(crate) fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    check_voting_extension(deps)?;
+    let info = get_contract_version(deps.storage)?;
+    Ok(InfoResponse { info })
+}
+
+/// Returns the address of the DAO the contract provides voting power to.
+pub(crate) fn query_dao(deps: Deps) -> StdResult<Addr> {
+    check_voting_extension(deps)?;
+    DAO.load(deps.storage)
+}
+
+/// Computes the voting power of a vesting account. Without weighted schedules, the voting power
+/// equals the amount of unclaimed vesting tokens.
+///
+/// * **vesting_info** vesting schedules for which to compute the voting power.
+fn compute_voting_power(vesting_info: &VestingInfo) -> StdResult<Uint128> {
+    match compute_weighted_voting_power(vesting_info)? {
+        Some((_, voting_power)) => Ok(voting_power),
+        None => compute_unclaimed_amount(vesting_info),
+    }
+}
+
+/// Computes the amount of unclaimed vesting tokens and the voting power of a vesting account
+/// with weighted schedules. The claimed tokens reduce the power of all the schedules
+/// proportionally to their amounts. Returns `None` if none of the schedules is weighted.
+///
+/// * **vesting_info** vesting schedules for which to compute the voting power.
+fn compute_weighted_voting_power(
+    vesting_info: &VestingInfo,
+) -> StdResult<Option<(Uint128, Uint128)>> {
+    let mut weighted = false;
+    let mut total_amount = Uint128::zero();
+    let mut total_power = Uint128::zero();
+    for sch in asset_schedules(vesting_info, None) {
+        let amount = compute_total_amount(sch)?;
+        let weight = match sch.voting_weight {
+            Some(weight) => {
+                weighted = true;
+                weight
+            }
+            None => Decimal::one(),
+        };
+        total_amount = total_amount.checked_add(amount)?;
+        total_power = total_power.checked_add(amount * weight)?;
+    }
+    if !weighted {
+        return Ok(None);
+    }
+
+    let unclaimed_amount = total_amount.checked_sub(vesting_info.spent_amount_of(None)?)?;
+    let voting_power = if total_amount.is_zero() {
+        Uint128::zero()
+    } else {
+        unclaimed_amount.multiply_ratio(total_power, total_amount)
+    };
+
+    Ok(Some((unclaimed_amount, voting_power)))
+}
+
+/// Replaces the contribution of an account with weighted schedules to the weighted voting state.
+///
+/// * **old_info** the vesting information of the account before the change.
+///
+/// * **new_info** the vesting information of the account after the change.
+pub(crate) fn update_weighted_voting_state(
+    storage: &mut dyn Storage,
+    historical: bool,
+    old_info: Option<&VestingInfo>,
+    new_info: Option<&VestingInfo>,
+    height: u64,
+) -> StdResult<()> {
+    let old_power = old_info.map(compute_weighted_voting_power).transpose()?;
+    let new_power = new_info.map(compute_weighted_voting_power).transpose()?;
+    let (old_power, new_power) = (old_power.flatten(), new_power.flatten());
+    if old_power.is_none() && new_power.is_none() {
+        return Ok(());
+    }
+
+    weighted_voting_state(historical).update(storage, height, |s| -> StdResult<_> {
+        let mut state = s.unwrap_or_default();
+        if let Some((unclaimed_amount, voting_power)) = old_power {
+            state.unclaimed_amount = state.unclaimed_amount.checked_sub(unclaimed_amount)?;
+            state.voting_power = state.voting_power.checked_sub(voting_power)?;
+        }
+        if let Some((unclaimed_amount, voting_power)) = new_power {
+            state.unclaimed_amount = state.unclaimed_amount.checked_add(unclaimed_amount)?;
+            state.voting_power = state.voting_power.checked_add(voting_power)?;
+        }
+        Ok(state)
+    })?;
+
+    Ok(())
+}
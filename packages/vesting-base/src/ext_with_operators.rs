@@ -0,0 +1,206 @@
+use crate::error::{ext_unsupported_err, ContractError};
+use crate::handlers::{claim_for_address, get_vesting_token};
+use crate::msg::{ExecuteMsgWithOperators, QueryMsgWithOperators};
+use crate::state::{CONFIG, DEFAULT_LIMIT, MAX_LIMIT, OPERATORS};
+use crate::types::{OperatorApproval, OperatorApprovalResponse};
+use astroport::asset::{addr_opt_validate, AssetInfo};
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
+    StdResult, Storage, Uint128,
+};
+use cw_storage_plus::Bound;
+use cw_utils::Expiration;
+
+/// Contains the with_operators extension check and routing of the message.
+pub(crate) fn handle_execute_with_operators_msg(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsgWithOperators,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.extensions.with_operators {
+        return Err(ext_unsupported_err("with_operators").into());
+    }
+
+    match msg {
+        ExecuteMsgWithOperators::ApproveOperator {
+            operator,
+            expires,
+            recipient,
+        } => approve_operator(deps, env, info, operator, expires, recipient),
+        ExecuteMsgWithOperators::RevokeOperator { operator } => {
+            revoke_operator(deps, env, info, operator)
+        }
+        ExecuteMsgWithOperators::ClaimOnBehalf {
+            address,
+            asset,
+            recipient,
+            amount,
+        } => claim_on_behalf(deps, env, info, address, asset, recipient, amount),
+    }
+}
+
+/// Contains the with_operators extension check and routing of the message.
+pub(crate) fn handle_query_with_operators_msg(
+    deps: Deps,
+    _env: Env,
+    msg: QueryMsgWithOperators,
+) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.extensions.with_operators {
+        return Err(ext_unsupported_err("with_operators"));
+    }
+
+    match msg {
+        QueryMsgWithOperators::Operators {
+            address,
+            start_after,
+            limit,
+        } => to_json_binary(&query_operators(deps, address, start_after, limit)?),
+    }
+}
+
+/// Allows an operator to claim the sender's vested tokens. Approving an already approved
+/// operator overwrites the previous approval.
+///
+/// * **operator** address that is allowed to claim tokens on behalf of the sender.
+///
+/// * **expires** moment after which the approval is no longer valid.
+///
+/// * **recipient** address besides the sender the operator is allowed to send the claimed tokens to.
+fn approve_operator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operator: String,
+    expires: Option<Expiration>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let operator = deps.api.addr_validate(&operator)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::OperatorApprovalExpired {});
+    }
+
+    OPERATORS.save(
+        deps.storage,
+        (info.sender.clone(), operator.clone()),
+        &OperatorApproval {
+            expires,
+            recipient: addr_opt_validate(deps.api, &recipient)?,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "approve_operator"),
+        attr("address", &info.sender),
+        attr("operator", &operator),
+    ]))
+}
+
+/// Revokes the approval of an operator to claim the sender's vested tokens.
+///
+/// * **operator** address whose approval is revoked.
+fn revoke_operator(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    operator: String,
+) -> Result<Response, ContractError> {
+    let operator = deps.api.addr_validate(&operator)?;
+    OPERATORS.remove(deps.storage, (info.sender.clone(), operator.clone()));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_operator"),
+        attr("address", &info.sender),
+        attr("operator", &operator),
+    ]))
+}
+
+/// Claims vested tokens of a vesting account on behalf of its owner. The tokens can only be sent
+/// to the vesting account owner or to the recipient approved for the operator.
+///
+/// * **address** vesting account for which to claim tokens.
+///
+/// * **asset** asset to claim. Defaults to the vesting token.
+///
+/// * **recipient** address that receives the claimed tokens. Defaults to the vesting account owner.
+///
+/// * **amount** amount of vested tokens to claim.
+fn claim_on_behalf(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    asset: Option<AssetInfo>,
+    recipient: Option<String>,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let approval = OPERATORS
+        .may_load(deps.storage, (address.clone(), info.sender.clone()))?
+        .ok_or(ContractError::Unauthorized {})?;
+    if approval.expires.is_expired(&env.block) {
+        return Err(ContractError::OperatorApprovalExpired {});
+    }
+
+    let recipient = match addr_opt_validate(deps.api, &recipient)? {
+        Some(recipient)
+            if recipient == address || Some(&recipient) == approval.recipient.as_ref() =>
+        {
+            recipient
+        }
+        Some(_) => return Err(ContractError::Unauthorized {}),
+        None => address.clone(),
+    };
+
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_token = get_vesting_token(&config)?;
+    let asset = asset.filter(|asset| *asset != vesting_token);
+
+    let response = claim_for_address(deps, env, address, asset, recipient.to_string(), amount)?;
+    Ok(response.add_attribute("operator", &info.sender))
+}
+
+/// Removes all the operator approvals of a vesting account.
+///
+/// * **address** vesting account whose approvals are removed.
+pub(crate) fn clear_operators(storage: &mut dyn Storage, address: &Addr) -> StdResult<()> {
+    let operators = OPERATORS
+        .prefix(address.clone())
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for operator in operators {
+        OPERATORS.remove(storage, (address.clone(), operator));
+    }
+    Ok(())
+}
+
+/// Returns a list of operators approved by a specific vesting account owner.
+///
+/// * **address** vesting account owner for which to return the approved operators.
+///
+/// * **start_after** operator address from which to start reading approvals.
+///
+/// * **limit** amount of approvals to return.
+fn query_operators(
+    deps: Deps,
+    address: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OperatorApprovalResponse>> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = addr_opt_validate(deps.api, &start_after)?.map(Bound::exclusive);
+
+    OPERATORS
+        .prefix(address)
+        .range(deps.storage, start_after, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (operator, approval) = item?;
+            Ok(OperatorApprovalResponse { operator, approval })
+        })
+        .collect()
+}
@@ -1,22 +1,51 @@
-use crate::error::ContractError;
+use crate::error::{ext_unsupported_err, ContractError};
 use crate::ext_historical::{handle_execute_historical_msg, handle_query_historical_msg};
-use crate::ext_managed::{handle_execute_managed_msg, handle_query_managed_msg};
+use crate::ext_managed::{handle_execute_managed_msg, handle_query_managed_msg, vesting_time};
+use crate::ext_pausable::{
+    assert_not_paused, handle_execute_pausable_msg, handle_query_pausable_msg,
+};
+use crate::ext_staking::{
+    assert_not_staked, handle_execute_staking_msg, handle_query_staking_msg, settle_unbondings,
+    staked_amount,
+};
+use crate::ext_voting::{
+    query_dao, query_info, query_total_power_at_height, query_voting_power_at_height,
+};
 use crate::ext_with_managers::{handle_execute_with_managers_msg, handle_query_managers_msg};
-use crate::msg::{Cw20HookMsg, ExecuteMsg, MigrateMsg, QueryMsg};
-use crate::state::{read_vesting_infos, vesting_info, vesting_state};
-use crate::state::{CONFIG, OWNERSHIP_PROPOSAL, VESTING_MANAGERS};
+use crate::ext_with_operators::{
+    clear_operators, handle_execute_with_operators_msg, handle_query_with_operators_msg,
+};
+use crate::msg::{Cw20HookMsg, ExecuteMsg, QueryMsg};
+use crate::state::{
+    claim_for_limit, read_vesting_infos, remove_vesting_info, save_vesting_info,
+    update_vesting_state, vesting_info, vesting_state,
+};
+use crate::state::{
+    CLAIM_FOR_LIMIT, CONFIG, DEFAULT_LIMIT, MAX_LIMIT, MAX_TIMELINE_POINTS, MERKLE_REGISTRATIONS,
+    MERKLE_ROOTS, MERKLE_ROOT_COUNT, OWNERSHIP_PROPOSAL, VESTING_ASSET_STATES, VESTING_MANAGERS,
+    VESTING_TEMPLATES, VESTING_TERMINATIONS,
+};
 use crate::types::{
-    Config, OrderBy, VestingAccount, VestingAccountResponse, VestingAccountsResponse, VestingInfo,
-    VestingSchedule, VestingState,
+    Config, MerkleRoot, MerkleRootResponse, OrderBy, TemplateVestingAccount,
+    UnlockForecastResponse, VestingAccount, VestingAccountResponse, VestingAccountsResponse,
+    VestingAssetState, VestingInfo, VestingSchedule, VestingSchedulePoint, VestingScheduleTemplate,
+    VestingState, VestingStateReconciliationResponse, VestingTemplateResponse,
+    VestingTimelinePoint,
+};
+use astroport::asset::{
+    addr_opt_validate, native_asset, token_asset, token_asset_info, Asset, AssetInfo, AssetInfoExt,
 };
-use astroport::asset::{addr_opt_validate, token_asset_info, AssetInfo, AssetInfoExt};
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use cosmwasm_std::{
-    attr, from_json, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Storage, SubMsg, Uint128,
+    attr, from_json, to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdError, StdResult, Storage, SubMsg, Uint128,
 };
+use cw2::get_contract_version;
 use cw20::Cw20ReceiveMsg;
-use cw_utils::must_pay;
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, one_coin, PaymentError};
+use sha2::{Digest, Sha256};
+use std::iter;
 
 /// Exposes execute functions available in the contract.
 pub fn execute(
@@ -26,22 +55,124 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Claim { recipient, amount } => claim(deps, env, info, recipient, amount),
+        ExecuteMsg::Claim { recipient, amount } => claim(deps, env, info, None, recipient, amount),
+        ExecuteMsg::ClaimAsset {
+            asset_info,
+            recipient,
+            amount,
+        } => claim(deps, env, info, Some(asset_info), recipient, amount),
+        ExecuteMsg::ClaimFor { addresses } => claim_for(deps, env, info, addresses),
+        ExecuteMsg::SetClaimForLimit { limit } => set_claim_for_limit(deps, env, info, limit),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::RegisterVestingAccounts { vesting_accounts } => {
             let config = CONFIG.load(deps.storage)?;
             let vesting_token = get_vesting_token(&config)?;
+            let asset_info = vesting_accounts_asset_info(&vesting_accounts, &vesting_token)?;
 
-            match &vesting_token {
+            match &asset_info {
                 AssetInfo::NativeToken { denom }
                     if is_sender_whitelisted(deps.storage, &config, &info.sender) =>
                 {
                     let amount = must_pay(&info, denom)?;
-                    register_vesting_accounts(deps, vesting_accounts, amount, env.block.height)
+                    register_vesting_accounts(
+                        deps,
+                        vesting_accounts,
+                        &asset_info,
+                        amount,
+                        env.block.height,
+                    )
                 }
                 _ => Err(ContractError::Unauthorized {}),
             }
         }
+        ExecuteMsg::RegisterTemplateVestingAccounts { vesting_accounts } => {
+            let vesting_accounts =
+                template_vesting_accounts(deps.as_ref(), &env, vesting_accounts)?;
+            execute(
+                deps,
+                env,
+                info,
+                ExecuteMsg::RegisterVestingAccounts { vesting_accounts },
+            )
+        }
+        ExecuteMsg::SetVestingTemplate {
+            template_id,
+            template,
+        } => set_vesting_template(deps, info, template_id, template),
+        ExecuteMsg::RemoveVestingTemplate { template_id } => {
+            remove_vesting_template(deps, info, template_id)
+        }
+        ExecuteMsg::RepairVestingState {
+            total_granted,
+            total_released,
+        } => repair_vesting_state(deps, env, info, total_granted, total_released),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            template_ids,
+            start_time,
+            expiration,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            if !is_sender_whitelisted(deps.storage, &config, &info.sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let coin = one_coin(&info)?;
+            register_merkle_root(
+                deps,
+                env,
+                merkle_root,
+                template_ids,
+                start_time,
+                expiration,
+                native_asset(coin.denom, coin.amount),
+            )
+        }
+        ExecuteMsg::ClaimWithMerkleProof {
+            root_id,
+            template_id,
+            amount,
+            proof,
+            recipient,
+        } => claim_with_merkle_proof(
+            deps,
+            env,
+            info,
+            root_id,
+            template_id,
+            amount,
+            proof,
+            recipient,
+        ),
+        ExecuteMsg::WithdrawMerkleRootRemainder { root_id, recipient } => {
+            withdraw_merkle_root_remainder(deps, env, info, root_id, recipient)
+        }
+        ExecuteMsg::AmendVestingAccount {
+            address,
+            schedule_index,
+            end_amount,
+            end_time,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            if !is_sender_whitelisted(deps.storage, &config, &info.sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let deposit = match info.funds.as_slice() {
+                [] => None,
+                [coin] => Some(native_asset(coin.denom.clone(), coin.amount)),
+                _ => return Err(PaymentError::MultipleDenoms {}.into()),
+            };
+            amend_vesting_account(
+                deps,
+                env,
+                address,
+                schedule_index,
+                end_amount,
+                end_time,
+                deposit,
+            )
+        }
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config: Config = CONFIG.load(deps.storage)?;
 
@@ -76,6 +207,9 @@ pub fn execute(
         ExecuteMsg::SetVestingToken { vesting_token } => {
             set_vesting_token(deps, env, info, vesting_token)
         }
+        ExecuteMsg::TransferVestingAccount { new_address } => {
+            transfer_vesting_account(deps, env, info, new_address)
+        }
         ExecuteMsg::ManagedExtension { msg } => handle_execute_managed_msg(deps, env, info, msg),
         ExecuteMsg::WithManagersExtension { msg } => {
             handle_execute_with_managers_msg(deps, env, info, msg)
@@ -83,6 +217,11 @@ pub fn execute(
         ExecuteMsg::HistoricalExtension { msg } => {
             handle_execute_historical_msg(deps, env, info, msg)
         }
+        ExecuteMsg::WithOperatorsExtension { msg } => {
+            handle_execute_with_operators_msg(deps, env, info, msg)
+        }
+        ExecuteMsg::StakingExtension { msg } => handle_execute_staking_msg(deps, env, info, msg),
+        ExecuteMsg::PausableExtension { msg } => handle_execute_pausable_msg(deps, env, info, msg),
     }
 }
 
@@ -96,67 +235,662 @@ fn receive_cw20(
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+
+    match from_json(&cw20_msg.msg)? {
+        Cw20HookMsg::RegisterVestingAccounts { vesting_accounts } => {
+            register_received_vesting_accounts(deps, env, info, cw20_msg, vesting_accounts)
+        }
+        Cw20HookMsg::RegisterTemplateVestingAccounts { vesting_accounts } => {
+            let vesting_accounts =
+                template_vesting_accounts(deps.as_ref(), &env, vesting_accounts)?;
+            register_received_vesting_accounts(deps, env, info, cw20_msg, vesting_accounts)
+        }
+        Cw20HookMsg::RegisterMerkleRoot {
+            merkle_root,
+            template_ids,
+            start_time,
+            expiration,
+        } => {
+            // Permission check
+            if !is_sender_whitelisted(
+                deps.storage,
+                &config,
+                &deps.api.addr_validate(&cw20_msg.sender)?,
+            ) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            register_merkle_root(
+                deps,
+                env,
+                merkle_root,
+                template_ids,
+                start_time,
+                expiration,
+                token_asset(info.sender, cw20_msg.amount),
+            )
+        }
+        Cw20HookMsg::AmendVestingAccount {
+            address,
+            schedule_index,
+            end_amount,
+            end_time,
+        } => {
+            // Permission check
+            if !is_sender_whitelisted(
+                deps.storage,
+                &config,
+                &deps.api.addr_validate(&cw20_msg.sender)?,
+            ) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            amend_vesting_account(
+                deps,
+                env,
+                address,
+                schedule_index,
+                end_amount,
+                end_time,
+                Some(token_asset(info.sender, cw20_msg.amount)),
+            )
+        }
+    }
+}
+
+/// Registers vesting accounts funded with the received CW20 tokens.
+///
+/// * **cw20_msg** CW20 message that funds the vesting accounts.
+///
+/// * **vesting_accounts** list of accounts and associated vesting schedules to create.
+fn register_received_vesting_accounts(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+    vesting_accounts: Vec<VestingAccount>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
     let vesting_token = get_vesting_token(&config)?;
+    let asset_info = vesting_accounts_asset_info(&vesting_accounts, &vesting_token)?;
 
     // Permission check
     if !is_sender_whitelisted(
         deps.storage,
         &config,
         &deps.api.addr_validate(&cw20_msg.sender)?,
-    ) || token_asset_info(info.sender) != vesting_token
+    ) || token_asset_info(info.sender) != asset_info
     {
         return Err(ContractError::Unauthorized {});
     }
 
-    match from_json(&cw20_msg.msg)? {
-        Cw20HookMsg::RegisterVestingAccounts { vesting_accounts } => {
-            register_vesting_accounts(deps, vesting_accounts, cw20_msg.amount, env.block.height)
-        }
+    register_vesting_accounts(
+        deps,
+        vesting_accounts,
+        &asset_info,
+        cw20_msg.amount,
+        env.block.height,
+    )
+}
+
+/// Computes the vesting schedules of a list of vesting accounts from their templates.
+///
+/// * **vesting_accounts** list of accounts and the templates of their vesting schedules.
+fn template_vesting_accounts(
+    deps: Deps,
+    env: &Env,
+    vesting_accounts: Vec<TemplateVestingAccount>,
+) -> Result<Vec<VestingAccount>, ContractError> {
+    vesting_accounts
+        .into_iter()
+        .map(|account| {
+            let template = VESTING_TEMPLATES
+                .may_load(deps.storage, account.template_id.clone())?
+                .ok_or(ContractError::VestingTemplateNotFound(account.template_id))?;
+            let start_time = account.start_time.unwrap_or(env.block.time.seconds());
+            Ok(VestingAccount {
+                address: account.address,
+                schedules: vec![template_schedule(&template, start_time, account.amount)],
+            })
+        })
+        .collect()
+}
+
+/// Computes a vesting schedule from a template.
+///
+/// * **template** the shape of the vesting schedule.
+///
+/// * **start_time** the start time of the vesting schedule.
+///
+/// * **amount** the total amount of tokens vested by the schedule.
+fn template_schedule(
+    template: &VestingScheduleTemplate,
+    start_time: u64,
+    amount: Uint128,
+) -> VestingSchedule {
+    let start_amount = amount * template.start_share;
+    // everything unlocked at once makes a schedule without an end point
+    let end_point =
+        (template.duration > 0 && start_amount < amount).then(|| VestingSchedulePoint {
+            time: start_time + template.duration,
+            amount,
+        });
+
+    VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: start_time,
+            amount: if end_point.is_some() {
+                start_amount
+            } else {
+                amount
+            },
+        },
+        // the tokens unlocked at once don't wait for a cliff
+        cliff: end_point
+            .as_ref()
+            .and(template.cliff)
+            .map(|cliff| start_time + cliff),
+        end_point,
+        intermediate_points: None,
+        periodic: None,
+        asset_info: template.asset_info.clone(),
+        voting_weight: template.voting_weight,
+    }
+}
+
+/// Creates or replaces a vesting schedule template.
+///
+/// * **template_id** the name of the template.
+///
+/// * **template** the shape of the vesting schedules registered with the template.
+fn set_vesting_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    template_id: String,
+    template: VestingScheduleTemplate,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // a cliff makes no sense when everything unlocks at the start time
+    if template.start_share > Decimal::one()
+        || template.cliff.map_or(false, |cliff| {
+            cliff > template.duration || (cliff > 0 && template.start_share == Decimal::one())
+        })
+    {
+        return Err(ContractError::VestingTemplateError(template_id));
+    }
+    VESTING_TEMPLATES.save(deps.storage, template_id.clone(), &template)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_vesting_template"),
+        attr("template_id", template_id),
+    ]))
+}
+
+/// Removes a vesting schedule template.
+///
+/// * **template_id** the name of the template.
+fn remove_vesting_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    template_id: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !VESTING_TEMPLATES.has(deps.storage, template_id.clone()) {
+        return Err(ContractError::VestingTemplateNotFound(template_id));
+    }
+    VESTING_TEMPLATES.remove(deps.storage, template_id.clone());
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_vesting_template"),
+        attr("template_id", template_id),
+    ]))
+}
+
+/// Overwrites the vesting state of the vesting token.
+///
+/// * **total_granted** the total amount of tokens granted to the vesting accounts.
+///
+/// * **total_released** the total amount of tokens released to the vesting accounts.
+fn repair_vesting_state(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    total_granted: Uint128,
+    total_released: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if total_released > total_granted {
+        return Err(ContractError::VestingStateRepairError {});
+    }
+
+    let state = update_vesting_state(
+        deps.storage,
+        config.extensions.historical,
+        None,
+        env.block.height,
+        |_| {
+            Ok(VestingState {
+                total_granted,
+                total_released,
+            })
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "repair_vesting_state"),
+        attr("total_granted", state.total_granted),
+        attr("total_released", state.total_released),
+    ]))
+}
+
+/// Clears the vesting schedules of an account that leaves the contract, e.g. to migrate to
+/// another contract, and deducts its granted and released amounts of the vesting token from the
+/// vesting state. The stored vesting information should match the expected one.
+///
+/// * **address** vesting account to clear.
+///
+/// * **expected_info** the vesting information the caller expects the account to have.
+pub fn clear_vesting_account(
+    storage: &mut dyn Storage,
+    address: Addr,
+    expected_info: &VestingInfo,
+    height: u64,
+) -> Result<(), ContractError> {
+    let config = CONFIG.load(storage)?;
+    let historical = config.extensions.historical;
+    let info = vesting_info(historical).load(storage, address.clone())?;
+    if info != *expected_info {
+        return Err(ContractError::VestingStateInvariantError(
+            address.to_string(),
+        ));
+    }
+
+    let granted_amount = compute_granted_amount(&info, None)?;
+    save_vesting_info(
+        storage,
+        historical,
+        address.clone(),
+        &VestingInfo {
+            schedules: vec![],
+            released_amount: Uint128::zero(),
+            released_assets: vec![],
+            slashed_amount: Uint128::zero(),
+        },
+        height,
+    )?;
+    update_vesting_state(storage, historical, None, height, |s| {
+        let mut state = s.unwrap_or_default();
+        let invariant_err = |_| ContractError::VestingStateInvariantError(address.to_string());
+        state.total_granted = state
+            .total_granted
+            .checked_sub(granted_amount)
+            .map_err(invariant_err)?;
+        state.total_released = state
+            .total_released
+            .checked_sub(info.spent_amount_of(None)?)
+            .map_err(invariant_err)?;
+        Ok(state)
+    })?;
+
+    Ok(())
+}
+
+/// Counts tokens of the vesting token of an account as released, e.g. the share of the tokens
+/// the account had claimed before it migrated from another contract, and adds them to the
+/// released amount of the vesting state.
+///
+/// * **address** vesting account to release tokens of.
+///
+/// * **amount** the amount of tokens to count as released.
+pub fn add_released_amount(
+    storage: &mut dyn Storage,
+    address: Addr,
+    amount: Uint128,
+    height: u64,
+) -> Result<(), ContractError> {
+    let config = CONFIG.load(storage)?;
+    let historical = config.extensions.historical;
+    let mut info = vesting_info(historical).load(storage, address.clone())?;
+    info.add_released_amount(None, amount)?;
+    if info.spent_amount_of(None)? > compute_granted_amount(&info, None)? {
+        return Err(ContractError::VestingStateInvariantError(
+            address.to_string(),
+        ));
+    }
+    save_vesting_info(storage, historical, address, &info, height)?;
+    update_vesting_state(storage, historical, None, height, |s| {
+        let mut state = s.unwrap_or_default();
+        state.total_released = state.total_released.checked_add(amount)?;
+        Ok(state)
+    })?;
+
+    Ok(())
+}
+
+/// Registers a funded Merkle root of vesting accounts. The templates used by the leaves are
+/// copied to the root, so later changes of the templates don't affect the root.
+///
+/// * **merkle_root** the hex-encoded root of the Merkle tree.
+///
+/// * **template_ids** the names of the templates used by the leaves.
+///
+/// * **start_time** the start time of the vesting schedules.
+///
+/// * **expiration** the time after which the accounts can't be registered from the root.
+///
+/// * **deposit** the tokens funding the vesting accounts.
+fn register_merkle_root(
+    deps: DepsMut,
+    env: Env,
+    merkle_root: String,
+    template_ids: Vec<String>,
+    start_time: Option<u64>,
+    expiration: u64,
+    deposit: Asset,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage)?;
+    let mut root_buf = [0; 32];
+    hex::decode_to_slice(&merkle_root, &mut root_buf)
+        .map_err(|_| ContractError::MerkleRootError {})?;
+    if expiration <= env.block.time.seconds() {
+        return Err(ContractError::MerkleRootExpirationError {});
     }
+    let templates = template_ids
+        .into_iter()
+        .map(|template_id| {
+            let template = VESTING_TEMPLATES
+                .may_load(deps.storage, template_id.clone())?
+                .ok_or_else(|| ContractError::VestingTemplateNotFound(template_id.clone()))?;
+            Ok(VestingTemplateResponse {
+                template_id,
+                template,
+            })
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    // the deposit funds the schedules of the templates, so it has to be the asset they vest
+    let vesting_token = get_vesting_token(&CONFIG.load(deps.storage)?)?;
+    if templates.iter().any(|template| {
+        template
+            .template
+            .asset_info
+            .as_ref()
+            .unwrap_or(&vesting_token)
+            != &deposit.info
+    }) {
+        return Err(ContractError::VestingScheduleAssetError {});
+    }
+
+    let root_id = MERKLE_ROOT_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    MERKLE_ROOT_COUNT.save(deps.storage, &(root_id + 1))?;
+    MERKLE_ROOTS.save(
+        deps.storage,
+        root_id,
+        &MerkleRoot {
+            merkle_root: merkle_root.clone(),
+            templates,
+            start_time: start_time.unwrap_or(env.block.time.seconds()),
+            expiration,
+            asset_info: deposit.info,
+            total_amount: deposit.amount,
+            registered_amount: Uint128::zero(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_merkle_root"),
+        attr("root_id", root_id.to_string()),
+        attr("merkle_root", merkle_root),
+        attr("deposited", deposit.amount),
+    ]))
+}
+
+/// Registers the sender's vesting account from a Merkle root and claims the tokens vested so
+/// far. The leaf is the SHA-256 hash of the encoding returned by [`merkle_leaf`], and every pair
+/// of hashes is sorted before hashing, the same way as in the Merkle airdrop.
+///
+/// * **root_id** the identifier of the Merkle root.
+///
+/// * **template_id** the name of the template of the vesting schedule.
+///
+/// * **amount** the total amount of tokens vested by the schedule.
+///
+/// * **proof** the hex-encoded hashes proving that the account is a leaf of the tree.
+///
+/// * **recipient** the address that receives the vested tokens.
+#[allow(clippy::too_many_arguments)]
+fn claim_with_merkle_proof(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    root_id: u64,
+    template_id: String,
+    amount: Uint128,
+    proof: Vec<String>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut root = MERKLE_ROOTS
+        .may_load(deps.storage, root_id)?
+        .ok_or(ContractError::MerkleRootNotFound(root_id))?;
+    if env.block.time.seconds() >= root.expiration {
+        return Err(ContractError::MerkleRootExpired(root_id));
+    }
+    if MERKLE_REGISTRATIONS.has(deps.storage, (root_id, info.sender.clone())) {
+        return Err(ContractError::MerkleRootAlreadyRegistered(
+            info.sender.to_string(),
+        ));
+    }
+
+    let leaf = merkle_leaf(info.sender.as_str(), &template_id, amount);
+    let hash = proof
+        .iter()
+        .try_fold(sha256(&leaf), |hash, p| -> Result<_, ContractError> {
+            let mut proof_buf = [0; 32];
+            hex::decode_to_slice(p, &mut proof_buf)
+                .map_err(|_| ContractError::MerkleProofError(info.sender.to_string()))?;
+            let mut hashes = [hash, proof_buf];
+            hashes.sort_unstable();
+            Ok(sha256(&hashes.concat()))
+        })?;
+    let mut root_buf = [0; 32];
+    hex::decode_to_slice(&root.merkle_root, &mut root_buf)
+        .map_err(|_| ContractError::MerkleRootError {})?;
+    if root_buf != hash {
+        return Err(ContractError::MerkleProofError(info.sender.to_string()));
+    }
+
+    root.registered_amount = root.registered_amount.checked_add(amount)?;
+    if root.registered_amount > root.total_amount {
+        return Err(ContractError::MerkleRootAmountError {});
+    }
+    MERKLE_ROOTS.save(deps.storage, root_id, &root)?;
+    MERKLE_REGISTRATIONS.save(deps.storage, (root_id, info.sender.clone()), &())?;
+
+    let template = root
+        .templates
+        .iter()
+        .find(|template| template.template_id == template_id)
+        .ok_or(ContractError::VestingTemplateNotFound(template_id))?;
+    let vesting_accounts = vec![VestingAccount {
+        address: info.sender.to_string(),
+        schedules: vec![template_schedule(
+            &template.template,
+            root.start_time,
+            amount,
+        )],
+    }];
+    register_vesting_accounts(
+        deps.branch(),
+        vesting_accounts,
+        &root.asset_info,
+        amount,
+        env.block.height,
+    )?;
+
+    let response = claim(deps, env, info, Some(root.asset_info), recipient, None)?;
+    Ok(response.add_attributes(vec![
+        attr("root_id", root_id.to_string()),
+        attr("registered_amount", amount),
+    ]))
+}
+
+/// Sends the part of the deposit of an expired Merkle root that wasn't registered by the
+/// beneficiaries.
+///
+/// * **root_id** the identifier of the Merkle root.
+///
+/// * **recipient** the address that receives the tokens. Defaults to the owner.
+fn withdraw_merkle_root_remainder(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    root_id: u64,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut root = MERKLE_ROOTS
+        .may_load(deps.storage, root_id)?
+        .ok_or(ContractError::MerkleRootNotFound(root_id))?;
+    if env.block.time.seconds() < root.expiration {
+        return Err(ContractError::MerkleRootNotExpired(root_id));
+    }
+    let remainder = root.total_amount.checked_sub(root.registered_amount)?;
+    if remainder.is_zero() {
+        return Err(ContractError::AmountIsNotAvailable {});
+    }
+    root.total_amount = root.registered_amount;
+    MERKLE_ROOTS.save(deps.storage, root_id, &root)?;
+
+    let recipient = addr_opt_validate(deps.api, &recipient)?.unwrap_or(info.sender);
+    let transfer_msg = root
+        .asset_info
+        .with_balance(remainder)
+        .into_msg(&deps.querier, recipient.clone())?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::new(transfer_msg))
+        .add_attributes(vec![
+            attr("action", "withdraw_merkle_root_remainder"),
+            attr("root_id", root_id.to_string()),
+            attr("recipient", recipient),
+            attr("amount", remainder),
+        ]))
+}
+
+/// Returns the leaf of a Merkle tree of vesting accounts. The address and the template name are
+/// prefixed with their lengths as big-endian `u32`, and the amount is encoded as a big-endian
+/// `u128`, so that different accounts can't have the same leaf.
+fn merkle_leaf(address: &str, template_id: &str, amount: Uint128) -> Vec<u8> {
+    let mut leaf = vec![];
+    for field in [address, template_id] {
+        leaf.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        leaf.extend_from_slice(field.as_bytes());
+    }
+    leaf.extend_from_slice(&amount.u128().to_be_bytes());
+    leaf
+}
+
+/// Returns the SHA-256 hash of the data.
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&Sha256::digest(data));
+    hash
+}
+
+/// Returns the asset vested by a list of vesting accounts. All the schedules registered at once
+/// should vest the same asset, which defaults to the vesting token.
+///
+/// * **vesting_accounts** list of accounts and associated vesting schedules to create.
+///
+/// * **vesting_token** the vesting token of the contract.
+fn vesting_accounts_asset_info(
+    vesting_accounts: &[VestingAccount],
+    vesting_token: &AssetInfo,
+) -> Result<AssetInfo, ContractError> {
+    let mut asset_infos = vesting_accounts
+        .iter()
+        .flat_map(|vesting_account| &vesting_account.schedules)
+        .map(|sch| sch.asset_info.as_ref().unwrap_or(vesting_token));
+    let asset_info = asset_infos.next().unwrap_or(vesting_token);
+    if asset_infos.any(|other| other != asset_info) {
+        return Err(ContractError::VestingScheduleAssetError {});
+    }
+
+    Ok(asset_info.clone())
 }
 
 /// Create new vesting schedules.
 ///
 /// * **vesting_accounts** list of accounts and associated vesting schedules to create.
 ///
+/// * **asset_info** the asset that funds the vesting schedules. Every schedule should vest it.
+///
 /// * **cw20_amount** sets the amount that confirms the total amount of all accounts to register.
 pub fn register_vesting_accounts(
     deps: DepsMut,
     vesting_accounts: Vec<VestingAccount>,
+    asset_info: &AssetInfo,
     amount: Uint128,
     height: u64,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage)?;
     let response = Response::new();
     let config = CONFIG.load(deps.storage)?;
+    let vesting_token = get_vesting_token(&config)?;
     let mut to_deposit = Uint128::zero();
 
+    // the schedules vesting the vesting token are stored without an asset
+    let schedule_asset_info = (*asset_info != vesting_token).then(|| asset_info.clone());
+
     for mut vesting_account in vesting_accounts {
         let mut released_amount = Uint128::zero();
+        let mut released_assets = vec![];
+        let mut slashed_amount = Uint128::zero();
         let account_address = deps.api.addr_validate(&vesting_account.address)?;
 
         assert_vesting_schedules(&account_address, &vesting_account.schedules)?;
 
-        for sch in &vesting_account.schedules {
-            let amount = if let Some(end_point) = &sch.end_point {
-                end_point.amount
-            } else {
-                sch.start_point.amount
-            };
-            to_deposit = to_deposit.checked_add(amount)?;
+        for sch in vesting_account.schedules.iter_mut() {
+            if sch.asset_info.as_ref().unwrap_or(&vesting_token) != asset_info {
+                return Err(ContractError::VestingScheduleAssetError {});
+            }
+            sch.asset_info = schedule_asset_info.clone();
+            to_deposit = to_deposit.checked_add(compute_total_amount(sch)?)?;
         }
 
         let vesting_info = vesting_info(config.extensions.historical);
         if let Some(mut old_info) = vesting_info.may_load(deps.storage, account_address.clone())? {
             released_amount = old_info.released_amount;
+            released_assets = old_info.released_assets;
+            slashed_amount = old_info.slashed_amount;
             vesting_account.schedules.append(&mut old_info.schedules);
         }
 
-        vesting_info.save(
+        save_vesting_info(
             deps.storage,
+            config.extensions.historical,
             account_address,
             &VestingInfo {
                 schedules: vesting_account.schedules,
                 released_amount,
+                released_assets,
+                slashed_amount,
             },
             height,
         )?;
@@ -166,8 +900,10 @@ pub fn register_vesting_accounts(
         return Err(ContractError::VestingScheduleAmountError {});
     }
 
-    vesting_state(config.extensions.historical).update::<_, ContractError>(
+    update_vesting_state(
         deps.storage,
+        config.extensions.historical,
+        schedule_asset_info.as_ref(),
         height,
         |s| {
             let mut state = s.unwrap_or_default();
@@ -184,8 +920,110 @@ pub fn register_vesting_accounts(
     }))
 }
 
+/// Changes the end point of a vesting schedule of an existing vesting account. The tokens
+/// already vested by the schedule stay vested, and an increase of the end amount should be
+/// funded by the deposit.
+///
+/// * **address** vesting account which schedule to amend.
+///
+/// * **schedule_index** index of the schedule to amend.
+///
+/// * **end_amount** new amount of tokens vested at the end point.
+///
+/// * **end_time** new time of the end point.
+///
+/// * **deposit** the tokens funding the increase of the end amount.
+fn amend_vesting_account(
+    deps: DepsMut,
+    env: Env,
+    address: String,
+    schedule_index: u32,
+    end_amount: Option<Uint128>,
+    end_time: Option<u64>,
+    deposit: Option<Asset>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_token = get_vesting_token(&config)?;
+    let address = deps.api.addr_validate(&address)?;
+    let mut account_vesting_info =
+        vesting_info(config.extensions.historical).load(deps.storage, address.clone())?;
+
+    let sch = account_vesting_info
+        .schedules
+        .get_mut(schedule_index as usize)
+        .ok_or_else(|| ContractError::VestingScheduleExtractError(address.to_string()))?;
+    let old_sch = sch.clone();
+    let end_point = sch
+        .end_point
+        .as_mut()
+        .ok_or_else(|| ContractError::VestingScheduleAmendError(address.to_string()))?;
+    if let Some(end_amount) = end_amount {
+        end_point.amount = end_amount;
+    }
+    if let Some(end_time) = end_time {
+        end_point.time = end_time;
+    }
+    assert_vesting_schedules(&address, &[sch.clone()])?;
+
+    let old_total_amount = compute_total_amount(&old_sch)?;
+    let new_total_amount = compute_total_amount(sch)?;
+    // the amended schedule can't take back the tokens that have already vested, as they might
+    // have been claimed already
+    let vesting_time = vesting_time(deps.storage, address.clone(), env.block.time.seconds())?;
+    if new_total_amount < old_total_amount
+        || compute_vested_amount(vesting_time, sch)?
+            < compute_vested_amount(vesting_time, &old_sch)?
+    {
+        return Err(ContractError::VestingScheduleAmendError(
+            address.to_string(),
+        ));
+    }
+
+    let to_deposit = new_total_amount.checked_sub(old_total_amount)?;
+    let deposited = match deposit {
+        Some(asset) if asset.info != *sch.asset_info.as_ref().unwrap_or(&vesting_token) => {
+            return Err(ContractError::VestingScheduleAssetError {});
+        }
+        Some(asset) => asset.amount,
+        None => Uint128::zero(),
+    };
+    if deposited != to_deposit {
+        return Err(ContractError::VestingScheduleAmountError {});
+    }
+
+    let schedule_asset_info = sch.asset_info.clone();
+    save_vesting_info(
+        deps.storage,
+        config.extensions.historical,
+        address.clone(),
+        &account_vesting_info,
+        env.block.height,
+    )?;
+    update_vesting_state(
+        deps.storage,
+        config.extensions.historical,
+        schedule_asset_info.as_ref(),
+        env.block.height,
+        |s| {
+            let mut state = s.unwrap_or_default();
+            state.total_granted = state.total_granted.checked_add(to_deposit)?;
+            Ok(state)
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "amend_vesting_account"),
+        attr("address", &address),
+        attr("schedule_index", schedule_index.to_string()),
+        attr("deposited", to_deposit),
+    ]))
+}
+
 /// Claims vested tokens and transfers them to the vesting recipient.
 ///
+/// * **asset_info** asset to claim. Defaults to the vesting token.
+///
 /// * **recipient** vesting recipient for which to claim tokens.
 ///
 /// * **amount** amount of vested tokens to claim.
@@ -193,16 +1031,54 @@ fn claim(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    asset_info: Option<AssetInfo>,
     recipient: Option<String>,
     amount: Option<Uint128>,
 ) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_token = get_vesting_token(&config)?;
+    let asset_info = asset_info.filter(|asset_info| *asset_info != vesting_token);
+
+    let recipient = recipient.unwrap_or_else(|| info.sender.to_string());
+    claim_for_address(deps, env, info.sender, asset_info, recipient, amount)
+}
+
+/// Claims vested tokens of a specific vesting account and transfers them to a recipient.
+/// The caller is responsible for checking that the claim is authorized.
+///
+/// * **address** vesting account for which to claim tokens.
+///
+/// * **asset_info** asset to claim. `None` stands for the vesting token.
+///
+/// * **recipient** address that receives the claimed tokens.
+///
+/// * **amount** amount of vested tokens to claim.
+pub(crate) fn claim_for_address(
+    deps: DepsMut,
+    env: Env,
+    address: Addr,
+    asset_info: Option<AssetInfo>,
+    recipient: String,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage)?;
     let config = CONFIG.load(deps.storage)?;
     let vesting_token = get_vesting_token(&config)?;
     let vesting_info = vesting_info(config.extensions.historical);
-    let mut sender_vesting_info = vesting_info.load(deps.storage, info.sender.clone())?;
+    settle_unbondings(deps.storage, &deps.querier, &env, &address)?;
+    let mut account_vesting_info = vesting_info.load(deps.storage, address.clone())?;
 
-    let available_amount =
-        compute_available_amount(env.block.time.seconds(), &sender_vesting_info)?;
+    let vesting_time = vesting_time(deps.storage, address.clone(), env.block.time.seconds())?;
+    let staked_amount = match asset_info {
+        None => staked_amount(deps.storage, address.clone(), env.block.time.seconds())?,
+        Some(_) => Uint128::zero(),
+    };
+    let available_amount = compute_available_amount(
+        vesting_time,
+        &account_vesting_info,
+        asset_info.as_ref(),
+        staked_amount,
+    )?;
 
     let claim_amount = if let Some(a) = amount {
         if a > available_amount {
@@ -216,23 +1092,25 @@ fn claim(
     let mut response = Response::new();
 
     if !claim_amount.is_zero() {
-        let transfer_msg = vesting_token.with_balance(claim_amount).into_msg(
-            &deps.querier,
-            recipient.unwrap_or_else(|| info.sender.to_string()),
-        )?;
+        let transfer_msg = asset_info
+            .as_ref()
+            .unwrap_or(&vesting_token)
+            .with_balance(claim_amount)
+            .into_msg(&deps.querier, recipient)?;
         response = response.add_submessage(SubMsg::new(transfer_msg));
 
-        sender_vesting_info.released_amount = sender_vesting_info
-            .released_amount
-            .checked_add(claim_amount)?;
-        vesting_info.save(
+        account_vesting_info.add_released_amount(asset_info.as_ref(), claim_amount)?;
+        save_vesting_info(
             deps.storage,
-            info.sender.clone(),
-            &sender_vesting_info,
+            config.extensions.historical,
+            address.clone(),
+            &account_vesting_info,
             env.block.height,
         )?;
-        vesting_state(config.extensions.historical).update::<_, ContractError>(
+        update_vesting_state(
             deps.storage,
+            config.extensions.historical,
+            asset_info.as_ref(),
             env.block.height,
             |s| {
                 let mut state = s.ok_or(ContractError::AmountIsNotAvailable {})?;
@@ -242,11 +1120,163 @@ fn claim(
         )?;
     };
 
-    Ok(response.add_attributes(vec![
+    let mut attributes = vec![
         attr("action", "claim"),
-        attr("address", &info.sender),
+        attr("address", &address),
         attr("available_amount", available_amount),
         attr("claimed_amount", claim_amount),
+    ];
+    if let Some(asset_info) = asset_info {
+        attributes.push(attr("asset", asset_info.to_string()));
+    }
+
+    Ok(response.add_attributes(attributes))
+}
+
+/// Claims the available tokens of all the assets of multiple vesting accounts and sends them
+/// to the accounts. Addresses without a vesting account are skipped and reported in the
+/// `skipped_address` attributes.
+///
+/// * **addresses** vesting accounts for which to claim tokens.
+fn claim_for(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !is_sender_whitelisted(deps.storage, &config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let limit = claim_for_limit(deps.storage)?;
+    if addresses.len() > limit as usize {
+        return Err(ContractError::ClaimForLimitExceeded { limit });
+    }
+
+    let vesting_info = vesting_info(config.extensions.historical);
+    let mut response = Response::new().add_attribute("action", "claim_for");
+    for address in addresses {
+        let address = deps.api.addr_validate(&address)?;
+        // an address without a vesting account doesn't block the claims of the others
+        let asset_infos = match vesting_info.may_load(deps.storage, address.clone())? {
+            Some(info) => info.additional_asset_infos(),
+            None => {
+                response = response.add_attribute("skipped_address", address);
+                continue;
+            }
+        };
+        for asset_info in iter::once(None).chain(asset_infos.into_iter().map(Some)) {
+            let claim_response = claim_for_address(
+                deps.branch(),
+                env.clone(),
+                address.clone(),
+                asset_info,
+                address.to_string(),
+                None,
+            )?;
+            response = response
+                .add_submessages(claim_response.messages)
+                .add_attributes(
+                    claim_response
+                        .attributes
+                        .into_iter()
+                        .filter(|attr| attr.key != "action"),
+                );
+        }
+    }
+
+    Ok(response)
+}
+
+/// Sets the maximum number of addresses a single ClaimFor message can process.
+///
+/// * **limit** the new limit.
+fn set_claim_for_limit(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    limit: u32,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    CLAIM_FOR_LIMIT.save(deps.storage, &limit)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_claim_for_limit"),
+        attr("limit", limit.to_string()),
+    ]))
+}
+
+/// Moves the sender's vesting information to another address, merging it with the vesting
+/// information the address might already have.
+///
+/// * **new_address** the address that receives the vesting information.
+fn transfer_vesting_account(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_address: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.extensions.transferable {
+        return Err(ext_unsupported_err("transferable").into());
+    }
+    assert_not_paused(deps.storage)?;
+
+    let new_address = deps.api.addr_validate(&new_address)?;
+    if new_address == info.sender {
+        return Err(ContractError::VestingTransferToSelfError(
+            info.sender.to_string(),
+        ));
+    }
+    assert_not_staked(deps.storage, &deps.querier, &env, &info.sender)?;
+    // a transfer must not let the tokens escape a termination or fall under somebody else's
+    for address in [&info.sender, &new_address] {
+        if VESTING_TERMINATIONS.has(deps.storage, address.clone()) {
+            return Err(ContractError::VestingTransferTerminationError(
+                address.to_string(),
+            ));
+        }
+    }
+
+    let vesting_info = vesting_info(config.extensions.historical);
+    let mut moved_info = vesting_info.load(deps.storage, info.sender.clone())?;
+    if let Some(mut new_address_info) = vesting_info.may_load(deps.storage, new_address.clone())? {
+        moved_info.schedules.append(&mut new_address_info.schedules);
+        moved_info.add_released_amount(None, new_address_info.released_amount)?;
+        moved_info.slashed_amount = moved_info
+            .slashed_amount
+            .checked_add(new_address_info.slashed_amount)?;
+        for asset in new_address_info.released_assets {
+            moved_info.add_released_amount(Some(&asset.info), asset.amount)?;
+        }
+    }
+
+    // both changes are written to the snapshots, so the historical queries stay correct
+    // for the sender and the new address
+    remove_vesting_info(
+        deps.storage,
+        config.extensions.historical,
+        info.sender.clone(),
+        env.block.height,
+    )?;
+    save_vesting_info(
+        deps.storage,
+        config.extensions.historical,
+        new_address.clone(),
+        &moved_info,
+        env.block.height,
+    )?;
+    // the operators were approved for the moved account, they must not regain access to the
+    // sender if it gets a vesting account again
+    clear_operators(deps.storage, &info.sender)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "transfer_vesting_account"),
+        attr("address", &info.sender),
+        attr("new_address", &new_address),
     ]))
 }
 
@@ -303,13 +1333,175 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             &query_vesting_available_amount(deps, env, address)?,
         )?),
         QueryMsg::VestingState {} => Ok(to_json_binary(&query_vesting_state(deps)?)?),
+        QueryMsg::VestingStates {} => Ok(to_json_binary(&query_vesting_states(deps)?)?),
+        QueryMsg::AvailableAmounts { address } => Ok(to_json_binary(
+            &query_vesting_available_amounts(deps, env, address)?,
+        )?),
         QueryMsg::Timestamp {} => Ok(to_json_binary(&query_timestamp(env)?)?),
+        QueryMsg::VestingTimeline {
+            address,
+            from,
+            to,
+            step,
+        } => Ok(to_json_binary(&query_vesting_timeline(
+            deps, address, from, to, step,
+        )?)?),
+        QueryMsg::UnlockForecast {
+            from,
+            to,
+            start_after,
+            limit,
+        } => Ok(to_json_binary(&query_unlock_forecast(
+            deps,
+            from,
+            to,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::ClaimForLimit {} => Ok(to_json_binary(&claim_for_limit(deps.storage)?)?),
+        QueryMsg::VestingTemplate { template_id } => {
+            Ok(to_json_binary(&query_vesting_template(deps, template_id)?)?)
+        }
+        QueryMsg::VestingTemplates { start_after, limit } => Ok(to_json_binary(
+            &query_vesting_templates(deps, start_after, limit)?,
+        )?),
+        QueryMsg::VestingStateReconciliation { start_after, limit } => Ok(to_json_binary(
+            &query_vesting_state_reconciliation(deps, start_after, limit)?,
+        )?),
+        QueryMsg::MerkleRoot { root_id } => Ok(to_json_binary(&query_merkle_root(deps, root_id)?)?),
+        QueryMsg::MerkleRoots { start_after, limit } => Ok(to_json_binary(&query_merkle_roots(
+            deps,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::IsMerkleRegistered { root_id, address } => {
+            let address = deps.api.addr_validate(&address)?;
+            Ok(to_json_binary(
+                &MERKLE_REGISTRATIONS.has(deps.storage, (root_id, address)),
+            )?)
+        }
+        QueryMsg::VotingPowerAtHeight { address, height } => Ok(to_json_binary(
+            &query_voting_power_at_height(deps, env, address, height)?,
+        )?),
+        QueryMsg::TotalPowerAtHeight { height } => Ok(to_json_binary(
+            &query_total_power_at_height(deps, env, height)?,
+        )?),
+        QueryMsg::Info {} => Ok(to_json_binary(&query_info(deps)?)?),
+        QueryMsg::Dao {} => Ok(to_json_binary(&query_dao(deps)?)?),
+        QueryMsg::ContractVersion {} => Ok(to_json_binary(&get_contract_version(deps.storage)?)?),
         QueryMsg::ManagedExtension { msg } => handle_query_managed_msg(deps, env, msg),
         QueryMsg::WithManagersExtension { msg } => handle_query_managers_msg(deps, env, msg),
         QueryMsg::HistoricalExtension { msg } => handle_query_historical_msg(deps, env, msg),
+        QueryMsg::WithOperatorsExtension { msg } => handle_query_with_operators_msg(deps, env, msg),
+        QueryMsg::StakingExtension { msg } => handle_query_staking_msg(deps, env, msg),
+        QueryMsg::PausableExtension { msg } => handle_query_pausable_msg(deps, env, msg),
     }
 }
 
+/// Returns a vesting schedule template.
+///
+/// * **template_id** the name of the template.
+fn query_vesting_template(deps: Deps, template_id: String) -> StdResult<VestingScheduleTemplate> {
+    VESTING_TEMPLATES
+        .may_load(deps.storage, template_id.clone())?
+        .ok_or_else(|| {
+            StdError::not_found(ContractError::VestingTemplateNotFound(template_id).to_string())
+        })
+}
+
+/// Returns a list of vesting schedule templates.
+///
+/// * **start_after** name from which to start reading templates.
+///
+/// * **limit** amount of templates to return.
+fn query_vesting_templates(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<VestingTemplateResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    VESTING_TEMPLATES
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (template_id, template) = item?;
+            Ok(VestingTemplateResponse {
+                template_id,
+                template,
+            })
+        })
+        .collect()
+}
+
+/// Returns the vesting state of the vesting token along with the amounts granted and released
+/// to a page of vesting accounts.
+///
+/// * **start_after** address from which to start reading vesting accounts.
+///
+/// * **limit** amount of vesting accounts to read.
+fn query_vesting_state_reconciliation(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<VestingStateReconciliationResponse> {
+    let start_after = addr_opt_validate(deps.api, &start_after)?;
+    let vesting_infos = read_vesting_infos(deps, start_after, limit, Some(OrderBy::Asc))?;
+
+    let mut granted_amount = Uint128::zero();
+    let mut released_amount = Uint128::zero();
+    for (_, info) in &vesting_infos {
+        granted_amount = granted_amount.checked_add(compute_granted_amount(info, None)?)?;
+        released_amount = released_amount.checked_add(info.spent_amount_of(None)?)?;
+    }
+
+    Ok(VestingStateReconciliationResponse {
+        vesting_state: query_vesting_state(deps)?,
+        granted_amount,
+        released_amount,
+        last_address: vesting_infos.last().map(|(address, _)| address.clone()),
+    })
+}
+
+/// Returns a Merkle root of vesting accounts.
+///
+/// * **root_id** the identifier of the root.
+fn query_merkle_root(deps: Deps, root_id: u64) -> StdResult<MerkleRoot> {
+    MERKLE_ROOTS
+        .may_load(deps.storage, root_id)?
+        .ok_or_else(|| StdError::not_found(ContractError::MerkleRootNotFound(root_id).to_string()))
+}
+
+/// Returns a list of Merkle roots of vesting accounts.
+///
+/// * **start_after** identifier from which to start reading roots.
+///
+/// * **limit** amount of roots to return.
+fn query_merkle_roots(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<MerkleRootResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    MERKLE_ROOTS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (root_id, root) = item?;
+            Ok(MerkleRootResponse { root_id, root })
+        })
+        .collect()
+}
+
 /// Returns the vesting contract configuration using a [`Config`] object.
 fn query_config(deps: Deps) -> StdResult<Config> {
     let config = CONFIG.load(deps.storage)?;
@@ -324,6 +1516,27 @@ fn query_vesting_state(deps: Deps) -> StdResult<VestingState> {
     Ok(state)
 }
 
+/// Returns the accumulated vesting information of every vested asset, starting with the vesting token.
+fn query_vesting_states(deps: Deps) -> StdResult<Vec<VestingAssetState>> {
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_token =
+        get_vesting_token(&config).map_err(|err| StdError::generic_err(err.to_string()))?;
+    let state = vesting_state(config.extensions.historical)
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
+    iter::once(Ok(VestingAssetState {
+        asset_info: vesting_token,
+        state,
+    }))
+    .chain(
+        VESTING_ASSET_STATES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, asset_state)| asset_state)),
+    )
+    .collect()
+}
+
 /// Return the current block timestamp (in seconds)
 /// * **env** is an object of type [`Env`].
 fn query_timestamp(env: Env) -> StdResult<u64> {
@@ -373,14 +1586,141 @@ fn query_vesting_available_amount(deps: Deps, env: Env, address: String) -> StdR
     let address = deps.api.addr_validate(&address)?;
 
     let config = CONFIG.load(deps.storage)?;
-    let info = vesting_info(config.extensions.historical).load(deps.storage, address)?;
-    let available_amount = compute_available_amount(env.block.time.seconds(), &info)?;
+    let info = vesting_info(config.extensions.historical).load(deps.storage, address.clone())?;
+    let vesting_time = vesting_time(deps.storage, address.clone(), env.block.time.seconds())?;
+    let staked_amount = staked_amount(deps.storage, address, env.block.time.seconds())?;
+    let available_amount = compute_available_amount(vesting_time, &info, None, staked_amount)?;
     Ok(available_amount)
 }
 
-/// Manages contract migration.
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+/// Returns the available amounts of all the vested and yet to be claimed assets for a specific
+/// vesting recipient, starting with the vesting token.
+///
+/// * **address** vesting recipient for which to return the available amounts of assets to claim.
+fn query_vesting_available_amounts(deps: Deps, env: Env, address: String) -> StdResult<Vec<Asset>> {
+    let address = deps.api.addr_validate(&address)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let vesting_token =
+        get_vesting_token(&config).map_err(|err| StdError::generic_err(err.to_string()))?;
+    let info = vesting_info(config.extensions.historical).load(deps.storage, address.clone())?;
+    let vesting_time = vesting_time(deps.storage, address.clone(), env.block.time.seconds())?;
+    let staked_amount = staked_amount(deps.storage, address, env.block.time.seconds())?;
+
+    iter::once(None)
+        .chain(info.additional_asset_infos().into_iter().map(Some))
+        .map(|asset_info| {
+            let staked_amount = match asset_info {
+                None => staked_amount,
+                Some(_) => Uint128::zero(),
+            };
+            let available_amount =
+                compute_available_amount(vesting_time, &info, asset_info.as_ref(), staked_amount)?;
+            Ok(asset_info
+                .unwrap_or_else(|| vesting_token.clone())
+                .with_balance(available_amount))
+        })
+        .collect()
+}
+
+/// Returns the cumulative amount of vesting tokens unlocked for a specific vesting recipient at
+/// every sample point between two timestamps. The already claimed tokens are included in the amounts.
+///
+/// * **address** vesting recipient for which to return the timeline.
+///
+/// * **from** timestamp of the first sample point.
+///
+/// * **to** timestamp of the last sample point.
+///
+/// * **step** interval between two adjacent sample points. The last interval can be shorter.
+fn query_vesting_timeline(
+    deps: Deps,
+    address: String,
+    from: u64,
+    to: u64,
+    step: u64,
+) -> StdResult<Vec<VestingTimelinePoint>> {
+    if step == 0 || from > to {
+        return Err(StdError::generic_err(
+            "Vesting timeline should satisfy: step > 0 and from <= to",
+        ));
+    }
+    let num_steps = (to - from) / step;
+    let partial_step = (to - from) % step != 0;
+    if num_steps + u64::from(partial_step) >= MAX_TIMELINE_POINTS {
+        return Err(StdError::generic_err(format!(
+            "Vesting timeline can't contain more than {} points",
+            MAX_TIMELINE_POINTS
+        )));
+    }
+    let address = deps.api.addr_validate(&address)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let info = vesting_info(config.extensions.historical).load(deps.storage, address.clone())?;
+    (0..=num_steps)
+        .map(|i| from + i * step)
+        .chain(partial_step.then_some(to))
+        .map(|time| {
+            let vesting_time = vesting_time(deps.storage, address.clone(), time)?;
+            Ok(VestingTimelinePoint {
+                time,
+                unlocked_amount: compute_unlocked_amount(vesting_time, &info, None)?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the total amount of vesting tokens unlocked between two timestamps across a page of vesting
+/// recipients. The amounts of all pages should be summed up to get the contract-wide forecast.
+///
+/// * **from** timestamp from which to count the unlocked tokens.
+///
+/// * **to** timestamp until which to count the unlocked tokens.
+///
+/// * **start_after** index from which to start reading vesting recipients.
+///
+/// * **limit** amount of vesting recipients to read.
+fn query_unlock_forecast(
+    deps: Deps,
+    from: u64,
+    to: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<UnlockForecastResponse> {
+    if from > to {
+        return Err(StdError::generic_err(
+            "Unlock forecast should satisfy: from <= to",
+        ));
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = addr_opt_validate(deps.api, &start_after)?.map(Bound::exclusive);
+
+    let mut amount = Uint128::zero();
+    let mut last_address = None;
+    for item in vesting_info(config.extensions.historical)
+        .range(deps.storage, start_after, None, Order::Ascending)
+        .take(limit)
+    {
+        let (address, info) = item?;
+        let unlocked_from = compute_unlocked_amount(
+            vesting_time(deps.storage, address.clone(), from)?,
+            &info,
+            None,
+        )?;
+        let unlocked_to = compute_unlocked_amount(
+            vesting_time(deps.storage, address.clone(), to)?,
+            &info,
+            None,
+        )?;
+        amount = amount.checked_add(unlocked_to.checked_sub(unlocked_from)?)?;
+        last_address = Some(address);
+    }
+
+    Ok(UnlockForecastResponse {
+        amount,
+        last_address,
+    })
 }
 
 fn is_sender_whitelisted(store: &mut dyn Storage, config: &Config, sender: &Addr) -> bool {
@@ -409,6 +1749,48 @@ pub fn assert_vesting_schedules(
                 return Err(ContractError::VestingScheduleError(addr.to_string()));
             }
         }
+
+        if let Some(intermediate_points) = &sch.intermediate_points {
+            let end_point = sch.end_point.as_ref().ok_or_else(|| {
+                ContractError::VestingScheduleIntermediatePointsError(addr.to_string())
+            })?;
+            let mut prev_point = &sch.start_point;
+            for point in intermediate_points.iter().chain(iter::once(end_point)) {
+                if point.time < prev_point.time || point.amount < prev_point.amount {
+                    return Err(ContractError::VestingScheduleIntermediatePointsError(
+                        addr.to_string(),
+                    ));
+                }
+                prev_point = point;
+            }
+        }
+
+        if let Some(periodic) = &sch.periodic {
+            if periodic.period == 0
+                || periodic.num_periods == 0
+                || periodic.amount_per_period.is_zero()
+                || sch.end_point.is_some()
+                || sch.intermediate_points.is_some()
+            {
+                return Err(ContractError::VestingSchedulePeriodicError(
+                    addr.to_string(),
+                ));
+            }
+        }
+
+        if let Some(cliff) = sch.cliff {
+            let end_time = match (&sch.end_point, &sch.periodic) {
+                (Some(end_point), _) => end_point.time,
+                (None, Some(periodic)) => sch
+                    .start_point
+                    .time
+                    .saturating_add(periodic.period.saturating_mul(periodic.num_periods)),
+                (None, None) => sch.start_point.time,
+            };
+            if cliff < sch.start_point.time || cliff > end_time {
+                return Err(ContractError::VestingScheduleCliffError(addr.to_string()));
+            }
+        }
     }
 
     Ok(())
@@ -418,33 +1800,163 @@ pub fn assert_vesting_schedules(
 /// Returns the computed amount if the operation is successful.
 ///
 /// * **current_time** timestamp from which to start querying for vesting schedules.
-/// Schedules that started later than current_time will be omitted.
+/// Schedules that started later than current_time or whose cliff is not reached yet will be omitted.
 ///
 /// * **vesting_info** vesting schedules for which to compute the amount of tokens
 /// that are vested and can be claimed by the recipient.
-fn compute_available_amount(current_time: u64, vesting_info: &VestingInfo) -> StdResult<Uint128> {
-    let mut available_amount: Uint128 = Uint128::zero();
-    for sch in &vesting_info.schedules {
-        if sch.start_point.time > current_time {
+///
+/// * **asset_info** asset for which to compute the amount. `None` stands for the vesting token.
+///
+/// * **staked_amount** amount of tokens of the recipient that are delegated or still unbonding.
+/// These tokens are not in the contract's balance, so they are never available.
+fn compute_available_amount(
+    current_time: u64,
+    vesting_info: &VestingInfo,
+    asset_info: Option<&AssetInfo>,
+    staked_amount: Uint128,
+) -> StdResult<Uint128> {
+    // slashed tokens are paid from the next unlocks, so they may exceed the unlocked amount
+    let spent_amount = vesting_info.spent_amount_of(asset_info)?;
+    let available_amount = compute_unlocked_amount(current_time, vesting_info, asset_info)?
+        .saturating_sub(spent_amount);
+    if staked_amount.is_zero() {
+        return Ok(available_amount);
+    }
+
+    let liquid_amount = compute_granted_amount(vesting_info, asset_info)?
+        .saturating_sub(spent_amount)
+        .saturating_sub(staked_amount);
+    Ok(available_amount.min(liquid_amount))
+}
+
+/// Computes the amount of tokens granted by all the vesting schedules of a specific vesting
+/// recipient.
+///
+/// * **vesting_info** vesting schedules for which to compute the granted amount.
+///
+/// * **asset_info** asset for which to compute the amount. `None` stands for the vesting token.
+pub(crate) fn compute_granted_amount(
+    vesting_info: &VestingInfo,
+    asset_info: Option<&AssetInfo>,
+) -> StdResult<Uint128> {
+    let mut granted_amount = Uint128::zero();
+    for sch in asset_schedules(vesting_info, asset_info) {
+        granted_amount = granted_amount.checked_add(compute_total_amount(sch)?)?;
+    }
+
+    Ok(granted_amount)
+}
+
+/// Computes the amount of tokens vested by all the vesting schedules of a specific vesting
+/// recipient, regardless of whether they were already claimed.
+///
+/// * **current_time** timestamp for which to compute the vested amount.
+///
+/// * **vesting_info** vesting schedules for which to compute the vested amount.
+///
+/// * **asset_info** asset for which to compute the amount. `None` stands for the vesting token.
+fn compute_unlocked_amount(
+    current_time: u64,
+    vesting_info: &VestingInfo,
+    asset_info: Option<&AssetInfo>,
+) -> StdResult<Uint128> {
+    let mut unlocked_amount: Uint128 = Uint128::zero();
+    for sch in asset_schedules(vesting_info, asset_info) {
+        unlocked_amount = unlocked_amount.checked_add(compute_vested_amount(current_time, sch)?)?;
+    }
+
+    Ok(unlocked_amount)
+}
+
+/// Returns the vesting schedules of a specific vesting recipient that vest an asset.
+///
+/// * **vesting_info** vesting schedules to filter.
+///
+/// * **asset_info** asset to filter the schedules by. `None` stands for the vesting token.
+pub(crate) fn asset_schedules<'a>(
+    vesting_info: &'a VestingInfo,
+    asset_info: Option<&'a AssetInfo>,
+) -> impl Iterator<Item = &'a VestingSchedule> {
+    vesting_info
+        .schedules
+        .iter()
+        .filter(move |sch| sch.asset_info.as_ref() == asset_info)
+}
+
+/// Computes the amount of tokens vested by a single vesting schedule at a specific time,
+/// regardless of whether they were already claimed.
+///
+/// * **current_time** timestamp for which to compute the vested amount.
+///
+/// * **sch** vesting schedule for which to compute the vested amount.
+pub(crate) fn compute_vested_amount(
+    current_time: u64,
+    sch: &VestingSchedule,
+) -> StdResult<Uint128> {
+    if sch.start_point.time > current_time {
+        return Ok(Uint128::zero());
+    }
+
+    // Nothing is unlocked before the cliff. After the cliff the schedule is computed as if
+    // there were no cliff, so everything accrued since the start point is unlocked at once.
+    if sch.cliff.map_or(false, |cliff| cliff > current_time) {
+        return Ok(Uint128::zero());
+    }
+
+    // Only whole tranches are unlocked for periodic schedules
+    if let Some(periodic) = &sch.periodic {
+        let passed_periods =
+            ((current_time - sch.start_point.time) / periodic.period).min(periodic.num_periods);
+        let released_amount = periodic
+            .amount_per_period
+            .checked_mul(Uint128::from(passed_periods))?;
+        return Ok(sch.start_point.amount.checked_add(released_amount)?);
+    }
+
+    let end_point = match &sch.end_point {
+        Some(end_point) => end_point,
+        None => return Ok(sch.start_point.amount),
+    };
+
+    // The schedule is a piecewise-linear curve going through the start point, the intermediate
+    // points and the end point. Points sharing the same time form a step.
+    let points: Vec<&VestingSchedulePoint> = iter::once(&sch.start_point)
+        .chain(sch.intermediate_points.iter().flatten())
+        .chain(iter::once(end_point))
+        .collect();
+
+    let mut vested_amount = sch.start_point.amount;
+    for segment in points.windows(2) {
+        let (from, to) = (segment[0], segment[1]);
+        if current_time >= to.time {
+            vested_amount = to.amount;
             continue;
         }
 
-        available_amount = available_amount.checked_add(sch.start_point.amount)?;
+        let passed_time = current_time - from.time;
+        let time_period = to.time - from.time;
+        let release_amount = Uint128::from(passed_time)
+            .multiply_ratio(to.amount.checked_sub(from.amount)?, time_period);
+        vested_amount = from.amount.checked_add(release_amount)?;
+        break;
+    }
 
-        if let Some(end_point) = &sch.end_point {
-            let passed_time = current_time.min(end_point.time) - sch.start_point.time;
-            let time_period = end_point.time - sch.start_point.time;
-            if passed_time != 0 && time_period != 0 {
-                let release_amount = Uint128::from(passed_time).multiply_ratio(
-                    end_point.amount.checked_sub(sch.start_point.amount)?,
-                    time_period,
-                );
-                available_amount = available_amount.checked_add(release_amount)?;
-            }
-        }
+    Ok(vested_amount)
+}
+
+/// Computes the total amount of tokens granted by a single vesting schedule.
+///
+/// * **sch** vesting schedule for which to compute the total amount.
+pub fn compute_total_amount(sch: &VestingSchedule) -> StdResult<Uint128> {
+    if let Some(periodic) = &sch.periodic {
+        let periodic_amount = periodic
+            .amount_per_period
+            .checked_mul(Uint128::from(periodic.num_periods))?;
+        return Ok(sch.start_point.amount.checked_add(periodic_amount)?);
     }
 
-    available_amount
-        .checked_sub(vesting_info.released_amount)
-        .map_err(StdError::from)
+    Ok(sch
+        .end_point
+        .as_ref()
+        .map_or(sch.start_point.amount, |end_point| end_point.amount))
 }
@@ -1,13 +1,18 @@
 pub mod builder;
 pub mod error;
 pub mod handlers;
+pub mod migration;
 pub mod msg;
 pub mod state;
 pub mod types;
 
 pub(crate) mod ext_historical;
 pub(crate) mod ext_managed;
+pub mod ext_pausable;
+pub(crate) mod ext_staking;
+pub(crate) mod ext_voting;
 pub(crate) mod ext_with_managers;
+pub(crate) mod ext_with_operators;
 
 #[cfg(test)]
 mod testing;
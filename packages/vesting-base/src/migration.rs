@@ -0,0 +1,137 @@
+use crate::error::ContractError;
+use crate::state::{CONFIG, DAO, GUARDIAN, PAUSED};
+use crate::types::ExtensionsMigration;
+use cosmwasm_std::{attr, DepsMut, Env, Response, StdError};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+
+/// A migration step that brings the contract state up to date with a specific version.
+type MigrationStep<'a> = Box<dyn FnOnce(DepsMut, &Env) -> Result<(), ContractError> + 'a>;
+
+/// A builder for migrations of vesting contracts between versions.
+pub struct VestingMigrationBuilder<'a> {
+    contract_name: &'a str,
+    contract_version: &'a str,
+    steps: Vec<(&'a str, MigrationStep<'a>)>,
+}
+
+impl<'a> VestingMigrationBuilder<'a> {
+    /// Creates a migration to a specific version of a contract.
+    ///
+    /// * **contract_name** the name of the contract stored with `cw2`.
+    ///
+    /// * **contract_version** the version the contract is migrated to.
+    pub fn new(contract_name: &'a str, contract_version: &'a str) -> Self {
+        VestingMigrationBuilder {
+            contract_name,
+            contract_version,
+            steps: vec![],
+        }
+    }
+
+    /// Registers a migration step. The step runs when the contract is migrated from a version
+    /// older than the step version to the step version or a newer one.
+    ///
+    /// * **version** the version that requires the step.
+    ///
+    /// * **step** the function that brings the contract state up to date.
+    pub fn step(
+        &mut self,
+        version: &'a str,
+        step: impl FnOnce(DepsMut, &Env) -> Result<(), ContractError> + 'a,
+    ) -> &mut VestingMigrationBuilder<'a> {
+        self.steps.push((version, Box::new(step)));
+        self
+    }
+
+    /// Validates the stored contract version, runs the required migration steps in the order of
+    /// their versions, applies the extension changes and stores the new contract version.
+    /// Disabling the pausable extension resumes the contract and removes its guardian. Enabling
+    /// the voting extension stores the DAO, which may replace the current one.
+    ///
+    /// * **extensions** the extensions to enable or disable.
+    pub fn migrate(
+        &mut self,
+        mut deps: DepsMut,
+        env: Env,
+        extensions: ExtensionsMigration,
+    ) -> Result<Response, ContractError> {
+        let stored = get_contract_version(deps.storage)?;
+        if stored.contract != self.contract_name {
+            return Err(ContractError::MigrationContractError {
+                expected: self.contract_name.to_string(),
+                actual: stored.contract,
+            });
+        }
+
+        let from_version: Version = stored.version.parse()?;
+        let to_version: Version = self.contract_version.parse()?;
+        if to_version < from_version {
+            return Err(ContractError::MigrationDowngradeError {
+                from: stored.version,
+                to: self.contract_version.to_string(),
+            });
+        }
+
+        let mut steps = vec![];
+        for (version, step) in self.steps.drain(..) {
+            let version: Version = version.parse()?;
+            if from_version < version && version <= to_version {
+                steps.push((version, step));
+            }
+        }
+        steps.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (_, step) in steps {
+            step(deps.branch(), &env)?;
+        }
+
+        let dao = extensions
+            .voting
+            .map(|dao| deps.api.addr_validate(&dao))
+            .transpose()?;
+        CONFIG.update::<_, ContractError>(deps.storage, |mut config| {
+            let extensions_config = &mut config.extensions;
+            if dao.is_some() {
+                if !extensions_config.historical {
+                    return Err(StdError::generic_err(
+                        "The voting extension requires the historical extension",
+                    )
+                    .into());
+                }
+                extensions_config.voting = true;
+            }
+            if let Some(managed) = extensions.managed {
+                extensions_config.managed = managed;
+            }
+            if let Some(with_managers) = extensions.with_managers {
+                extensions_config.with_managers = with_managers;
+            }
+            if let Some(transferable) = extensions.transferable {
+                extensions_config.transferable = transferable;
+            }
+            if let Some(with_operators) = extensions.with_operators {
+                extensions_config.with_operators = with_operators;
+            }
+            if let Some(pausable) = extensions.pausable {
+                extensions_config.pausable = pausable;
+            }
+            Ok(config)
+        })?;
+        if let Some(dao) = dao {
+            DAO.save(deps.storage, &dao)?;
+        }
+        // a contract can't be resumed without the pausable extension, so it's never left paused
+        if extensions.pausable == Some(false) {
+            PAUSED.remove(deps.storage);
+            GUARDIAN.remove(deps.storage);
+        }
+
+        set_contract_version(deps.storage, self.contract_name, self.contract_version)?;
+
+        Ok(Response::new().add_attributes(vec![
+            attr("action", "migrate"),
+            attr("from_version", stored.version),
+            attr("to_version", self.contract_version),
+        ]))
+    }
+}
@@ -1,10 +1,18 @@
 use crate::types::{
-    Config, OrderBy, VestingAccount, VestingAccountResponse, VestingAccountsResponse, VestingState,
+    Config, ExtensionsMigration, InfoResponse, IsPausedResponse, MerkleRoot, MerkleRootResponse,
+    OperatorApprovalResponse, OrderBy, StakingInfoResponse, TemplateVestingAccount,
+    TotalPowerAtHeightResponse, UnclaimedAmountsAtHeightResponse, UnlockForecastResponse,
+    VestingAccount, VestingAccountResponse, VestingAccountsResponse, VestingAssetState,
+    VestingInfoHistoryEntry, VestingScheduleTemplate, VestingState,
+    VestingStateReconciliationResponse, VestingTemplateResponse, VestingTerminationResponse,
+    VestingTimelinePoint, VotingPowerAtHeightResponse,
 };
-use astroport::asset::AssetInfo;
+use astroport::asset::{Asset, AssetInfo};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, Binary, Uint128};
+use cw2::ContractVersion;
 use cw20::Cw20ReceiveMsg;
+use cw_utils::Expiration;
 
 /// This structure describes the execute messages available in a vesting contract.
 #[cw_serde]
@@ -16,12 +24,112 @@ pub enum ExecuteMsg {
         /// The amount of tokens to claim
         amount: Option<Uint128>,
     },
+    /// Claims vested tokens of a specific asset and sends them to a recipient
+    ClaimAsset {
+        /// The asset to claim
+        asset_info: AssetInfo,
+        /// The address that receives the vested tokens
+        recipient: Option<String>,
+        /// The amount of tokens to claim
+        amount: Option<Uint128>,
+    },
+    /// Claims the available tokens of all the assets of multiple vesting accounts and sends them
+    /// to the accounts. Addresses without a vesting account are skipped.
+    /// The number of addresses per call is limited by the claim_for limit.
+    /// ## Executor
+    /// Only the current owner or a vesting manager can execute this
+    ClaimFor { addresses: Vec<String> },
+    /// Sets the maximum number of addresses a single ClaimFor message can process
+    /// ## Executor
+    /// Only the current owner can execute this
+    SetClaimForLimit { limit: u32 },
     /// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template
     Receive(Cw20ReceiveMsg),
     /// RegisterVestingAccounts registers vesting targets/accounts
     RegisterVestingAccounts {
         vesting_accounts: Vec<VestingAccount>,
     },
+    /// Changes the end amount and/or the end time of a vesting schedule of an existing vesting
+    /// account. An increase of the end amount should be funded with the native tokens sent along.
+    /// ## Executor
+    /// Only the current owner or a vesting manager can execute this
+    AmendVestingAccount {
+        /// The address of the vesting account
+        address: String,
+        /// The index of the schedule in the list of the account's vesting schedules
+        schedule_index: u32,
+        /// The new amount of tokens vested at the end point
+        end_amount: Option<Uint128>,
+        /// The new time of the end point
+        end_time: Option<u64>,
+    },
+    /// Registers vesting accounts with schedules computed from templates. The native tokens
+    /// sent along should match the total amount of the accounts.
+    /// ## Executor
+    /// Only the current owner or a vesting manager can execute this
+    RegisterTemplateVestingAccounts {
+        vesting_accounts: Vec<TemplateVestingAccount>,
+    },
+    /// Creates or replaces a vesting schedule template. The schedules already registered with
+    /// the template are not changed.
+    /// ## Executor
+    /// Only the current owner can execute this
+    SetVestingTemplate {
+        template_id: String,
+        template: VestingScheduleTemplate,
+    },
+    /// Removes a vesting schedule template.
+    /// ## Executor
+    /// Only the current owner can execute this
+    RemoveVestingTemplate { template_id: String },
+    /// Overwrites the vesting state of the vesting token, e.g. to reconcile it with the vesting
+    /// accounts after an accounting error.
+    /// ## Executor
+    /// Only the current owner can execute this
+    RepairVestingState {
+        total_granted: Uint128,
+        total_released: Uint128,
+    },
+    /// Registers a Merkle root of vesting accounts funded with the native tokens sent along.
+    /// Every leaf of the tree describes a vesting account as `(address, template_id, amount)`.
+    /// ## Executor
+    /// Only the current owner or a vesting manager can execute this
+    RegisterMerkleRoot {
+        /// The hex-encoded root of the Merkle tree
+        merkle_root: String,
+        /// The names of the templates used by the leaves. The templates are copied to the root
+        template_ids: Vec<String>,
+        /// The start time of the vesting schedules. Defaults to the current block time
+        start_time: Option<u64>,
+        /// The time after which the accounts can't be registered from the root anymore
+        expiration: u64,
+    },
+    /// Registers the sender's vesting account from a Merkle root and claims the tokens
+    /// vested so far.
+    /// ## Executor
+    /// Only the vesting account owner can execute this
+    ClaimWithMerkleProof {
+        /// The identifier of the Merkle root
+        root_id: u64,
+        /// The name of the template of the vesting schedule
+        template_id: String,
+        /// The total amount of tokens vested by the schedule
+        amount: Uint128,
+        /// The hex-encoded hashes proving that the account is a leaf of the tree
+        proof: Vec<String>,
+        /// The address that receives the vested tokens
+        recipient: Option<String>,
+    },
+    /// Sends the part of the deposit of an expired Merkle root that wasn't registered by the
+    /// beneficiaries.
+    /// ## Executor
+    /// Only the current owner can execute this
+    WithdrawMerkleRootRemainder {
+        /// The identifier of the Merkle root
+        root_id: u64,
+        /// The address that receives the tokens. Defaults to the owner
+        recipient: Option<String>,
+    },
     /// Creates a request to change contract ownership
     /// ## Executor
     /// Only the current owner can execute this
@@ -43,12 +151,24 @@ pub enum ExecuteMsg {
     /// ## Executor
     /// Only the current owner or token info manager can execute this
     SetVestingToken { vesting_token: AssetInfo },
+    /// Moves the sender's vesting schedules and released amount to another address. If the
+    /// address already vests tokens, the vesting information of both accounts is merged.
+    /// Requires the transferable extension to be enabled.
+    /// ## Executor
+    /// Only the vesting account owner can execute this
+    TransferVestingAccount { new_address: String },
     /// Contains messages associated with the managed extension for vesting contracts.
     ManagedExtension { msg: ExecuteMsgManaged },
     /// Contains messages associated with the with_managers extension for vesting contracts.
     WithManagersExtension { msg: ExecuteMsgWithManagers },
     /// Contains messages associated with the historical extension for vesting contracts.
     HistoricalExtension { msg: ExecuteMsgHistorical },
+    /// Contains messages associated with the with_operators extension for vesting contracts.
+    WithOperatorsExtension { msg: ExecuteMsgWithOperators },
+    /// Contains messages associated with the staking extension for vesting contracts.
+    StakingExtension { msg: ExecuteMsgStaking },
+    /// Contains messages associated with the pausable extension for vesting contracts.
+    PausableExtension { msg: ExecuteMsgPausable },
 }
 
 /// This structure describes the execute messages available in a managed vesting contract.
@@ -62,6 +182,35 @@ pub enum ExecuteMsgManaged {
         /// Specifies the account that will receive the funds taken from the vesting accounts.
         clawback_account: String,
     },
+    /// Claws back the still vesting tokens of vesting targets/accounts. The already vested
+    /// tokens stay claimable by the accounts.
+    /// ## Executor
+    /// Only the current owner can execute this
+    TruncateVestingAccounts {
+        vesting_accounts: Vec<String>,
+        /// Specifies the account that will receive the funds taken from the vesting accounts.
+        clawback_account: String,
+    },
+    /// Schedules a termination of a vesting target/account. After the termination time the
+    /// account's tokens stop vesting, and the ones that haven't vested can be clawed back.
+    /// ## Executor
+    /// Only the current owner can execute this
+    ScheduleVestingTermination {
+        vesting_account: String,
+        /// The time after which the tokens of the vesting account stop vesting
+        termination_time: u64,
+        /// Specifies the account that will receive the funds taken from the vesting account.
+        clawback_account: String,
+    },
+    /// Cancels a scheduled termination of a vesting target/account.
+    /// ## Executor
+    /// Only the current owner can execute this
+    CancelVestingTermination { vesting_account: String },
+    /// Claws back the tokens that haven't vested before the termination time of the terminated
+    /// vesting targets/accounts. The already vested tokens stay claimable by the accounts.
+    /// ## Executor
+    /// Only the current owner or the clawback account of a termination can execute this
+    ClawBackTerminatedAccounts { vesting_accounts: Vec<String> },
 }
 
 /// This structure describes the execute messages available in a with_managers vesting contract.
@@ -81,6 +230,86 @@ pub enum ExecuteMsgWithManagers {
 #[cw_serde]
 pub enum ExecuteMsgHistorical {}
 
+/// This structure describes the execute messages available in a with_operators vesting contract.
+#[cw_serde]
+pub enum ExecuteMsgWithOperators {
+    /// Allows an operator to claim the sender's vested tokens on the sender's behalf.
+    /// ## Executor
+    /// Only the vesting account owner can execute this
+    ApproveOperator {
+        operator: String,
+        /// The moment after which the approval is no longer valid. Never expires by default.
+        expires: Option<Expiration>,
+        /// The address besides the vesting account the operator is allowed to send the claimed tokens to.
+        recipient: Option<String>,
+    },
+    /// Revokes an operator's approval.
+    /// ## Executor
+    /// Only the vesting account owner can execute this
+    RevokeOperator { operator: String },
+    /// Claims vested tokens on behalf of a vesting account.
+    /// ## Executor
+    /// Only an approved operator of the vesting account can execute this
+    ClaimOnBehalf {
+        address: String,
+        /// The asset to claim. Defaults to the vesting token
+        asset: Option<AssetInfo>,
+        /// The address that receives the tokens. Either the vesting account or the approved
+        /// recipient. Defaults to the vesting account.
+        recipient: Option<String>,
+        /// The amount of tokens to claim
+        amount: Option<Uint128>,
+    },
+}
+
+/// This structure describes the execute messages available in a staking vesting contract.
+#[cw_serde]
+pub enum ExecuteMsgStaking {
+    /// Delegates unclaimed vesting tokens of the sender to a validator.
+    /// ## Executor
+    /// Only the vesting account owner can execute this
+    Delegate { validator: String, amount: Uint128 },
+    /// Moves delegated tokens of the sender from one validator to another.
+    /// ## Executor
+    /// Only the vesting account owner can execute this
+    Redelegate {
+        src_validator: String,
+        dst_validator: String,
+        amount: Uint128,
+    },
+    /// Undelegates tokens of the sender from a validator. The tokens can't be claimed until
+    /// the unbonding period ends.
+    /// ## Executor
+    /// Only the vesting account owner can execute this
+    Undelegate { validator: String, amount: Uint128 },
+    /// Sends the staking rewards accrued by the sender's delegations to the sender.
+    /// ## Executor
+    /// Only the vesting account owner can execute this
+    ClaimRewards {},
+    /// Undelegates all the tokens of a vesting account and forbids the account to delegate
+    /// again, so that its tokens can be clawed back once the unbonding period ends.
+    /// ## Executor
+    /// Only the contract owner can execute this
+    ForceUndelegate { vesting_account: String },
+}
+
+/// This structure describes the execute messages available in a pausable vesting contract.
+#[cw_serde]
+pub enum ExecuteMsgPausable {
+    /// Freezes claims, registrations, transfers, staking and liquidity migrations.
+    /// ## Executor
+    /// Only the current owner or the guardian can execute this
+    Pause {},
+    /// Lifts the freeze set by the Pause message.
+    /// ## Executor
+    /// Only the current owner or the guardian can execute this
+    Resume {},
+    /// Sets or removes the guardian that can pause and resume the contract besides the owner.
+    /// ## Executor
+    /// Only the current owner can execute this
+    SetGuardian { guardian: Option<String> },
+}
+
 /// This structure describes the query messages available in a vesting contract.
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -101,12 +330,88 @@ pub enum QueryMsg {
     /// Returns the total unvested amount of tokens for a specific address.
     #[returns(Uint128)]
     AvailableAmount { address: String },
+    /// Returns the available amounts of all the assets vested for a specific address,
+    /// starting with the vesting token.
+    #[returns(Vec<Asset>)]
+    AvailableAmounts { address: String },
+    /// Returns the cumulative amount of tokens unlocked for a specific address at every `step`
+    /// seconds from `from` to `to`, including `to` itself. At most 100 points can be returned.
+    #[returns(Vec<VestingTimelinePoint>)]
+    VestingTimeline {
+        address: String,
+        from: u64,
+        to: u64,
+        step: u64,
+    },
     /// Timestamp returns the current timestamp
     #[returns(u64)]
     Timestamp {},
     /// VestingState returns the current vesting state.
     #[returns(VestingState)]
     VestingState {},
+    /// Returns the vesting state of every vested asset, starting with the vesting token.
+    #[returns(Vec<VestingAssetState>)]
+    VestingStates {},
+    /// Returns the total amount of tokens unlocking between `from` and `to` across a page of
+    /// vesting accounts ordered by address. Summing up all the pages gives the contract-wide amount.
+    #[returns(UnlockForecastResponse)]
+    UnlockForecast {
+        from: u64,
+        to: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the maximum number of addresses a single ClaimFor message can process.
+    #[returns(u32)]
+    ClaimForLimit {},
+    /// Returns a vesting schedule template.
+    #[returns(VestingScheduleTemplate)]
+    VestingTemplate { template_id: String },
+    /// Returns a list of vesting schedule templates ordered by name.
+    #[returns(Vec<VestingTemplateResponse>)]
+    VestingTemplates {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the vesting state of the vesting token along with the amounts granted and released
+    /// to a page of vesting accounts ordered by address. Summing up the amounts of all the pages
+    /// should give the totals of the vesting state.
+    #[returns(VestingStateReconciliationResponse)]
+    VestingStateReconciliation {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns a Merkle root of vesting accounts.
+    #[returns(MerkleRoot)]
+    MerkleRoot { root_id: u64 },
+    /// Returns a list of Merkle roots of vesting accounts ordered by identifier.
+    #[returns(Vec<MerkleRootResponse>)]
+    MerkleRoots {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns whether a vesting account is registered from a Merkle root.
+    #[returns(bool)]
+    IsMerkleRegistered { root_id: u64, address: String },
+    /// Returns the voting power of a specific address at certain height. Available with the voting
+    /// extension.
+    #[returns(VotingPowerAtHeightResponse)]
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    /// Returns the total voting power at certain height. Available with the voting extension.
+    #[returns(TotalPowerAtHeightResponse)]
+    TotalPowerAtHeight { height: Option<u64> },
+    /// Returns the contract name and version. Available with the voting extension.
+    #[returns(InfoResponse)]
+    Info {},
+    /// Returns the address of the DAO. Available with the voting extension.
+    #[returns(Addr)]
+    Dao {},
+    /// Returns the name and the version of the contract.
+    #[returns(ContractVersion)]
+    ContractVersion {},
     /// Contains messages associated with the managed extension for vesting contracts.
     #[returns(Binary)]
     ManagedExtension { msg: QueryMsgManaged },
@@ -116,12 +421,28 @@ pub enum QueryMsg {
     /// Contains messages associated with the historical extension for vesting contracts.
     #[returns(QueryMsgHistorical)]
     HistoricalExtension { msg: QueryMsgHistorical },
+    /// Contains messages associated with the with_operators extension for vesting contracts.
+    #[returns(QueryMsgWithOperators)]
+    WithOperatorsExtension { msg: QueryMsgWithOperators },
+    /// Contains messages associated with the staking extension for vesting contracts.
+    #[returns(QueryMsgStaking)]
+    StakingExtension { msg: QueryMsgStaking },
+    /// Contains messages associated with the pausable extension for vesting contracts.
+    #[returns(QueryMsgPausable)]
+    PausableExtension { msg: QueryMsgPausable },
 }
 
 /// This structure describes the query messages available in a managed vesting contract.
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsgManaged {}
+pub enum QueryMsgManaged {
+    /// Returns a list of scheduled vesting terminations.
+    #[returns(Vec<VestingTerminationResponse>)]
+    PendingTerminations {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
 
 /// This structure describes the query messages available in a with_managers vesting contract.
 #[cw_serde]
@@ -143,12 +464,66 @@ pub enum QueryMsgHistorical {
     /// Returns the total unclaimed amount of tokens for all the users at certain height.
     #[returns(Uint128)]
     UnclaimedTotalAmountAtHeight { height: u64 },
+    /// Returns the unclaimed amounts of tokens for a page of addresses at certain height, ordered
+    /// by address.
+    #[returns(UnclaimedAmountsAtHeightResponse)]
+    UnclaimedAmountsAtHeight {
+        height: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the changes of the vesting information of a specific address, ordered by height.
+    #[returns(Vec<VestingInfoHistoryEntry>)]
+    VestingInfoHistory {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+/// This structure describes the query messages available in a with_operators vesting contract.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsgWithOperators {
+    /// Returns a list of operators approved by a vesting account.
+    #[returns(Vec<OperatorApprovalResponse>)]
+    Operators {
+        address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+/// This structure describes the query messages available in a staking vesting contract.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsgStaking {
+    /// Returns the delegations, the undelegations and the pending staking rewards of a vesting
+    /// account.
+    #[returns(StakingInfoResponse)]
+    StakingInfo { address: String },
+}
+
+/// This structure describes the query messages available in a pausable vesting contract.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsgPausable {
+    /// Returns whether the contract is paused.
+    #[returns(IsPausedResponse)]
+    IsPaused {},
+    /// Returns the guardian that can pause and resume the contract besides the owner.
+    #[returns(Option<Addr>)]
+    Guardian {},
 }
 
 /// This structure describes a migration message.
-/// We currently take no arguments for migrations.
 #[cw_serde]
-pub struct MigrateMsg {}
+#[derive(Default)]
+pub struct MigrateMsg {
+    /// The extensions to enable or disable
+    #[serde(default)]
+    pub extensions: ExtensionsMigration,
+}
 
 /// This structure describes a CW20 hook message.
 #[cw_serde]
@@ -157,4 +532,24 @@ pub enum Cw20HookMsg {
     RegisterVestingAccounts {
         vesting_accounts: Vec<VestingAccount>,
     },
+    /// AmendVestingAccount changes a vesting schedule of an existing vesting account, funding
+    /// an increase of its end amount with the received tokens
+    AmendVestingAccount {
+        address: String,
+        schedule_index: u32,
+        end_amount: Option<Uint128>,
+        end_time: Option<u64>,
+    },
+    /// RegisterTemplateVestingAccounts registers vesting targets/accounts with schedules
+    /// computed from templates
+    RegisterTemplateVestingAccounts {
+        vesting_accounts: Vec<TemplateVestingAccount>,
+    },
+    /// RegisterMerkleRoot registers a Merkle root of vesting accounts
+    RegisterMerkleRoot {
+        merkle_root: String,
+        template_ids: Vec<String>,
+        start_time: Option<u64>,
+        expiration: u64,
+    },
 }
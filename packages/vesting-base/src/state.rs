@@ -1,6 +1,13 @@
-use crate::types::{Config, OrderBy, VestingInfo, VestingState};
+use crate::error::ContractError;
+use crate::ext_voting::update_weighted_voting_state;
+use crate::types::{
+    Config, MerkleRoot, OperatorApproval, OrderBy, StakingDelegation, StakingUnbonding,
+    StakingValidator, VestingAssetState, VestingInfo, VestingScheduleTemplate, VestingState,
+    VestingTermination, WeightedVotingState,
+};
+use astroport::asset::AssetInfo;
 use astroport::common::OwnershipProposal;
-use cosmwasm_std::{Addr, Deps, StdResult};
+use cosmwasm_std::{Addr, Deps, StdResult, Storage, Uint128};
 use cw_storage_plus::{Bound, Item, Map, SnapshotItem, SnapshotMap, Strategy};
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -18,6 +25,40 @@ pub(crate) const VESTING_INFO: SnapshotMap<Addr, VestingInfo> = SnapshotMap::new
     "vesting_info__changelog",
     Strategy::Never,
 );
+pub(crate) const VESTING_TERMINATIONS: Map<Addr, VestingTermination> =
+    Map::new("vesting_terminations");
+/// Vesting states of the assets vested besides the vesting token, keyed by the asset.
+pub(crate) const VESTING_ASSET_STATES: Map<String, VestingAssetState> =
+    Map::new("vesting_asset_states");
+/// The unbonding period of the chain in seconds, set by the staking extension.
+pub(crate) const UNBONDING_PERIOD: Item<u64> = Item::new("unbonding_period");
+/// Delegations of the vesting accounts keyed by the vesting account and the validator address.
+pub(crate) const STAKING_DELEGATIONS: Map<(Addr, String), StakingDelegation> =
+    Map::new("staking_delegations");
+/// Delegations of all the vesting accounts keyed by the validator address.
+pub(crate) const STAKING_VALIDATORS: Map<String, StakingValidator> = Map::new("staking_validators");
+pub(crate) const STAKING_UNBONDINGS: Map<Addr, Vec<StakingUnbonding>> =
+    Map::new("staking_unbondings");
+/// Vesting accounts whose delegations were undelegated by the owner and which can't delegate
+/// until their vesting is truncated, removed or the termination is cancelled.
+pub(crate) const STAKING_FROZEN_ACCOUNTS: Map<Addr, bool> = Map::new("staking_frozen_accounts");
+/// Staking rewards distributed to the vesting accounts and not yet sent to them.
+pub(crate) const STAKING_REWARDS: Map<Addr, Uint128> = Map::new("staking_rewards");
+/// Vesting schedule templates keyed by their names.
+pub(crate) const VESTING_TEMPLATES: Map<String, VestingScheduleTemplate> =
+    Map::new("vesting_templates");
+/// Funded Merkle roots of vesting accounts keyed by their identifiers.
+pub(crate) const MERKLE_ROOTS: Map<u64, MerkleRoot> = Map::new("merkle_roots");
+/// The number of the registered Merkle roots, used as the identifier of the next one.
+pub(crate) const MERKLE_ROOT_COUNT: Item<u64> = Item::new("merkle_root_count");
+/// The vesting accounts registered from Merkle roots keyed by the root and the account address.
+pub(crate) const MERKLE_REGISTRATIONS: Map<(u64, Addr), ()> = Map::new("merkle_registrations");
+/// Whether the contract is paused, set by the pausable extension.
+pub(crate) const PAUSED: Item<bool> = Item::new("paused");
+/// The address that can pause and resume the contract besides the owner.
+pub const GUARDIAN: Item<Addr> = Item::new("guardian");
+/// Operator approvals keyed by the vesting account and the operator address.
+pub(crate) const OPERATORS: Map<(Addr, Addr), OperatorApproval> = Map::new("operators");
 pub(crate) const VESTING_STATE_HISTORICAL: SnapshotItem<VestingState> = SnapshotItem::new(
     "vesting_state",
     "vesting_state__checkpoints",
@@ -31,6 +72,22 @@ pub(crate) const VESTING_INFO_HISTORICAL: SnapshotMap<Addr, VestingInfo> = Snaps
     Strategy::EveryBlock,
 );
 
+/// The DAO the contract provides voting power to, set by the voting extension.
+pub const DAO: Item<Addr> = Item::new("dao");
+pub(crate) const WEIGHTED_VOTING_STATE: SnapshotItem<WeightedVotingState> = SnapshotItem::new(
+    "weighted_voting_state",
+    "weighted_voting_state__checkpoints",
+    "weighted_voting_state__changelog",
+    Strategy::Never,
+);
+pub(crate) const WEIGHTED_VOTING_STATE_HISTORICAL: SnapshotItem<WeightedVotingState> =
+    SnapshotItem::new(
+        "weighted_voting_state",
+        "weighted_voting_state__checkpoints",
+        "weighted_voting_state__changelog",
+        Strategy::EveryBlock,
+    );
+
 pub fn vesting_state(historical: bool) -> SnapshotItem<'static, VestingState> {
     if historical {
         return VESTING_STATE_HISTORICAL;
@@ -45,8 +102,89 @@ pub fn vesting_info(historical: bool) -> SnapshotMap<'static, Addr, VestingInfo>
     VESTING_INFO
 }
 
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
+pub fn weighted_voting_state(historical: bool) -> SnapshotItem<'static, WeightedVotingState> {
+    if historical {
+        return WEIGHTED_VOTING_STATE_HISTORICAL;
+    }
+    WEIGHTED_VOTING_STATE
+}
+
+/// Saves the vesting information of an account and keeps the voting power of the accounts with
+/// weighted vesting schedules in sync with it.
+pub fn save_vesting_info(
+    storage: &mut dyn Storage,
+    historical: bool,
+    address: Addr,
+    info: &VestingInfo,
+    height: u64,
+) -> StdResult<()> {
+    let vesting_info = vesting_info(historical);
+    let old_info = vesting_info.may_load(storage, address.clone())?;
+    update_weighted_voting_state(storage, historical, old_info.as_ref(), Some(info), height)?;
+    vesting_info.save(storage, address, info, height)
+}
+
+/// Removes the vesting information of an account and keeps the voting power of the accounts with
+/// weighted vesting schedules in sync with it.
+pub fn remove_vesting_info(
+    storage: &mut dyn Storage,
+    historical: bool,
+    address: Addr,
+    height: u64,
+) -> StdResult<()> {
+    let vesting_info = vesting_info(historical);
+    let old_info = vesting_info.may_load(storage, address.clone())?;
+    update_weighted_voting_state(storage, historical, old_info.as_ref(), None, height)?;
+    vesting_info.remove(storage, address, height)
+}
+
+/// Updates the vesting state of an asset. `None` stands for the vesting token, the only asset
+/// whose vesting state is recorded by the historical extension.
+pub(crate) fn update_vesting_state<A>(
+    storage: &mut dyn Storage,
+    historical: bool,
+    asset_info: Option<&AssetInfo>,
+    height: u64,
+    action: A,
+) -> Result<VestingState, ContractError>
+where
+    A: FnOnce(Option<VestingState>) -> Result<VestingState, ContractError>,
+{
+    match asset_info {
+        None => vesting_state(historical).update(storage, height, action),
+        Some(asset_info) => {
+            let key = asset_info.to_string();
+            let state = action(
+                VESTING_ASSET_STATES
+                    .may_load(storage, key.clone())?
+                    .map(|asset_state| asset_state.state),
+            )?;
+            VESTING_ASSET_STATES.save(
+                storage,
+                key,
+                &VestingAssetState {
+                    asset_info: asset_info.clone(),
+                    state: state.clone(),
+                },
+            )?;
+            Ok(state)
+        }
+    }
+}
+
+pub(crate) const CLAIM_FOR_LIMIT: Item<u32> = Item::new("claim_for_limit");
+
+pub(crate) const DEFAULT_CLAIM_FOR_LIMIT: u32 = 30;
+pub(crate) const MAX_LIMIT: u32 = 30;
+pub(crate) const MAX_TIMELINE_POINTS: u64 = 100;
+pub(crate) const DEFAULT_LIMIT: u32 = 10;
+
+/// Returns the maximum number of addresses a single ClaimFor message can process.
+pub(crate) fn claim_for_limit(storage: &dyn Storage) -> StdResult<u32> {
+    Ok(CLAIM_FOR_LIMIT
+        .may_load(storage)?
+        .unwrap_or(DEFAULT_CLAIM_FOR_LIMIT))
+}
 
 /// Returns an empty vector if it does not find data, otherwise returns a vector that
 /// contains objects of type [`VESTING_INFO`].
@@ -98,6 +236,8 @@ mod testing {
         let vi_mock = VestingInfo {
             released_amount: Uint128::zero(),
             schedules: vec![],
+            released_assets: vec![],
+            slashed_amount: Uint128::zero(),
         };
 
         for i in 1..5 {
@@ -1,13 +1,33 @@
 use crate::builder::VestingBaseBuilder;
 use crate::error::{ext_unsupported_err, ContractError};
-use crate::handlers::{execute, query};
+use crate::handlers::{clear_vesting_account, execute, query};
+use crate::migration::VestingMigrationBuilder;
 use crate::msg::{
-    ExecuteMsg, ExecuteMsgManaged, QueryMsg, QueryMsgHistorical, QueryMsgWithManagers,
+    Cw20HookMsg, ExecuteMsg, ExecuteMsgManaged, ExecuteMsgPausable, ExecuteMsgWithOperators,
+    QueryMsg, QueryMsgHistorical, QueryMsgManaged, QueryMsgPausable, QueryMsgWithManagers,
+    QueryMsgWithOperators,
 };
-use crate::types::{Config, Extensions};
-use astroport::asset::token_asset_info;
+use crate::types::{
+    Config, Extensions, ExtensionsMigration, InfoResponse, IsPausedResponse, MerkleRoot,
+    OperatorApproval, OperatorApprovalResponse, TemplateVestingAccount, TotalPowerAtHeightResponse,
+    UnclaimedAmountResponse, UnclaimedAmountsAtHeightResponse, UnlockForecastResponse,
+    VestingAccount, VestingAccountResponse, VestingAssetState, VestingInfo,
+    VestingInfoHistoryEntry, VestingSchedule, VestingSchedulePeriodic, VestingSchedulePoint,
+    VestingScheduleTemplate, VestingState, VestingStateReconciliationResponse,
+    VestingTemplateResponse, VestingTermination, VestingTerminationResponse, VestingTimelinePoint,
+    VotingPowerAtHeightResponse,
+};
+use astroport::asset::{native_asset_info, token_asset_info, Asset, AssetInfo, AssetInfoExt};
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_json, Addr};
+use cosmwasm_std::{
+    coins, from_json, to_json_binary, Addr, BankMsg, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    StdError, Timestamp, Uint128,
+};
+use cw2::{set_contract_version, ContractVersion};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::{Expiration, PaymentError};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
 
 #[test]
 fn set_vesting_token() {
@@ -30,7 +50,12 @@ fn set_vesting_token() {
             extensions: Extensions {
                 historical: false,
                 managed: false,
-                with_managers: false
+                with_managers: false,
+                transferable: false,
+                with_operators: false,
+                voting: false,
+                staking: false,
+                pausable: false,
             }
         }
     );
@@ -72,7 +97,12 @@ fn set_vesting_token() {
             extensions: Extensions {
                 historical: false,
                 managed: false,
-                with_managers: false
+                with_managers: false,
+                transferable: false,
+                with_operators: false,
+                voting: false,
+                staking: false,
+                pausable: false,
             }
         }
     );
@@ -100,7 +130,12 @@ fn set_vesting_token() {
             extensions: Extensions {
                 historical: false,
                 managed: false,
-                with_managers: false
+                with_managers: false,
+                transferable: false,
+                with_operators: false,
+                voting: false,
+                staking: false,
+                pausable: false,
             }
         }
     );
@@ -128,7 +163,12 @@ fn proper_building_standard() {
             extensions: Extensions {
                 historical: false,
                 managed: false,
-                with_managers: false
+                with_managers: false,
+                transferable: false,
+                with_operators: false,
+                voting: false,
+                staking: false,
+                pausable: false,
             }
         }
     );
@@ -201,7 +241,12 @@ fn proper_building_managers() {
             extensions: Extensions {
                 historical: false,
                 managed: false,
-                with_managers: true
+                with_managers: true,
+                transferable: false,
+                with_operators: false,
+                voting: false,
+                staking: false,
+                pausable: false,
             }
         }
     );
@@ -276,7 +321,12 @@ fn proper_building_historical() {
             extensions: Extensions {
                 historical: true,
                 managed: false,
-                with_managers: false
+                with_managers: false,
+                transferable: false,
+                with_operators: false,
+                voting: false,
+                staking: false,
+                pausable: false,
             }
         }
     );
@@ -344,7 +394,12 @@ fn proper_building_managed() {
             extensions: Extensions {
                 historical: false,
                 managed: true,
-                with_managers: false
+                with_managers: false,
+                transferable: false,
+                with_operators: false,
+                voting: false,
+                staking: false,
+                pausable: false,
             }
         }
     );
@@ -426,7 +481,12 @@ fn proper_building_all_extensions() {
             extensions: Extensions {
                 historical: true,
                 managed: true,
-                with_managers: true
+                with_managers: true,
+                transferable: false,
+                with_operators: false,
+                voting: false,
+                staking: false,
+                pausable: false,
             }
         }
     );
@@ -482,3 +542,3523 @@ fn proper_building_all_extensions() {
     )
     .unwrap();
 }
+
+#[test]
+fn vesting_schedule_with_cliff() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .historical()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let schedule = VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: 200,
+            amount: Uint128::new(100),
+        }),
+        cliff: Some(150),
+        ..Default::default()
+    };
+
+    // the cliff can be neither before the start point nor after the end point
+    for cliff in [99, 201] {
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("owner", &coins(100, "untrn")),
+                ExecuteMsg::RegisterVestingAccounts {
+                    vesting_accounts: vec![VestingAccount {
+                        address: String::from("user"),
+                        schedules: vec![VestingSchedule {
+                            cliff: Some(cliff),
+                            ..schedule.clone()
+                        }],
+                    }],
+                },
+            )
+            .unwrap_err(),
+            ContractError::VestingScheduleCliffError(String::from("user")),
+        );
+    }
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![schedule],
+            }],
+        },
+    )
+    .unwrap();
+
+    // nothing is available before the cliff, then the accrued amount is unlocked at once
+    for (time, expected) in [(149u64, 0u128), (150, 50), (175, 75), (300, 100)] {
+        env.block.time = Timestamp::from_seconds(time);
+        assert_eq!(
+            from_json::<Uint128>(
+                &query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::AvailableAmount {
+                        address: String::from("user"),
+                    },
+                )
+                .unwrap()
+            )
+            .unwrap(),
+            Uint128::new(expected),
+        );
+    }
+
+    // tokens locked behind the cliff are still unclaimed
+    assert_eq!(
+        from_json::<Uint128>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::HistoricalExtension {
+                    msg: QueryMsgHistorical::UnclaimedAmountAtHeight {
+                        address: String::from("user"),
+                        height: env.block.height + 1,
+                    },
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        Uint128::new(100),
+    );
+}
+
+#[test]
+fn vesting_schedule_with_intermediate_points() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    // 10% at start, a 30% step at time 150, then linear up to 100% at time 200
+    let schedule = VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::new(10),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: 200,
+            amount: Uint128::new(100),
+        }),
+        intermediate_points: Some(vec![
+            VestingSchedulePoint {
+                time: 150,
+                amount: Uint128::new(10),
+            },
+            VestingSchedulePoint {
+                time: 150,
+                amount: Uint128::new(40),
+            },
+        ]),
+        ..Default::default()
+    };
+
+    // intermediate points must be ordered and the end point must be set
+    for invalid_schedule in [
+        VestingSchedule {
+            intermediate_points: Some(vec![VestingSchedulePoint {
+                time: 250,
+                amount: Uint128::new(40),
+            }]),
+            ..schedule.clone()
+        },
+        VestingSchedule {
+            intermediate_points: Some(vec![VestingSchedulePoint {
+                time: 150,
+                amount: Uint128::new(5),
+            }]),
+            ..schedule.clone()
+        },
+        VestingSchedule {
+            end_point: None,
+            ..schedule.clone()
+        },
+    ] {
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("owner", &coins(100, "untrn")),
+                ExecuteMsg::RegisterVestingAccounts {
+                    vesting_accounts: vec![VestingAccount {
+                        address: String::from("user"),
+                        schedules: vec![invalid_schedule],
+                    }],
+                },
+            )
+            .unwrap_err(),
+            ContractError::VestingScheduleIntermediatePointsError(String::from("user")),
+        );
+    }
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![schedule],
+            }],
+        },
+    )
+    .unwrap();
+
+    for (time, expected) in [
+        (99u64, 0u128),
+        (100, 10),
+        (149, 10),
+        (150, 40),
+        (175, 70),
+        (200, 100),
+        (300, 100),
+    ] {
+        env.block.time = Timestamp::from_seconds(time);
+        assert_eq!(
+            from_json::<Uint128>(
+                &query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::AvailableAmount {
+                        address: String::from("user"),
+                    },
+                )
+                .unwrap()
+            )
+            .unwrap(),
+            Uint128::new(expected),
+        );
+    }
+}
+
+#[test]
+fn vesting_schedule_periodic() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    // 10 tokens at start, then 4 tranches of 25 tokens every 30 seconds
+    let schedule = VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::new(10),
+        },
+        periodic: Some(VestingSchedulePeriodic {
+            period: 30,
+            num_periods: 4,
+            amount_per_period: Uint128::new(25),
+        }),
+        ..Default::default()
+    };
+
+    for invalid_schedule in [
+        VestingSchedule {
+            periodic: Some(VestingSchedulePeriodic {
+                period: 0,
+                num_periods: 4,
+                amount_per_period: Uint128::new(25),
+            }),
+            ..schedule.clone()
+        },
+        VestingSchedule {
+            end_point: Some(VestingSchedulePoint {
+                time: 220,
+                amount: Uint128::new(110),
+            }),
+            ..schedule.clone()
+        },
+    ] {
+        assert_eq!(
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info("owner", &coins(110, "untrn")),
+                ExecuteMsg::RegisterVestingAccounts {
+                    vesting_accounts: vec![VestingAccount {
+                        address: String::from("user"),
+                        schedules: vec![invalid_schedule],
+                    }],
+                },
+            )
+            .unwrap_err(),
+            ContractError::VestingSchedulePeriodicError(String::from("user")),
+        );
+    }
+
+    // the deposit must cover all the tranches
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(100, "untrn")),
+            ExecuteMsg::RegisterVestingAccounts {
+                vesting_accounts: vec![VestingAccount {
+                    address: String::from("user"),
+                    schedules: vec![schedule.clone()],
+                }],
+            },
+        )
+        .unwrap_err(),
+        ContractError::VestingScheduleAmountError {},
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(110, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![schedule],
+            }],
+        },
+    )
+    .unwrap();
+
+    for (time, expected) in [
+        (99u64, 0u128),
+        (100, 10),
+        (129, 10),
+        (130, 35),
+        (189, 60),
+        (190, 85),
+        (220, 110),
+        (1000, 110),
+    ] {
+        env.block.time = Timestamp::from_seconds(time);
+        assert_eq!(
+            from_json::<Uint128>(
+                &query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::AvailableAmount {
+                        address: String::from("user"),
+                    },
+                )
+                .unwrap()
+            )
+            .unwrap(),
+            Uint128::new(expected),
+        );
+    }
+}
+
+#[test]
+fn truncate_vesting_accounts() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .managed()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: 100,
+                        amount: Uint128::zero(),
+                    },
+                    end_point: Some(VestingSchedulePoint {
+                        time: 200,
+                        amount: Uint128::new(100),
+                    }),
+                    ..Default::default()
+                }],
+            }],
+        },
+    )
+    .unwrap();
+
+    // the user claims a part of the vested tokens
+    env.block.time = Timestamp::from_seconds(150);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::Claim {
+            recipient: None,
+            amount: Some(Uint128::new(20)),
+        },
+    )
+    .unwrap();
+
+    // truncate by a stranger -> Unauthorized
+    let msg = ExecuteMsg::ManagedExtension {
+        msg: ExecuteMsgManaged::TruncateVestingAccounts {
+            vesting_accounts: vec![String::from("user")],
+            clawback_account: String::from("clawback"),
+        },
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("stranger", &[]),
+            msg.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+
+    // only the unvested half is clawed back
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("clawback"),
+            amount: coins(50, "untrn"),
+        }),
+    );
+
+    assert_eq!(
+        from_json::<VestingAccountResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingAccount {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        VestingAccountResponse {
+            address: Addr::unchecked("user"),
+            info: VestingInfo {
+                schedules: vec![VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: 150,
+                        amount: Uint128::new(50),
+                    },
+                    ..Default::default()
+                }],
+                released_amount: Uint128::new(20),
+                released_assets: vec![],
+                slashed_amount: Uint128::zero(),
+            },
+        },
+    );
+    assert_eq!(
+        from_json::<VestingState>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingState {}).unwrap()
+        )
+        .unwrap(),
+        VestingState {
+            total_granted: Uint128::new(50),
+            total_released: Uint128::new(20),
+        },
+    );
+
+    // the vested but unclaimed tokens are still claimable, nothing vests anymore
+    env.block.time = Timestamp::from_seconds(300);
+    assert_eq!(
+        from_json::<Uint128>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::AvailableAmount {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        Uint128::new(30),
+    );
+}
+
+#[test]
+fn scheduled_vesting_termination() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .managed()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: 100,
+                        amount: Uint128::zero(),
+                    },
+                    end_point: Some(VestingSchedulePoint {
+                        time: 200,
+                        amount: Uint128::new(100),
+                    }),
+                    ..Default::default()
+                }],
+            }],
+        },
+    )
+    .unwrap();
+
+    // the termination time must be in the future
+    env.block.time = Timestamp::from_seconds(120);
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            ExecuteMsg::ManagedExtension {
+                msg: ExecuteMsgManaged::ScheduleVestingTermination {
+                    vesting_account: String::from("user"),
+                    termination_time: 120,
+                    clawback_account: String::from("clawback"),
+                },
+            },
+        )
+        .unwrap_err(),
+        ContractError::VestingTerminationTimeError(String::from("user")),
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::ManagedExtension {
+            msg: ExecuteMsgManaged::ScheduleVestingTermination {
+                vesting_account: String::from("user"),
+                termination_time: 150,
+                clawback_account: String::from("clawback"),
+            },
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        from_json::<Vec<VestingTerminationResponse>>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::ManagedExtension {
+                    msg: QueryMsgManaged::PendingTerminations {
+                        start_after: None,
+                        limit: None,
+                    },
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        vec![VestingTerminationResponse {
+            address: Addr::unchecked("user"),
+            termination: VestingTermination {
+                termination_time: 150,
+                clawback_account: Addr::unchecked("clawback"),
+            },
+        }],
+    );
+
+    // nothing can be clawed back before the termination time
+    let claw_back_msg = ExecuteMsg::ManagedExtension {
+        msg: ExecuteMsgManaged::ClawBackTerminatedAccounts {
+            vesting_accounts: vec![String::from("user")],
+        },
+    };
+    env.block.time = Timestamp::from_seconds(140);
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("clawback", &[]),
+            claw_back_msg.clone(),
+        )
+        .unwrap_err(),
+        ContractError::VestingTerminationNotReachedError(String::from("user")),
+    );
+
+    // the vesting stops at the termination time
+    env.block.time = Timestamp::from_seconds(180);
+    let query_available_amount = |deps: Deps, env: Env| {
+        from_json::<Uint128>(
+            &query(
+                deps,
+                env,
+                QueryMsg::AvailableAmount {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        query_available_amount(deps.as_ref(), env.clone()),
+        Uint128::new(50)
+    );
+
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("stranger", &[]),
+            claw_back_msg.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("clawback", &[]),
+        claw_back_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("clawback"),
+            amount: coins(50, "untrn"),
+        }),
+    );
+
+    // the termination is done, and the vested tokens are still claimable
+    assert_eq!(
+        from_json::<Vec<VestingTerminationResponse>>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::ManagedExtension {
+                    msg: QueryMsgManaged::PendingTerminations {
+                        start_after: None,
+                        limit: None,
+                    },
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        vec![],
+    );
+    env.block.time = Timestamp::from_seconds(300);
+    assert_eq!(
+        query_available_amount(deps.as_ref(), env.clone()),
+        Uint128::new(50)
+    );
+}
+
+#[test]
+fn transfer_vesting_account() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .historical()
+        .transferable()
+        .with_operators()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let schedule = |amount: u128| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: 200,
+            amount: Uint128::new(amount),
+        }),
+        ..Default::default()
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(150, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![
+                VestingAccount {
+                    address: String::from("user"),
+                    schedules: vec![schedule(100)],
+                },
+                VestingAccount {
+                    address: String::from("multisig"),
+                    schedules: vec![schedule(50)],
+                },
+            ],
+        },
+    )
+    .unwrap();
+
+    env.block.time = Timestamp::from_seconds(150);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::Claim {
+            recipient: None,
+            amount: Some(Uint128::new(20)),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &[]),
+            ExecuteMsg::TransferVestingAccount {
+                new_address: String::from("user"),
+            },
+        )
+        .unwrap_err(),
+        ContractError::VestingTransferToSelfError(String::from("user")),
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::WithOperatorsExtension {
+            msg: ExecuteMsgWithOperators::ApproveOperator {
+                operator: String::from("operator"),
+                expires: None,
+                recipient: None,
+            },
+        },
+    )
+    .unwrap();
+
+    let transfer_height = env.block.height + 1;
+    env.block.height = transfer_height;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::TransferVestingAccount {
+            new_address: String::from("multisig"),
+        },
+    )
+    .unwrap();
+
+    // the operators of the sender are removed along with its account
+    assert_eq!(
+        from_json::<Vec<OperatorApprovalResponse>>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::WithOperatorsExtension {
+                    msg: QueryMsgWithOperators::Operators {
+                        address: String::from("user"),
+                        start_after: None,
+                        limit: None,
+                    },
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        vec![],
+    );
+
+    // the schedules and the released amount are merged into the existing account
+    assert_eq!(
+        from_json::<VestingAccountResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingAccount {
+                    address: String::from("multisig"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        VestingAccountResponse {
+            address: Addr::unchecked("multisig"),
+            info: VestingInfo {
+                schedules: vec![schedule(100), schedule(50)],
+                released_amount: Uint128::new(20),
+                released_assets: vec![],
+                slashed_amount: Uint128::zero(),
+            },
+        },
+    );
+    query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::VestingAccount {
+            address: String::from("user"),
+        },
+    )
+    .unwrap_err();
+
+    // the move is recorded in the snapshots of both addresses
+    let query_unclaimed_amount = |deps: Deps, env: Env, address: &str, height: u64| {
+        from_json::<Uint128>(
+            &query(
+                deps,
+                env,
+                QueryMsg::HistoricalExtension {
+                    msg: QueryMsgHistorical::UnclaimedAmountAtHeight {
+                        address: String::from(address),
+                        height,
+                    },
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        query_unclaimed_amount(deps.as_ref(), env.clone(), "user", transfer_height),
+        Uint128::new(80)
+    );
+    assert_eq!(
+        query_unclaimed_amount(deps.as_ref(), env.clone(), "multisig", transfer_height),
+        Uint128::new(50)
+    );
+    assert_eq!(
+        query_unclaimed_amount(deps.as_ref(), env.clone(), "user", transfer_height + 1),
+        Uint128::zero()
+    );
+    assert_eq!(
+        query_unclaimed_amount(deps.as_ref(), env.clone(), "multisig", transfer_height + 1),
+        Uint128::new(130)
+    );
+}
+
+#[test]
+fn transfer_vesting_account_not_enabled() {
+    let mut deps = mock_dependencies();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &[]),
+            ExecuteMsg::TransferVestingAccount {
+                new_address: String::from("multisig"),
+            },
+        )
+        .unwrap_err(),
+        ext_unsupported_err("transferable").into()
+    );
+}
+
+#[test]
+fn claim_for_multiple_accounts() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+    let vesting_account = |address: &str, start_time: u64| VestingAccount {
+        address: String::from(address),
+        schedules: vec![VestingSchedule {
+            start_point: VestingSchedulePoint {
+                time: start_time,
+                amount: Uint128::zero(),
+            },
+            end_point: Some(VestingSchedulePoint {
+                time: start_time + 100,
+                amount: Uint128::new(100),
+            }),
+            ..Default::default()
+        }],
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(200, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![
+                vesting_account("user1", 100),
+                vesting_account("user2", 1000),
+            ],
+        },
+    )
+    .unwrap();
+    env.block.time = Timestamp::from_seconds(150);
+
+    let claim_for_msg = ExecuteMsg::ClaimFor {
+        addresses: vec![
+            String::from("user1"),
+            String::from("unknown"),
+            String::from("user2"),
+        ],
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user1", &[]),
+            claim_for_msg.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+
+    // the address without a vesting account and the one with nothing vested don't fail the batch
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        claim_for_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<_>>(),
+        vec![CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("user1"),
+            amount: coins(50, "untrn"),
+        })],
+    );
+    assert_eq!(
+        res.attributes
+            .iter()
+            .filter(|attr| attr.key == "skipped_address")
+            .map(|attr| attr.value.as_str())
+            .collect::<Vec<_>>(),
+        vec!["unknown"],
+    );
+}
+
+#[test]
+fn claim_on_behalf() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .with_operators()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: 100,
+                        amount: Uint128::zero(),
+                    },
+                    end_point: Some(VestingSchedulePoint {
+                        time: 200,
+                        amount: Uint128::new(100),
+                    }),
+                    ..Default::default()
+                }],
+            }],
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(50, "uatom")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: 100,
+                        amount: Uint128::zero(),
+                    },
+                    end_point: Some(VestingSchedulePoint {
+                        time: 200,
+                        amount: Uint128::new(50),
+                    }),
+                    asset_info: Some(native_asset_info(String::from("uatom"))),
+                    ..Default::default()
+                }],
+            }],
+        },
+    )
+    .unwrap();
+    env.block.time = Timestamp::from_seconds(150);
+
+    let claim_on_behalf = |recipient: Option<&str>| ExecuteMsg::WithOperatorsExtension {
+        msg: ExecuteMsgWithOperators::ClaimOnBehalf {
+            address: String::from("user"),
+            asset: None,
+            recipient: recipient.map(String::from),
+            amount: Some(Uint128::new(10)),
+        },
+    };
+
+    // an operator can't claim without an approval
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("operator", &[]),
+            claim_on_behalf(None),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+
+    // an approval can't be expired at the moment it's granted
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &[]),
+            ExecuteMsg::WithOperatorsExtension {
+                msg: ExecuteMsgWithOperators::ApproveOperator {
+                    operator: String::from("operator"),
+                    expires: Some(Expiration::AtTime(Timestamp::from_seconds(150))),
+                    recipient: None,
+                },
+            },
+        )
+        .unwrap_err(),
+        ContractError::OperatorApprovalExpired {},
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::WithOperatorsExtension {
+            msg: ExecuteMsgWithOperators::ApproveOperator {
+                operator: String::from("operator"),
+                expires: Some(Expiration::AtTime(Timestamp::from_seconds(160))),
+                recipient: Some(String::from("cold_wallet")),
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_json::<Vec<OperatorApprovalResponse>>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::WithOperatorsExtension {
+                    msg: QueryMsgWithOperators::Operators {
+                        address: String::from("user"),
+                        start_after: None,
+                        limit: None,
+                    },
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        vec![OperatorApprovalResponse {
+            operator: Addr::unchecked("operator"),
+            approval: OperatorApproval {
+                expires: Expiration::AtTime(Timestamp::from_seconds(160)),
+                recipient: Some(Addr::unchecked("cold_wallet")),
+            },
+        }],
+    );
+
+    // the tokens can only be sent to the account itself or to the approved recipient
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("operator", &[]),
+            claim_on_behalf(Some("operator")),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    for (recipient, expected_recipient) in [(None, "user"), (Some("cold_wallet"), "cold_wallet")] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("operator", &[]),
+            claim_on_behalf(recipient),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from(expected_recipient),
+                amount: coins(10, "untrn"),
+            }),
+        );
+    }
+    assert_eq!(
+        from_json::<VestingAccountResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingAccount {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .info
+        .released_amount,
+        Uint128::new(20),
+    );
+
+    // the other assets of the account are claimed the same way
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("operator", &[]),
+        ExecuteMsg::WithOperatorsExtension {
+            msg: ExecuteMsgWithOperators::ClaimOnBehalf {
+                address: String::from("user"),
+                asset: Some(native_asset_info(String::from("uatom"))),
+                recipient: None,
+                amount: None,
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("user"),
+            amount: coins(25, "uatom"),
+        }),
+    );
+
+    // the approval can't be used after it expires
+    env.block.time = Timestamp::from_seconds(160);
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("operator", &[]),
+            claim_on_behalf(None),
+        )
+        .unwrap_err(),
+        ContractError::OperatorApprovalExpired {},
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::WithOperatorsExtension {
+            msg: ExecuteMsgWithOperators::RevokeOperator {
+                operator: String::from("operator"),
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("operator", &[]),
+            claim_on_behalf(None),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+}
+
+#[test]
+fn vesting_timeline() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: 100,
+                        amount: Uint128::zero(),
+                    },
+                    end_point: Some(VestingSchedulePoint {
+                        time: 200,
+                        amount: Uint128::new(100),
+                    }),
+                    ..Default::default()
+                }],
+            }],
+        },
+    )
+    .unwrap();
+
+    // the already claimed tokens are still included in the timeline
+    env.block.time = Timestamp::from_seconds(150);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::Claim {
+            recipient: None,
+            amount: Some(Uint128::new(20)),
+        },
+    )
+    .unwrap();
+
+    let query_timeline = |deps: Deps, from: u64, to: u64, step: u64| {
+        query(
+            deps,
+            mock_env(),
+            QueryMsg::VestingTimeline {
+                address: String::from("user"),
+                from,
+                to,
+                step,
+            },
+        )
+        .map(|res| from_json::<Vec<VestingTimelinePoint>>(&res).unwrap())
+    };
+    assert_eq!(
+        query_timeline(deps.as_ref(), 90, 200, 30).unwrap(),
+        [(90, 0), (120, 20), (150, 50), (180, 80), (200, 100)]
+            .into_iter()
+            .map(|(time, amount)| VestingTimelinePoint {
+                time,
+                unlocked_amount: Uint128::new(amount),
+            })
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(
+        query_timeline(deps.as_ref(), 150, 150, 10).unwrap(),
+        vec![VestingTimelinePoint {
+            time: 150,
+            unlocked_amount: Uint128::new(50),
+        }],
+    );
+
+    query_timeline(deps.as_ref(), 200, 100, 10).unwrap_err();
+    query_timeline(deps.as_ref(), 100, 200, 0).unwrap_err();
+    assert_eq!(query_timeline(deps.as_ref(), 0, 99, 1).unwrap().len(), 100);
+    query_timeline(deps.as_ref(), 0, 100, 1).unwrap_err();
+}
+
+#[test]
+fn unlock_forecast() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let schedule = |start: u64, end: u64, amount: u128| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: start,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: end,
+            amount: Uint128::new(amount),
+        }),
+        ..Default::default()
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(300, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![
+                VestingAccount {
+                    address: String::from("user1"),
+                    schedules: vec![schedule(100, 200, 100)],
+                },
+                VestingAccount {
+                    address: String::from("user2"),
+                    schedules: vec![schedule(150, 250, 200)],
+                },
+            ],
+        },
+    )
+    .unwrap();
+
+    let query_forecast = |deps: Deps, start_after: Option<&str>, limit: Option<u32>| {
+        from_json::<UnlockForecastResponse>(
+            &query(
+                deps,
+                env.clone(),
+                QueryMsg::UnlockForecast {
+                    from: 120,
+                    to: 170,
+                    start_after: start_after.map(String::from),
+                    limit,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        query_forecast(deps.as_ref(), None, None),
+        UnlockForecastResponse {
+            amount: Uint128::new(90),
+            last_address: Some(Addr::unchecked("user2")),
+        },
+    );
+
+    // the pages sum up to the contract-wide amount
+    assert_eq!(
+        query_forecast(deps.as_ref(), None, Some(1)),
+        UnlockForecastResponse {
+            amount: Uint128::new(50),
+            last_address: Some(Addr::unchecked("user1")),
+        },
+    );
+    assert_eq!(
+        query_forecast(deps.as_ref(), Some("user1"), Some(1)),
+        UnlockForecastResponse {
+            amount: Uint128::new(40),
+            last_address: Some(Addr::unchecked("user2")),
+        },
+    );
+    assert_eq!(
+        query_forecast(deps.as_ref(), Some("user2"), Some(1)),
+        UnlockForecastResponse {
+            amount: Uint128::zero(),
+            last_address: None,
+        },
+    );
+}
+
+#[test]
+fn multi_asset_vesting() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let schedule = |amount: u128, asset_info: Option<AssetInfo>| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: 200,
+            amount: Uint128::new(amount),
+        }),
+        asset_info,
+        ..Default::default()
+    };
+    let uatom = native_asset_info(String::from("uatom"));
+
+    // a single registration can only vest a single asset
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(100, "untrn")),
+            ExecuteMsg::RegisterVestingAccounts {
+                vesting_accounts: vec![VestingAccount {
+                    address: String::from("user"),
+                    schedules: vec![schedule(100, None), schedule(50, Some(uatom.clone()))],
+                }],
+            },
+        )
+        .unwrap_err(),
+        ContractError::VestingScheduleAssetError {},
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![schedule(100, None)],
+            }],
+        },
+    )
+    .unwrap();
+    // the additional asset is paid for with its own funds
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(50, "untrn")),
+            ExecuteMsg::RegisterVestingAccounts {
+                vesting_accounts: vec![VestingAccount {
+                    address: String::from("user"),
+                    schedules: vec![schedule(50, Some(uatom.clone()))],
+                }],
+            },
+        )
+        .unwrap_err(),
+        ContractError::PaymentError(PaymentError::MissingDenom(String::from("uatom"))),
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(50, "uatom")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![schedule(50, Some(uatom.clone()))],
+            }],
+        },
+    )
+    .unwrap();
+
+    env.block.time = Timestamp::from_seconds(150);
+    let query_available_amounts = |deps: Deps, env: Env| {
+        from_json::<Vec<Asset>>(
+            &query(
+                deps,
+                env,
+                QueryMsg::AvailableAmounts {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        query_available_amounts(deps.as_ref(), env.clone()),
+        vec![
+            native_asset_info(String::from("untrn")).with_balance(50u128),
+            uatom.with_balance(25u128),
+        ],
+    );
+
+    // the plain claim only pays the vesting token
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::Claim {
+            recipient: None,
+            amount: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("user"),
+            amount: coins(50, "untrn"),
+        }),
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::ClaimAsset {
+            asset_info: uatom.clone(),
+            recipient: None,
+            amount: Some(Uint128::new(10)),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("user"),
+            amount: coins(10, "uatom"),
+        }),
+    );
+    assert_eq!(
+        query_available_amounts(deps.as_ref(), env.clone()),
+        vec![
+            native_asset_info(String::from("untrn")).with_balance(0u128),
+            uatom.with_balance(15u128),
+        ],
+    );
+
+    assert_eq!(
+        from_json::<Vec<VestingAssetState>>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingStates {}).unwrap()
+        )
+        .unwrap(),
+        vec![
+            VestingAssetState {
+                asset_info: native_asset_info(String::from("untrn")),
+                state: VestingState {
+                    total_granted: Uint128::new(100),
+                    total_released: Uint128::new(50),
+                },
+            },
+            VestingAssetState {
+                asset_info: uatom.clone(),
+                state: VestingState {
+                    total_granted: Uint128::new(50),
+                    total_released: Uint128::new(10),
+                },
+            },
+        ],
+    );
+}
+
+#[test]
+fn voting_power() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    assert_eq!(
+        VestingBaseBuilder::default()
+            .voting(String::from("dao"))
+            .build(
+                deps.as_mut(),
+                String::from("owner"),
+                String::from("token_info_manager"),
+            )
+            .unwrap_err(),
+        StdError::generic_err("The voting extension requires the historical extension"),
+    );
+    VestingBaseBuilder::default()
+        .historical()
+        .voting(String::from("dao"))
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    set_contract_version(&mut deps.storage, "vesting", "1.0.0").unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        from_json::<Addr>(&query(deps.as_ref(), env.clone(), QueryMsg::Dao {}).unwrap()).unwrap(),
+        Addr::unchecked("dao"),
+    );
+    assert_eq!(
+        from_json::<InfoResponse>(&query(deps.as_ref(), env.clone(), QueryMsg::Info {}).unwrap())
+            .unwrap(),
+        InfoResponse {
+            info: ContractVersion {
+                contract: String::from("vesting"),
+                version: String::from("1.0.0"),
+            },
+        },
+    );
+
+    let schedule = |amount: u128, voting_weight: Option<Decimal>| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: 200,
+            amount: Uint128::new(amount),
+        }),
+        voting_weight,
+        ..Default::default()
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(300, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![
+                VestingAccount {
+                    address: String::from("user1"),
+                    schedules: vec![
+                        schedule(100, Some(Decimal::percent(200))),
+                        schedule(100, None),
+                    ],
+                },
+                VestingAccount {
+                    address: String::from("user2"),
+                    schedules: vec![schedule(100, None)],
+                },
+            ],
+        },
+    )
+    .unwrap();
+
+    let query_voting_power = |deps: Deps, env: Env, address: &str, height: u64| {
+        from_json::<VotingPowerAtHeightResponse>(
+            &query(
+                deps,
+                env,
+                QueryMsg::VotingPowerAtHeight {
+                    address: String::from(address),
+                    height: Some(height),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .power
+        .u128()
+    };
+    let query_total_power = |deps: Deps, env: Env, height: u64| {
+        from_json::<TotalPowerAtHeightResponse>(
+            &query(
+                deps,
+                env,
+                QueryMsg::TotalPowerAtHeight {
+                    height: Some(height),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .power
+        .u128()
+    };
+
+    // the snapshots are taken at the beginning of a block
+    let registration_height = env.block.height;
+    env.block.height += 1;
+    assert_eq!(
+        query_voting_power(deps.as_ref(), env.clone(), "user1", registration_height + 1),
+        300,
+    );
+    assert_eq!(
+        query_voting_power(deps.as_ref(), env.clone(), "user2", registration_height + 1),
+        100,
+    );
+    assert_eq!(
+        query_total_power(deps.as_ref(), env.clone(), registration_height + 1),
+        400,
+    );
+
+    // the claimed tokens reduce the power of the weighted and unweighted schedules alike
+    env.block.time = Timestamp::from_seconds(200);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        ExecuteMsg::Claim {
+            recipient: None,
+            amount: Some(Uint128::new(100)),
+        },
+    )
+    .unwrap();
+    let claim_height = env.block.height;
+    env.block.height += 1;
+    assert_eq!(
+        query_voting_power(deps.as_ref(), env.clone(), "user1", claim_height + 1),
+        150,
+    );
+    assert_eq!(
+        query_total_power(deps.as_ref(), env.clone(), claim_height + 1),
+        250,
+    );
+    assert_eq!(
+        query_voting_power(deps.as_ref(), env.clone(), "user1", claim_height),
+        300,
+    );
+    assert_eq!(
+        query_total_power(deps.as_ref(), env.clone(), claim_height),
+        400
+    );
+
+    // the current height is used by default
+    assert_eq!(
+        from_json::<TotalPowerAtHeightResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::TotalPowerAtHeight { height: None },
+            )
+            .unwrap(),
+        )
+        .unwrap(),
+        TotalPowerAtHeightResponse {
+            power: Uint128::new(250),
+            height: env.block.height,
+        },
+    );
+}
+
+#[test]
+fn voting_power_after_truncation() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .historical()
+        .managed()
+        .voting(String::from("dao"))
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let schedule = |amount: u128, voting_weight: Option<Decimal>| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: 200,
+            amount: Uint128::new(amount),
+        }),
+        voting_weight,
+        ..Default::default()
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(200, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![
+                    schedule(100, Some(Decimal::percent(200))),
+                    schedule(100, None),
+                ],
+            }],
+        },
+    )
+    .unwrap();
+
+    // half of each schedule has vested by the truncation
+    env.block.time = Timestamp::from_seconds(150);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::ManagedExtension {
+            msg: ExecuteMsgManaged::TruncateVestingAccounts {
+                vesting_accounts: vec![String::from("user")],
+                clawback_account: String::from("clawback"),
+            },
+        },
+    )
+    .unwrap();
+
+    let truncated_schedule = |voting_weight: Option<Decimal>| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 150,
+            amount: Uint128::new(50),
+        },
+        voting_weight,
+        ..Default::default()
+    };
+    assert_eq!(
+        from_json::<VestingAccountResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingAccount {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .info
+        .schedules,
+        vec![
+            truncated_schedule(Some(Decimal::percent(200))),
+            truncated_schedule(None),
+        ],
+    );
+
+    // the weighted half keeps its weight: 50 * 2 + 50
+    env.block.height += 1;
+    assert_eq!(
+        from_json::<VotingPowerAtHeightResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VotingPowerAtHeight {
+                    address: String::from("user"),
+                    height: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .power,
+        Uint128::new(150),
+    );
+    assert_eq!(
+        from_json::<TotalPowerAtHeightResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::TotalPowerAtHeight { height: None },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .power,
+        Uint128::new(150),
+    );
+}
+
+#[test]
+fn historical_accounts() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .historical()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let schedule = |amount: u128| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: 200,
+            amount: Uint128::new(amount),
+        }),
+        ..Default::default()
+    };
+    let register_height = env.block.height;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(150, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![
+                VestingAccount {
+                    address: String::from("user1"),
+                    schedules: vec![schedule(100)],
+                },
+                VestingAccount {
+                    address: String::from("user2"),
+                    schedules: vec![schedule(50)],
+                },
+            ],
+        },
+    )
+    .unwrap();
+
+    env.block.height = register_height + 1;
+    env.block.time = Timestamp::from_seconds(150);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        ExecuteMsg::Claim {
+            recipient: None,
+            amount: Some(Uint128::new(20)),
+        },
+    )
+    .unwrap();
+
+    env.block.height = register_height + 2;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(30, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user3"),
+                schedules: vec![schedule(30)],
+            }],
+        },
+    )
+    .unwrap();
+
+    let query_unclaimed_amounts =
+        |deps: Deps, height: u64, start_after: Option<&str>, limit: Option<u32>| {
+            from_json::<UnclaimedAmountsAtHeightResponse>(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::HistoricalExtension {
+                        msg: QueryMsgHistorical::UnclaimedAmountsAtHeight {
+                            height,
+                            start_after: start_after.map(String::from),
+                            limit,
+                        },
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+    let unclaimed = |address: &str, amount: u128| UnclaimedAmountResponse {
+        address: Addr::unchecked(address),
+        amount: Uint128::new(amount),
+    };
+
+    // the changes are visible starting from the next height
+    assert_eq!(
+        query_unclaimed_amounts(deps.as_ref(), register_height, None, None),
+        UnclaimedAmountsAtHeightResponse {
+            accounts: vec![],
+            last_address: None,
+        }
+    );
+    assert_eq!(
+        query_unclaimed_amounts(deps.as_ref(), register_height + 1, None, None),
+        UnclaimedAmountsAtHeightResponse {
+            accounts: vec![unclaimed("user1", 100), unclaimed("user2", 50)],
+            last_address: None,
+        }
+    );
+    assert_eq!(
+        query_unclaimed_amounts(deps.as_ref(), register_height + 3, None, None),
+        UnclaimedAmountsAtHeightResponse {
+            accounts: vec![
+                unclaimed("user1", 80),
+                unclaimed("user2", 50),
+                unclaimed("user3", 30),
+            ],
+            last_address: None,
+        }
+    );
+
+    // accounts are paginated by address
+    assert_eq!(
+        query_unclaimed_amounts(deps.as_ref(), register_height + 3, None, Some(1)),
+        UnclaimedAmountsAtHeightResponse {
+            accounts: vec![unclaimed("user1", 80)],
+            last_address: Some(Addr::unchecked("user1")),
+        }
+    );
+    assert_eq!(
+        query_unclaimed_amounts(deps.as_ref(), register_height + 3, Some("user1"), Some(2)),
+        UnclaimedAmountsAtHeightResponse {
+            accounts: vec![unclaimed("user2", 50), unclaimed("user3", 30)],
+            last_address: Some(Addr::unchecked("user3")),
+        }
+    );
+    assert_eq!(
+        query_unclaimed_amounts(deps.as_ref(), register_height + 3, Some("user3"), None),
+        UnclaimedAmountsAtHeightResponse {
+            accounts: vec![],
+            last_address: None,
+        }
+    );
+
+    let query_history = |deps: Deps, start_after: Option<u64>, limit: Option<u32>| {
+        from_json::<Vec<VestingInfoHistoryEntry>>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::HistoricalExtension {
+                    msg: QueryMsgHistorical::VestingInfoHistory {
+                        address: String::from("user1"),
+                        start_after,
+                        limit,
+                    },
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let history_entry = |height: u64, released_amount: u128| VestingInfoHistoryEntry {
+        height,
+        info: Some(VestingInfo {
+            schedules: vec![schedule(100)],
+            released_amount: Uint128::new(released_amount),
+            released_assets: vec![],
+            slashed_amount: Uint128::zero(),
+        }),
+    };
+    assert_eq!(
+        query_history(deps.as_ref(), None, None),
+        vec![
+            history_entry(register_height, 0),
+            history_entry(register_height + 1, 20),
+        ]
+    );
+    assert_eq!(
+        query_history(deps.as_ref(), None, Some(1)),
+        vec![history_entry(register_height, 0)]
+    );
+    assert_eq!(
+        query_history(deps.as_ref(), Some(register_height), None),
+        vec![history_entry(register_height + 1, 20)]
+    );
+}
+
+#[test]
+fn amend_vesting_account() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .historical()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let schedule = |end_time: u64, end_amount: u128| VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: end_time,
+            amount: Uint128::new(end_amount),
+        }),
+        ..Default::default()
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: String::from("user"),
+                schedules: vec![schedule(200, 100)],
+            }],
+        },
+    )
+    .unwrap();
+
+    env.block.time = Timestamp::from_seconds(150);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        ExecuteMsg::Claim {
+            recipient: None,
+            amount: None,
+        },
+    )
+    .unwrap();
+
+    let amend_msg = |schedule_index: u32, end_amount: Option<u128>, end_time: Option<u64>| {
+        ExecuteMsg::AmendVestingAccount {
+            address: String::from("user"),
+            schedule_index,
+            end_amount: end_amount.map(Uint128::new),
+            end_time,
+        }
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &coins(20, "untrn")),
+            amend_msg(0, Some(120), None),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(20, "untrn")),
+            amend_msg(1, Some(120), None),
+        )
+        .unwrap_err(),
+        ContractError::VestingScheduleExtractError(String::from("user")),
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(10, "untrn")),
+            amend_msg(0, Some(120), None),
+        )
+        .unwrap_err(),
+        ContractError::VestingScheduleAmountError {},
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            amend_msg(0, Some(80), None),
+        )
+        .unwrap_err(),
+        ContractError::VestingScheduleAmendError(String::from("user")),
+    );
+    // the 50 tokens vested and claimed by now can't be taken back by postponing the end
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            amend_msg(0, None, Some(400)),
+        )
+        .unwrap_err(),
+        ContractError::VestingScheduleAmendError(String::from("user")),
+    );
+    // the schedule asset should be deposited
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("cw20_token", &[]),
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: String::from("owner"),
+                amount: Uint128::new(20),
+                msg: to_json_binary(&Cw20HookMsg::AmendVestingAccount {
+                    address: String::from("user"),
+                    schedule_index: 0,
+                    end_amount: Some(Uint128::new(120)),
+                    end_time: None,
+                })
+                .unwrap(),
+            }),
+        )
+        .unwrap_err(),
+        ContractError::VestingScheduleAssetError {},
+    );
+
+    // add 20% to the schedule over the same end date
+    env.block.height += 1;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(20, "untrn")),
+        amend_msg(0, Some(120), None),
+    )
+    .unwrap();
+    // postpone the end keeping the vested amount
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(60, "untrn")),
+        amend_msg(0, Some(180), Some(250)),
+    )
+    .unwrap();
+
+    assert_eq!(
+        from_json::<VestingAccountResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingAccount {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .info,
+        VestingInfo {
+            schedules: vec![schedule(250, 180)],
+            released_amount: Uint128::new(50),
+            released_assets: vec![],
+            slashed_amount: Uint128::zero(),
+        },
+    );
+    assert_eq!(
+        from_json::<VestingState>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingState {}).unwrap()
+        )
+        .unwrap(),
+        VestingState {
+            total_granted: Uint128::new(180),
+            total_released: Uint128::new(50),
+        },
+    );
+    assert_eq!(
+        from_json::<Uint128>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::AvailableAmount {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        Uint128::new(10),
+    );
+
+    // the amendment is visible starting from the next height
+    let query_unclaimed_amount = |height: u64| {
+        from_json::<Uint128>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::HistoricalExtension {
+                    msg: QueryMsgHistorical::UnclaimedAmountAtHeight {
+                        address: String::from("user"),
+                        height,
+                    },
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(query_unclaimed_amount(env.block.height), Uint128::new(50));
+    assert_eq!(
+        query_unclaimed_amount(env.block.height + 1),
+        Uint128::new(130)
+    );
+}
+
+#[test]
+fn vesting_templates() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let template = VestingScheduleTemplate {
+        start_share: Decimal::percent(10),
+        duration: 1000,
+        cliff: Some(100),
+        asset_info: None,
+        voting_weight: None,
+    };
+    let set_template_msg =
+        |template_id: &str, template: VestingScheduleTemplate| ExecuteMsg::SetVestingTemplate {
+            template_id: String::from(template_id),
+            template,
+        };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &[]),
+            set_template_msg("team", template.clone()),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            set_template_msg(
+                "team",
+                VestingScheduleTemplate {
+                    cliff: Some(1001),
+                    ..template.clone()
+                }
+            ),
+        )
+        .unwrap_err(),
+        ContractError::VestingTemplateError(String::from("team")),
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            set_template_msg(
+                "team",
+                VestingScheduleTemplate {
+                    start_share: Decimal::percent(101),
+                    ..template.clone()
+                }
+            ),
+        )
+        .unwrap_err(),
+        ContractError::VestingTemplateError(String::from("team")),
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            set_template_msg(
+                "team",
+                VestingScheduleTemplate {
+                    start_share: Decimal::one(),
+                    ..template.clone()
+                }
+            ),
+        )
+        .unwrap_err(),
+        ContractError::VestingTemplateError(String::from("team")),
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        set_template_msg("team", template.clone()),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        set_template_msg(
+            "airdrop",
+            VestingScheduleTemplate {
+                start_share: Decimal::one(),
+                duration: 0,
+                cliff: None,
+                asset_info: None,
+                voting_weight: None,
+            },
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(
+        from_json::<VestingScheduleTemplate>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingTemplate {
+                    template_id: String::from("team"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        template,
+    );
+    assert_eq!(
+        from_json::<Vec<VestingTemplateResponse>>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingTemplates {
+                    start_after: Some(String::from("airdrop")),
+                    limit: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        vec![VestingTemplateResponse {
+            template_id: String::from("team"),
+            template: template.clone(),
+        }],
+    );
+
+    let template_account =
+        |address: &str, template_id: &str, start_time: Option<u64>| TemplateVestingAccount {
+            address: String::from(address),
+            template_id: String::from(template_id),
+            amount: Uint128::new(100),
+            start_time,
+        };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(100, "untrn")),
+            ExecuteMsg::RegisterTemplateVestingAccounts {
+                vesting_accounts: vec![template_account("user1", "advisors", None)],
+            },
+        )
+        .unwrap_err(),
+        ContractError::VestingTemplateNotFound(String::from("advisors")),
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(200, "untrn")),
+        ExecuteMsg::RegisterTemplateVestingAccounts {
+            vesting_accounts: vec![
+                template_account("user1", "team", Some(100)),
+                template_account("user2", "airdrop", None),
+            ],
+        },
+    )
+    .unwrap();
+
+    let query_schedules = |address: &str| {
+        from_json::<VestingAccountResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingAccount {
+                    address: String::from(address),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .info
+        .schedules
+    };
+    assert_eq!(
+        query_schedules("user1"),
+        vec![VestingSchedule {
+            start_point: VestingSchedulePoint {
+                time: 100,
+                amount: Uint128::new(10),
+            },
+            end_point: Some(VestingSchedulePoint {
+                time: 1100,
+                amount: Uint128::new(100),
+            }),
+            cliff: Some(200),
+            ..Default::default()
+        }],
+    );
+    assert_eq!(
+        query_schedules("user2"),
+        vec![VestingSchedule {
+            start_point: VestingSchedulePoint {
+                time: env.block.time.seconds(),
+                amount: Uint128::new(100),
+            },
+            ..Default::default()
+        }],
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::RemoveVestingTemplate {
+            template_id: String::from("team"),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            ExecuteMsg::RemoveVestingTemplate {
+                template_id: String::from("team"),
+            },
+        )
+        .unwrap_err(),
+        ContractError::VestingTemplateNotFound(String::from("team")),
+    );
+    // registered accounts keep their schedules
+    assert_eq!(query_schedules("user1").len(), 1);
+}
+
+#[test]
+fn merkle_root_registration() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingTemplate {
+            template_id: String::from("team"),
+            template: VestingScheduleTemplate {
+                start_share: Decimal::zero(),
+                duration: 1000,
+                cliff: None,
+                asset_info: None,
+                voting_weight: None,
+            },
+        },
+    )
+    .unwrap();
+
+    // a tree of two leaves, the proof of each leaf is the hash of the other one
+    let leaf = |address: &str, amount: u128| {
+        let mut data = vec![];
+        for field in [address, "team"] {
+            data.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            data.extend_from_slice(field.as_bytes());
+        }
+        data.extend_from_slice(&amount.to_be_bytes());
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&Sha256::digest(data));
+        hash
+    };
+    let (leaf1, leaf2) = (leaf("user1", 100), leaf("user2", 200));
+    let mut leaves = [leaf1, leaf2];
+    leaves.sort_unstable();
+    let merkle_root = hex::encode(Sha256::digest(leaves.concat()));
+
+    let expiration = env.block.time.seconds() + 1000;
+    let register_root_msg = |merkle_root: &str| ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: String::from(merkle_root),
+        template_ids: vec![String::from("team")],
+        start_time: None,
+        expiration,
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user1", &coins(300, "untrn")),
+            register_root_msg(&merkle_root),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(300, "untrn")),
+            register_root_msg("root"),
+        )
+        .unwrap_err(),
+        ContractError::MerkleRootError {},
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(300, "untrn")),
+            ExecuteMsg::RegisterMerkleRoot {
+                merkle_root: merkle_root.clone(),
+                template_ids: vec![String::from("team")],
+                start_time: None,
+                expiration: env.block.time.seconds(),
+            },
+        )
+        .unwrap_err(),
+        ContractError::MerkleRootExpirationError {},
+    );
+    // the deposit has to be the asset vested by the templates
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(300, "uatom")),
+            register_root_msg(&merkle_root),
+        )
+        .unwrap_err(),
+        ContractError::VestingScheduleAssetError {},
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(300, "untrn")),
+        register_root_msg(&merkle_root),
+    )
+    .unwrap();
+
+    // the root keeps the template it was registered with
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingTemplate {
+            template_id: String::from("team"),
+            template: VestingScheduleTemplate {
+                start_share: Decimal::zero(),
+                duration: 100_000,
+                cliff: None,
+                asset_info: None,
+                voting_weight: None,
+            },
+        },
+    )
+    .unwrap();
+
+    let claim_msg = |amount: u128, proof: &[u8; 32]| ExecuteMsg::ClaimWithMerkleProof {
+        root_id: 0,
+        template_id: String::from("team"),
+        amount: Uint128::new(amount),
+        proof: vec![hex::encode(proof)],
+        recipient: None,
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user1", &[]),
+            claim_msg(200, &leaf2),
+        )
+        .unwrap_err(),
+        ContractError::MerkleProofError(String::from("user1")),
+    );
+
+    // the schedule starts at the registration of the root
+    env.block.time = env.block.time.plus_seconds(500);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        claim_msg(100, &leaf2),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("user1"),
+            amount: coins(50, "untrn"),
+        }),
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user1", &[]),
+            claim_msg(100, &leaf2),
+        )
+        .unwrap_err(),
+        ContractError::MerkleRootAlreadyRegistered(String::from("user1")),
+    );
+
+    let query_is_registered = |address: &str| {
+        from_json::<bool>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::IsMerkleRegistered {
+                    root_id: 0,
+                    address: String::from(address),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert!(query_is_registered("user1"));
+    assert!(!query_is_registered("user2"));
+    assert_eq!(
+        from_json::<MerkleRoot>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::MerkleRoot { root_id: 0 },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        MerkleRoot {
+            merkle_root,
+            templates: vec![VestingTemplateResponse {
+                template_id: String::from("team"),
+                template: VestingScheduleTemplate {
+                    start_share: Decimal::zero(),
+                    duration: 1000,
+                    cliff: None,
+                    asset_info: None,
+                    voting_weight: None,
+                },
+            }],
+            start_time: mock_env().block.time.seconds(),
+            expiration,
+            asset_info: native_asset_info(String::from("untrn")),
+            total_amount: Uint128::new(300),
+            registered_amount: Uint128::new(100),
+        },
+    );
+    assert_eq!(
+        from_json::<VestingState>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingState {}).unwrap()
+        )
+        .unwrap(),
+        VestingState {
+            total_granted: Uint128::new(100),
+            total_released: Uint128::new(50),
+        },
+    );
+
+    // the unregistered part of the deposit can be withdrawn once the root expires
+    let withdraw_msg = ExecuteMsg::WithdrawMerkleRootRemainder {
+        root_id: 0,
+        recipient: Some(String::from("treasury")),
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap_err(),
+        ContractError::MerkleRootNotExpired(0),
+    );
+    env.block.time = env.block.time.plus_seconds(500);
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user2", &[]),
+            claim_msg(200, &leaf1),
+        )
+        .unwrap_err(),
+        ContractError::MerkleRootExpired(0),
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user2", &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        withdraw_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("treasury"),
+            amount: coins(200, "untrn"),
+        }),
+    );
+    assert_eq!(
+        execute(deps.as_mut(), env, mock_info("owner", &[]), withdraw_msg).unwrap_err(),
+        ContractError::AmountIsNotAvailable {},
+    );
+}
+
+#[test]
+fn vesting_state_reconciliation() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .historical()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let vesting_account = |address: &str, amount: u128| VestingAccount {
+        address: String::from(address),
+        schedules: vec![VestingSchedule {
+            start_point: VestingSchedulePoint {
+                time: 100,
+                amount: Uint128::new(amount),
+            },
+            ..Default::default()
+        }],
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(300, "untrn")),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![vesting_account("user1", 100), vesting_account("user2", 200)],
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        ExecuteMsg::Claim {
+            recipient: None,
+            amount: Some(Uint128::new(40)),
+        },
+    )
+    .unwrap();
+
+    let query_reconciliation = |deps: Deps, start_after: Option<&str>| {
+        from_json::<VestingStateReconciliationResponse>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::VestingStateReconciliation {
+                    start_after: start_after.map(String::from),
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let vesting_state = VestingState {
+        total_granted: Uint128::new(300),
+        total_released: Uint128::new(40),
+    };
+    assert_eq!(
+        query_reconciliation(deps.as_ref(), None),
+        VestingStateReconciliationResponse {
+            vesting_state: vesting_state.clone(),
+            granted_amount: Uint128::new(100),
+            released_amount: Uint128::new(40),
+            last_address: Some(Addr::unchecked("user1")),
+        },
+    );
+    assert_eq!(
+        query_reconciliation(deps.as_ref(), Some("user1")),
+        VestingStateReconciliationResponse {
+            vesting_state,
+            granted_amount: Uint128::new(200),
+            released_amount: Uint128::zero(),
+            last_address: Some(Addr::unchecked("user2")),
+        },
+    );
+
+    // the account leaves the contract with its granted and released amounts
+    let user1_info = VestingInfo {
+        schedules: vesting_account("user1", 100).schedules,
+        released_amount: Uint128::new(40),
+        released_assets: vec![],
+        slashed_amount: Uint128::zero(),
+    };
+    env.block.height += 1;
+    assert_eq!(
+        clear_vesting_account(
+            deps.as_mut().storage,
+            Addr::unchecked("user1"),
+            &VestingInfo {
+                released_amount: Uint128::zero(),
+                ..user1_info.clone()
+            },
+            env.block.height,
+        )
+        .unwrap_err(),
+        ContractError::VestingStateInvariantError(String::from("user1")),
+    );
+    clear_vesting_account(
+        deps.as_mut().storage,
+        Addr::unchecked("user1"),
+        &user1_info,
+        env.block.height,
+    )
+    .unwrap();
+    assert_eq!(
+        query_reconciliation(deps.as_ref(), None).vesting_state,
+        VestingState {
+            total_granted: Uint128::new(200),
+            total_released: Uint128::zero(),
+        },
+    );
+
+    let repair_msg = |total_granted: u128, total_released: u128| ExecuteMsg::RepairVestingState {
+        total_granted: Uint128::new(total_granted),
+        total_released: Uint128::new(total_released),
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user2", &[]),
+            repair_msg(200, 0),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            repair_msg(200, 201),
+        )
+        .unwrap_err(),
+        ContractError::VestingStateRepairError {},
+    );
+    env.block.height += 1;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        repair_msg(250, 50),
+    )
+    .unwrap();
+    assert_eq!(
+        query_reconciliation(deps.as_ref(), None).vesting_state,
+        VestingState {
+            total_granted: Uint128::new(250),
+            total_released: Uint128::new(50),
+        },
+    );
+}
+
+#[test]
+fn pausable() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    VestingBaseBuilder::default()
+        .pausable(Some(String::from("guardian")))
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+
+    let schedule = VestingSchedule {
+        start_point: VestingSchedulePoint {
+            time: 100,
+            amount: Uint128::zero(),
+        },
+        end_point: Some(VestingSchedulePoint {
+            time: 200,
+            amount: Uint128::new(100),
+        }),
+        ..Default::default()
+    };
+    let register_msg = |address: &str| ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: String::from(address),
+            schedules: vec![schedule.clone()],
+        }],
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &coins(100, "untrn")),
+        register_msg("user"),
+    )
+    .unwrap();
+
+    let pausable_msg = |msg: ExecuteMsgPausable| ExecuteMsg::PausableExtension { msg };
+    let query_is_paused = |deps: Deps| {
+        from_json::<IsPausedResponse>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::PausableExtension {
+                    msg: QueryMsgPausable::IsPaused {},
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .is_paused
+    };
+    assert!(!query_is_paused(deps.as_ref()));
+
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &[]),
+            pausable_msg(ExecuteMsgPausable::Pause {}),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("guardian", &[]),
+        pausable_msg(ExecuteMsgPausable::Pause {}),
+    )
+    .unwrap();
+    assert!(query_is_paused(deps.as_ref()));
+
+    // claims and registrations are frozen, queries are still available
+    env.block.time = Timestamp::from_seconds(150);
+    let claim_msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("user", &[]),
+            claim_msg.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Paused {},
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &coins(100, "untrn")),
+            register_msg("user2"),
+        )
+        .unwrap_err(),
+        ContractError::Paused {},
+    );
+    assert_eq!(
+        from_json::<Uint128>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::AvailableAmount {
+                    address: String::from("user"),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        Uint128::new(50),
+    );
+
+    // only the owner can change the guardian
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guardian", &[]),
+            pausable_msg(ExecuteMsgPausable::SetGuardian { guardian: None }),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        pausable_msg(ExecuteMsgPausable::SetGuardian { guardian: None }),
+    )
+    .unwrap();
+    assert_eq!(
+        from_json::<Option<Addr>>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::PausableExtension {
+                    msg: QueryMsgPausable::Guardian {},
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        None,
+    );
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("guardian", &[]),
+            pausable_msg(ExecuteMsgPausable::Resume {}),
+        )
+        .unwrap_err(),
+        ContractError::Unauthorized {},
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        pausable_msg(ExecuteMsgPausable::Resume {}),
+    )
+    .unwrap();
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            pausable_msg(ExecuteMsgPausable::Resume {}),
+        )
+        .unwrap_err(),
+        ContractError::NotPaused {},
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user", &[]),
+        claim_msg,
+    )
+    .unwrap();
+}
+
+#[test]
+fn pausable_not_enabled() {
+    let mut deps = mock_dependencies();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::PausableExtension {
+                msg: ExecuteMsgPausable::Pause {},
+            },
+        )
+        .unwrap_err(),
+        ContractError::Std(ext_unsupported_err("pausable")),
+    );
+}
+
+#[test]
+fn versioned_migration() {
+    let mut deps = mock_dependencies();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    set_contract_version(&mut deps.storage, "vesting", "1.0.0").unwrap();
+
+    let steps = RefCell::new(vec![]);
+    let migrate = |deps: DepsMut, version: &str, extensions: ExtensionsMigration| {
+        let mut builder = VestingMigrationBuilder::new("vesting", version);
+        for step_version in ["1.2.0", "1.0.0", "1.1.0"] {
+            let steps = &steps;
+            builder.step(step_version, move |_, _| {
+                steps.borrow_mut().push(step_version);
+                Ok(())
+            });
+        }
+        builder.migrate(deps, mock_env(), extensions)
+    };
+
+    // the steps newer than the stored version run in the order of their versions
+    migrate(
+        deps.as_mut(),
+        "1.2.0",
+        ExtensionsMigration {
+            managed: Some(true),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(steps.take(), vec!["1.1.0", "1.2.0"]);
+    assert_eq!(
+        from_json::<ContractVersion>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::ContractVersion {}).unwrap()
+        )
+        .unwrap(),
+        ContractVersion {
+            contract: String::from("vesting"),
+            version: String::from("1.2.0"),
+        },
+    );
+    let config =
+        from_json::<Config>(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap())
+            .unwrap();
+    assert!(config.extensions.managed);
+    assert!(!config.extensions.transferable);
+
+    // running the same migration again doesn't repeat the steps
+    migrate(deps.as_mut(), "1.2.0", ExtensionsMigration::default()).unwrap();
+    assert!(steps.take().is_empty());
+    assert_eq!(
+        migrate(deps.as_mut(), "1.1.0", ExtensionsMigration::default()).unwrap_err(),
+        ContractError::MigrationDowngradeError {
+            from: String::from("1.2.0"),
+            to: String::from("1.1.0"),
+        },
+    );
+    assert_eq!(
+        VestingMigrationBuilder::new("another", "1.3.0")
+            .migrate(deps.as_mut(), mock_env(), ExtensionsMigration::default())
+            .unwrap_err(),
+        ContractError::MigrationContractError {
+            expected: String::from("another"),
+            actual: String::from("vesting"),
+        },
+    );
+    assert!(steps.take().is_empty());
+}
+
+#[test]
+fn migration_disabling_pausable() {
+    let mut deps = mock_dependencies();
+    VestingBaseBuilder::default()
+        .pausable(Some(String::from("guardian")))
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::SetVestingToken {
+            vesting_token: native_asset_info(String::from("untrn")),
+        },
+    )
+    .unwrap();
+    set_contract_version(&mut deps.storage, "vesting", "1.0.0").unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        ExecuteMsg::PausableExtension {
+            msg: ExecuteMsgPausable::Pause {},
+        },
+    )
+    .unwrap();
+    let register_msg = ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: String::from("user"),
+            schedules: vec![VestingSchedule {
+                start_point: VestingSchedulePoint {
+                    time: 100,
+                    amount: Uint128::new(100),
+                },
+                ..Default::default()
+            }],
+        }],
+    };
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &coins(100, "untrn")),
+            register_msg.clone(),
+        )
+        .unwrap_err(),
+        ContractError::Paused {},
+    );
+
+    // disabling the extension resumes the contract, since it can't be resumed anymore
+    VestingMigrationBuilder::new("vesting", "1.1.0")
+        .migrate(
+            deps.as_mut(),
+            mock_env(),
+            ExtensionsMigration {
+                pausable: Some(false),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &coins(100, "untrn")),
+        register_msg,
+    )
+    .unwrap();
+
+    // enabling the extension again doesn't bring back the pause or the guardian
+    VestingMigrationBuilder::new("vesting", "1.2.0")
+        .migrate(
+            deps.as_mut(),
+            mock_env(),
+            ExtensionsMigration {
+                pausable: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let is_paused = from_json::<IsPausedResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PausableExtension {
+                msg: QueryMsgPausable::IsPaused {},
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(!is_paused.is_paused);
+    let guardian = from_json::<Option<Addr>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PausableExtension {
+                msg: QueryMsgPausable::Guardian {},
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(guardian, None);
+}
+
+#[test]
+fn migration_enabling_voting() {
+    let mut deps = mock_dependencies();
+    VestingBaseBuilder::default()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    set_contract_version(&mut deps.storage, "vesting", "1.0.0").unwrap();
+    let extensions = ExtensionsMigration {
+        voting: Some(String::from("dao")),
+        ..Default::default()
+    };
+
+    // the voting extension can't be enabled without the historical one
+    assert_eq!(
+        VestingMigrationBuilder::new("vesting", "1.1.0")
+            .migrate(deps.as_mut(), mock_env(), extensions.clone())
+            .unwrap_err(),
+        ContractError::Std(StdError::generic_err(
+            "The voting extension requires the historical extension",
+        )),
+    );
+
+    let mut deps = mock_dependencies();
+    VestingBaseBuilder::default()
+        .historical()
+        .build(
+            deps.as_mut(),
+            String::from("owner"),
+            String::from("token_info_manager"),
+        )
+        .unwrap();
+    set_contract_version(&mut deps.storage, "vesting", "1.0.0").unwrap();
+    assert_eq!(
+        query(deps.as_ref(), mock_env(), QueryMsg::Dao {}).unwrap_err(),
+        ext_unsupported_err("voting"),
+    );
+
+    VestingMigrationBuilder::new("vesting", "1.1.0")
+        .migrate(deps.as_mut(), mock_env(), extensions)
+        .unwrap();
+    assert_eq!(
+        from_json::<Addr>(&query(deps.as_ref(), mock_env(), QueryMsg::Dao {}).unwrap()).unwrap(),
+        Addr::unchecked("dao"),
+    );
+    assert_eq!(
+        from_json::<TotalPowerAtHeightResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::TotalPowerAtHeight { height: None },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .power,
+        Uint128::zero(),
+    );
+}
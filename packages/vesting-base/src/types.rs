@@ -1,6 +1,8 @@
-use astroport::asset::AssetInfo;
+use astroport::asset::{Asset, AssetInfo, AssetInfoExt};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Order, Uint128};
+use cosmwasm_std::{Addr, Decimal, Order, StdResult, Uint128};
+use cw2::ContractVersion;
+use cw_utils::Expiration;
 
 /// This structure stores the main parameters for the generator vesting contract.
 #[cw_serde]
@@ -24,6 +26,38 @@ pub struct Extensions {
     pub managed: bool,
     /// Whether the with_managers extension is enabled for the contract.
     pub with_managers: bool,
+    /// Whether the transferable extension is enabled for the contract.
+    #[serde(default)]
+    pub transferable: bool,
+    /// Whether the with_operators extension is enabled for the contract.
+    #[serde(default)]
+    pub with_operators: bool,
+    /// Whether the voting extension is enabled for the contract.
+    #[serde(default)]
+    pub voting: bool,
+    /// Whether the staking extension is enabled for the contract.
+    #[serde(default)]
+    pub staking: bool,
+    /// Whether the pausable extension is enabled for the contract.
+    #[serde(default)]
+    pub pausable: bool,
+}
+
+/// This structure describes the extensions to enable or disable with a migration. The extensions
+/// left unset keep their current state. The historical and staking extensions can't be changed,
+/// as their state is only initialised by the builder.
+#[cw_serde]
+#[derive(Default)]
+pub struct ExtensionsMigration {
+    pub managed: Option<bool>,
+    pub with_managers: Option<bool>,
+    pub transferable: Option<bool>,
+    pub with_operators: Option<bool>,
+    pub pausable: Option<bool>,
+    /// The DAO to provide voting power to. Enables the voting extension, which requires the
+    /// historical extension
+    #[serde(default)]
+    pub voting: Option<String>,
 }
 
 /// This structure stores the accumulated vesting information for all addresses.
@@ -32,10 +66,36 @@ pub struct Extensions {
 pub struct VestingState {
     /// The total amount of tokens granted to the users
     pub total_granted: Uint128,
-    /// The total amount of tokens already claimed
+    /// The total amount of tokens already claimed or lost to slashing
     pub total_released: Uint128,
 }
 
+/// This structure describes a custom struct used to reconcile the vesting state of the vesting
+/// token with a page of vesting accounts. Summing up the amounts of all the pages should give the
+/// totals of the vesting state.
+#[cw_serde]
+pub struct VestingStateReconciliationResponse {
+    /// The current vesting state of the vesting token
+    pub vesting_state: VestingState,
+    /// The amount of tokens granted to the accounts of the page
+    pub granted_amount: Uint128,
+    /// The amount of tokens released to the accounts of the page or lost to slashing
+    pub released_amount: Uint128,
+    /// The last address of the page. `None` means there are no more accounts
+    pub last_address: Option<Addr>,
+}
+
+/// This structure stores the accumulated vesting information of the accounts that have weighted
+/// vesting schedules.
+#[cw_serde]
+#[derive(Default)]
+pub struct WeightedVotingState {
+    /// The total amount of unclaimed tokens of the accounts
+    pub unclaimed_amount: Uint128,
+    /// The total voting power of the accounts
+    pub voting_power: Uint128,
+}
+
 /// This structure stores vesting information for a specific address that is getting tokens.
 #[cw_serde]
 pub struct VestingAccount {
@@ -45,6 +105,29 @@ pub struct VestingAccount {
     pub schedules: Vec<VestingSchedule>,
 }
 
+/// This structure describes a vesting account registered with a vesting schedule template.
+#[cw_serde]
+pub struct TemplateVestingAccount {
+    /// The address that is getting tokens
+    pub address: String,
+    /// The name of the template the vesting schedule is computed from
+    pub template_id: String,
+    /// The total amount of tokens vested by the schedule
+    pub amount: Uint128,
+    /// The start time of the vesting schedule. Defaults to the current block time
+    pub start_time: Option<u64>,
+}
+
+/// This structure stores the accumulated vesting information of an asset vested besides the
+/// vesting token.
+#[cw_serde]
+pub struct VestingAssetState {
+    /// The vested asset
+    pub asset_info: AssetInfo,
+    /// The accumulated vesting information of the asset
+    pub state: VestingState,
+}
+
 /// This structure stores parameters for a batch of vesting schedules.
 #[cw_serde]
 pub struct VestingInfo {
@@ -52,19 +135,170 @@ pub struct VestingInfo {
     pub schedules: Vec<VestingSchedule>,
     /// The total amount of vested tokens already claimed
     pub released_amount: Uint128,
+    /// The total amounts of the assets vested besides the vesting token already claimed
+    #[serde(default)]
+    pub released_assets: Vec<Asset>,
+    /// The total amount of delegated vesting tokens lost to validator slashing
+    #[serde(default)]
+    pub slashed_amount: Uint128,
+}
+
+impl VestingInfo {
+    /// Returns the assets vested by the schedules besides the vesting token, in the order
+    /// they first appear in the schedules.
+    pub fn additional_asset_infos(&self) -> Vec<AssetInfo> {
+        let mut asset_infos: Vec<AssetInfo> = vec![];
+        for asset_info in self
+            .schedules
+            .iter()
+            .filter_map(|sch| sch.asset_info.as_ref())
+        {
+            if !asset_infos.contains(asset_info) {
+                asset_infos.push(asset_info.clone());
+            }
+        }
+        asset_infos
+    }
+
+    /// Returns the claimed amount of an asset. `None` stands for the vesting token.
+    pub fn released_amount_of(&self, asset_info: Option<&AssetInfo>) -> Uint128 {
+        match asset_info {
+            None => self.released_amount,
+            Some(asset_info) => self
+                .released_assets
+                .iter()
+                .find(|asset| &asset.info == asset_info)
+                .map_or(Uint128::zero(), |asset| asset.amount),
+        }
+    }
+
+    /// Returns the amount of an asset that has left the account, claimed or lost to slashing.
+    /// Only the vesting token (`None`) can be slashed.
+    pub fn spent_amount_of(&self, asset_info: Option<&AssetInfo>) -> StdResult<Uint128> {
+        let released_amount = self.released_amount_of(asset_info);
+        match asset_info {
+            None => Ok(released_amount.checked_add(self.slashed_amount)?),
+            Some(_) => Ok(released_amount),
+        }
+    }
+
+    /// Increases the claimed amount of an asset. `None` stands for the vesting token.
+    pub fn add_released_amount(
+        &mut self,
+        asset_info: Option<&AssetInfo>,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        match asset_info {
+            None => self.released_amount = self.released_amount.checked_add(amount)?,
+            Some(asset_info) => {
+                match self
+                    .released_assets
+                    .iter_mut()
+                    .find(|asset| &asset.info == asset_info)
+                {
+                    Some(asset) => asset.amount = asset.amount.checked_add(amount)?,
+                    None => self.released_assets.push(asset_info.with_balance(amount)),
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// This structure stores parameters for a specific vesting schedule
 #[cw_serde]
+#[derive(Default)]
 pub struct VestingSchedule {
     /// The start date for the vesting schedule
     pub start_point: VestingSchedulePoint,
     /// The end point for the vesting schedule
     pub end_point: Option<VestingSchedulePoint>,
+    /// The cliff time for the vesting schedule. Nothing is unlocked before this moment, then the
+    /// amount accrued since the start point is unlocked at once
+    pub cliff: Option<u64>,
+    /// The points between the start and the end points of the vesting schedule, ordered by time.
+    /// The tokens are vested linearly between every two adjacent points, and two points with the
+    /// same time make a step
+    pub intermediate_points: Option<Vec<VestingSchedulePoint>>,
+    /// The tranches unlocked after the start point. A periodic vesting schedule has no end point
+    pub periodic: Option<VestingSchedulePeriodic>,
+    /// The asset vested by the schedule. Defaults to the vesting token of the contract
+    pub asset_info: Option<AssetInfo>,
+    /// The voting power of every unclaimed token of the schedule. Defaults to one. Only the
+    /// schedules vesting the vesting token carry voting power
+    pub voting_weight: Option<Decimal>,
+}
+
+/// This structure stores the shape of the vesting schedules registered with a template. The
+/// times are relative to the start time of a registered schedule, and the schedule vests
+/// linearly from the start to the end of its duration.
+#[cw_serde]
+pub struct VestingScheduleTemplate {
+    /// The share of the amount unlocked at the start time
+    pub start_share: Decimal,
+    /// The duration of the vesting in seconds
+    pub duration: u64,
+    /// The cliff in seconds after the start time
+    pub cliff: Option<u64>,
+    /// The asset vested by the schedules. Defaults to the vesting token of the contract
+    pub asset_info: Option<AssetInfo>,
+    /// The voting power of every unclaimed token of the schedules. Defaults to one
+    pub voting_weight: Option<Decimal>,
+}
+
+/// This structure describes a custom struct used to return a vesting schedule template.
+#[cw_serde]
+pub struct VestingTemplateResponse {
+    /// The name of the template
+    pub template_id: String,
+    /// The shape of the vesting schedules registered with the template
+    pub template: VestingScheduleTemplate,
+}
+
+/// This structure stores a funded Merkle root of vesting accounts. Every leaf of the tree
+/// describes a vesting account as `(address, template_id, amount)`, and the beneficiaries
+/// register their own accounts by submitting a proof until the root expires.
+#[cw_serde]
+pub struct MerkleRoot {
+    /// The hex-encoded root of the Merkle tree
+    pub merkle_root: String,
+    /// The templates used by the leaves, copied at the registration of the root
+    pub templates: Vec<VestingTemplateResponse>,
+    /// The start time of the vesting schedules registered from the root
+    pub start_time: u64,
+    /// The time after which the accounts can't be registered from the root anymore
+    pub expiration: u64,
+    /// The asset that funds the vesting accounts
+    pub asset_info: AssetInfo,
+    /// The total amount of tokens deposited with the root
+    pub total_amount: Uint128,
+    /// The amount of tokens already registered by the beneficiaries
+    pub registered_amount: Uint128,
+}
+
+/// This structure describes a custom struct used to return a Merkle root of vesting accounts.
+#[cw_serde]
+pub struct MerkleRootResponse {
+    /// The identifier of the root
+    pub root_id: u64,
+    /// The Merkle root of vesting accounts
+    pub root: MerkleRoot,
+}
+
+/// This structure stores the parameters used to unlock a vesting schedule in tranches.
+#[cw_serde]
+pub struct VestingSchedulePeriodic {
+    /// The duration of a single period in seconds
+    pub period: u64,
+    /// The number of periods
+    pub num_periods: u64,
+    /// The amount of tokens unlocked at the end of every period
+    pub amount_per_period: Uint128,
 }
 
 /// This structure stores the parameters used to create a vesting schedule.
 #[cw_serde]
+#[derive(Default)]
 pub struct VestingSchedulePoint {
     /// The start time for the vesting schedule
     pub time: u64,
@@ -72,6 +306,24 @@ pub struct VestingSchedulePoint {
     pub amount: Uint128,
 }
 
+/// This structure stores the parameters of a scheduled termination of a vesting account.
+#[cw_serde]
+pub struct VestingTermination {
+    /// The time after which the tokens of the vesting account stop vesting
+    pub termination_time: u64,
+    /// The address that receives the tokens that haven't vested before the termination
+    pub clawback_account: Addr,
+}
+
+/// This structure stores the parameters of an operator's approval to claim tokens on behalf of a vesting account.
+#[cw_serde]
+pub struct OperatorApproval {
+    /// The moment after which the approval is no longer valid
+    pub expires: Expiration,
+    /// The address besides the vesting account the operator is allowed to send the claimed tokens to
+    pub recipient: Option<Addr>,
+}
+
 /// This structure describes a custom struct used to return vesting data about a specific vesting target.
 #[cw_serde]
 pub struct VestingAccountResponse {
@@ -90,6 +342,72 @@ pub struct VestingAccountFullInfo {
     pub info: VestingInfo,
 }
 
+/// This structure describes a custom struct used to return a scheduled termination of a specific vesting target.
+#[cw_serde]
+pub struct VestingTerminationResponse {
+    /// The address that's vesting tokens
+    pub address: Addr,
+    /// Termination parameters
+    pub termination: VestingTermination,
+}
+
+/// This structure describes a custom struct used to return an approval of a specific operator.
+#[cw_serde]
+pub struct OperatorApprovalResponse {
+    /// The address that's allowed to claim tokens
+    pub operator: Addr,
+    /// Approval parameters
+    pub approval: OperatorApproval,
+}
+
+/// This structure describes a custom struct used to return the amount of tokens unlocked for a vesting target at a specific time.
+#[cw_serde]
+pub struct VestingTimelinePoint {
+    /// The time of the sample point
+    pub time: u64,
+    /// The cumulative amount of tokens unlocked at the time, including the already claimed ones
+    pub unlocked_amount: Uint128,
+}
+
+/// This structure describes a custom struct used to return the amount of tokens unlocking across a page of vesting targets.
+#[cw_serde]
+pub struct UnlockForecastResponse {
+    /// The total amount of tokens unlocking between the requested timestamps
+    pub amount: Uint128,
+    /// The last vesting target of the page, used as `start_after` to query the next page.
+    /// Equals `None` when the page is empty
+    pub last_address: Option<Addr>,
+}
+
+/// This structure describes the unclaimed amount of tokens of a vesting target at certain height.
+#[cw_serde]
+pub struct UnclaimedAmountResponse {
+    /// The address that's vesting tokens
+    pub address: Addr,
+    /// The unclaimed amount of tokens
+    pub amount: Uint128,
+}
+
+/// This structure describes a custom struct used to return the unclaimed amounts of tokens of a
+/// page of vesting targets at certain height.
+#[cw_serde]
+pub struct UnclaimedAmountsAtHeightResponse {
+    /// The vesting targets that had vesting information at the requested height
+    pub accounts: Vec<UnclaimedAmountResponse>,
+    /// The last vesting target of the page, used as `start_after` to query the next page.
+    /// Equals `None` when there are no more vesting targets
+    pub last_address: Option<Addr>,
+}
+
+/// This structure describes a change of the vesting information of a vesting target.
+#[cw_serde]
+pub struct VestingInfoHistoryEntry {
+    /// The height the change was made at. The change is visible to the queries at later heights
+    pub height: u64,
+    /// The vesting information after the change. Equals `None` if the information was removed
+    pub info: Option<VestingInfo>,
+}
+
 /// This structure describes a custom struct used to return vesting data for multiple vesting targets.
 #[cw_serde]
 pub struct VestingAccountsResponse {
@@ -116,3 +434,99 @@ impl Into<Order> for OrderBy {
         }
     }
 }
+
+/// This structure describes the voting power of an address at a certain height.
+#[cw_serde]
+pub struct VotingPowerAtHeightResponse {
+    /// The voting power
+    pub power: Uint128,
+    /// The height at which the voting power is computed
+    pub height: u64,
+}
+
+/// This structure describes the total voting power at a certain height.
+#[cw_serde]
+pub struct TotalPowerAtHeightResponse {
+    /// The total voting power
+    pub power: Uint128,
+    /// The height at which the total voting power is computed
+    pub height: u64,
+}
+
+/// This structure describes the contract information returned to the DAO.
+#[cw_serde]
+pub struct InfoResponse {
+    /// The contract name and version
+    pub info: ContractVersion,
+}
+
+/// This structure stores the tokens a vesting account has delegated to a validator.
+#[cw_serde]
+pub struct StakingDelegation {
+    /// The amount of delegated tokens
+    pub amount: Uint128,
+    /// The reward index of the validator at the last reward distribution to the account
+    pub reward_index: Decimal,
+    /// The slash index of the validator at the last update of the delegation
+    pub slash_index: Decimal,
+}
+
+/// This structure stores the tokens all the vesting accounts have delegated to a validator.
+#[cw_serde]
+pub struct StakingValidator {
+    /// The total amount of delegated tokens
+    pub amount: Uint128,
+    /// The accumulated amount of rewards per delegated token
+    pub reward_index: Decimal,
+    /// The share of the delegated tokens left after all the slashings of the validator
+    pub slash_index: Decimal,
+}
+
+impl Default for StakingValidator {
+    fn default() -> Self {
+        StakingValidator {
+            amount: Uint128::zero(),
+            reward_index: Decimal::zero(),
+            slash_index: Decimal::one(),
+        }
+    }
+}
+
+/// This structure stores an undelegation of a vesting account.
+#[cw_serde]
+pub struct StakingUnbonding {
+    /// The amount of undelegated tokens
+    pub amount: Uint128,
+    /// The time the tokens become claimable at
+    pub release_time: u64,
+    /// The validator the tokens were undelegated from
+    pub validator: String,
+    /// The slash index of the validator at the last update of the unbonding
+    pub slash_index: Decimal,
+}
+
+/// This structure describes the tokens a vesting account has delegated to a validator.
+#[cw_serde]
+pub struct StakingDelegationResponse {
+    /// The validator address
+    pub validator: String,
+    /// The amount of delegated tokens
+    pub amount: Uint128,
+}
+
+/// This structure describes a custom struct used to return whether the contract is paused.
+#[cw_serde]
+pub struct IsPausedResponse {
+    pub is_paused: bool,
+}
+
+/// This structure describes the staking position of a vesting account.
+#[cw_serde]
+pub struct StakingInfoResponse {
+    /// The delegations of the account
+    pub delegations: Vec<StakingDelegationResponse>,
+    /// The undelegations of the account that are still unbonding
+    pub unbondings: Vec<StakingUnbonding>,
+    /// The staking rewards accrued by the account and not yet sent to it
+    pub pending_rewards: Uint128,
+}